@@ -11,15 +11,51 @@ struct Cli {
 
     #[arg(short, long, value_name = "OUTPUT_FILE")]
     output: Option<PathBuf>,
+
+    /// Disassemble an assembled file back into HAND
+    #[arg(short, long)]
+    disassemble: bool,
 }
 
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
+    if cli.disassemble {
+        let binary = std::fs::read(&cli.file_path)?;
+        let text = asm::disassemble(&binary);
+
+        match cli.output {
+            Some(output) => std::fs::write(output, text)?,
+            None => print!("{text}"),
+        }
+
+        return Ok(());
+    }
+
     let source_text = std::fs::read_to_string(&cli.file_path)?;
-    let source_text = Arc::from(source_text);
+    let source_text = Arc::<str>::from(source_text);
+
+    let assembly = asm::assemble(source_text.clone());
+
+    let path = cli.file_path.display().to_string();
+    for diagnostic in assembly.diagnostics() {
+        eprintln!("{}", asm::report::render(&path, &source_text, diagnostic));
+    }
+
+    let errors = assembly
+        .diagnostics()
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .count();
+    if errors > 0 {
+        match errors {
+            1 => eprintln!("error: could not assemble `{path}` due to the previous error"),
+            n => eprintln!("error: could not assemble `{path}` due to {n} previous errors"),
+        }
+        std::process::exit(1);
+    }
 
-    let binary = asm::assemble(source_text);
+    let binary = assembly.into_binary();
 
     let output = cli
         .output
@@ -1,72 +1,488 @@
-use std::sync::Arc;
+pub mod report;
+#[cfg(test)]
+mod tests;
+
+use std::{collections::BTreeSet, fmt::Write as _, sync::Arc};
 
 use cir::{
     structured::{self, Structured},
     CIR,
 };
+use dec::{Decoded, Decoder};
 use enc::{Encodable, Encoder};
+use hand::Diagnostic;
 use instructions::*;
 use matcher::ConstPattern;
 
-type CB = fn(&[CIR]) -> Box<dyn Encodable>;
+type CB = fn(&[CIR]) -> Result<Box<dyn Encodable>, structured::Error>;
+
+/// The binary assembled from some HAND, and every problem found on the way.
+#[derive(Debug)]
+pub struct Assembly {
+    binary: Vec<u8>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Assembly {
+    /// The assembled program, empty if there were any errors.
+    pub fn binary(&self) -> &[u8] {
+        &self.binary
+    }
+
+    pub fn into_binary(self) -> Vec<u8> {
+        self.binary
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+}
 
-pub fn assemble(text: Arc<str>) -> Vec<u8> {
+pub fn assemble(text: Arc<str>) -> Assembly {
     use cir::Convert;
     use matcher::pattern;
 
     let matcher = build_matcher();
 
     let hand = hand::parse(text);
-    let cir = hand.to_cir();
+    let mut diagnostics = hand.diagnostics().to_vec();
+    if hand.has_errors() {
+        return Assembly {
+            binary: Vec::new(),
+            diagnostics,
+        };
+    }
 
-    let instructions = instructions(&cir);
+    let cir = hand.to_cir();
 
     let mut encoder = Encoder::new_le();
 
-    for (_inst, args) in instructions {
+    let mut instructions = 0;
+    for statement in statements(&cir) {
+        let (index, args) = match statement {
+            Statement::Instruction(args) => {
+                instructions += 1;
+                (instructions - 1, args)
+            }
+            Statement::Data(CIR::Byte(byte)) => {
+                encoder.push_byte(byte);
+                continue;
+            }
+            Statement::Data(CIR::Half(half)) => {
+                encoder.push_half(half);
+                continue;
+            }
+            Statement::Data(CIR::Word(word)) => {
+                encoder.push(enc::Word::base(word));
+                continue;
+            }
+            Statement::Data(_) => unreachable!("only data is placed as is"),
+        };
+
+        let range = || {
+            hand.instruction(index)
+                .expect("every instruction has a range")
+        };
+
         let pattern = pattern::from_cir(args);
-        let pair = matcher::match_pair(&matcher, &pattern).expect("Correct pattern");
+        let pair = match matcher::match_pair(&matcher, &pattern) {
+            Ok(pair) => pair,
+            Err(mismatch) => {
+                diagnostics.push(no_match(range(), &mismatch));
+                continue;
+            }
+        };
 
-        let bits = (pair.value())(args).encode();
+        let inst = match (pair.value())(args) {
+            Ok(inst) => inst,
+            Err(error) => {
+                diagnostics.push(unencodable(range(), error));
+                continue;
+            }
+        };
 
-        encoder.push(bits);
+        encoder.push(inst.encode());
     }
 
-    encoder.finish()
+    let binary = if diagnostics.iter().any(Diagnostic::is_error) {
+        Vec::new()
+    } else {
+        encoder.finish()
+    };
+
+    Assembly {
+        binary,
+        diagnostics,
+    }
 }
 
-fn instructions(cir: &[CIR]) -> impl Iterator<Item = (&CIR, &[CIR])> {
-    let mut curr = 0;
-    cir.chunk_by({
-        move |_, b| {
-            if let CIR::Instruction(it) = b {
-                if *it > curr {
-                    curr = *it;
-                    return false;
-                }
-            }
-            true
+/// Disassembles the output of [`assemble`] back into HAND.
+///
+/// Branch targets are given generated labels, and words that no instruction
+/// claims are written out as comments.
+pub fn disassemble(binary: &[u8]) -> String {
+    use cir::Convert;
+
+    let lines = Decoder::new_le(binary)
+        .zip((0_u32..).step_by(4))
+        .map(|(word, address)| {
+            let cir = match dec::decode::<Instruction>(word) {
+                Decoded::Known(inst) => Ok(inst.to_cir()),
+                Decoded::Unknown(word) => Err(word),
+            };
+            (address, cir)
+        })
+        .collect::<Vec<_>>();
+
+    // labels are relative to the PC, which is 8 bytes ahead
+    let target = |address: u32, offset: i32| address.wrapping_add(8).wrapping_add_signed(offset);
+    let label = |target: u32| format!("L{target:04X}");
+
+    let targets = lines
+        .iter()
+        .filter_map(|(address, cir)| Some((address, cir.as_ref().ok()?)))
+        .flat_map(|(&address, cir)| {
+            cir.iter().filter_map(move |part| match part {
+                CIR::Label(offset) => Some(target(address, *offset)),
+                _ => None,
+            })
+        })
+        .collect::<BTreeSet<_>>();
+
+    let mut text = String::new();
+    for (address, cir) in lines {
+        let name = if targets.contains(&address) {
+            format!("{}:", label(address))
+        } else {
+            String::new()
+        };
+
+        let line = match cir {
+            Ok(cir) => dec::render::render(&cir, |offset| label(target(address, offset))),
+            Err(word) => format!("; unknown encoding {:#010X}", word.get()),
+        };
+
+        writeln!(text, "{name:<8}{line}").expect("Writing to a String can't fail");
+    }
+
+    text
+}
+
+fn no_match(range: hand::TextRange, mismatch: &matcher::Mismatch) -> Diagnostic {
+    if mismatch.is_unknown_name() {
+        return Diagnostic::error(range, format!("unknown instruction `{}`", mismatch.name));
+    }
+
+    Diagnostic::error(
+        range,
+        format!(
+            "no matching instruction form for `{}`: {}",
+            mismatch.name,
+            mismatch.divergence()
+        ),
+    )
+    .with_hint(mismatch.to_string())
+}
+
+/// The instruction matched a form, but one of its values can't be encoded.
+fn unencodable(range: hand::TextRange, error: structured::Error) -> Diagnostic {
+    use structured::Error;
+
+    let unsigned = |bits: u8| format!("this value goes from 0 to {}", u32::MAX >> (32 - bits));
+    let signed = |bits: u8| {
+        let reach = 1_i32 << (bits - 1);
+        format!("this offset goes from {} to {} words", -reach, reach - 1)
+    };
+
+    match error {
+        Error::Mismatch => unreachable!("CIR matches the pattern"),
+        Error::TooLarge { value, bits } => {
+            Diagnostic::error(range, format!("{value} does not fit in {bits} bits"))
+                .with_hint(unsigned(bits))
+        }
+        Error::Negative { value, bits } => {
+            Diagnostic::error(range, format!("{value} is negative, it can't be encoded"))
+                .with_hint(unsigned(bits))
         }
-    })
-    .filter_map(|inst| inst.split_first())
+        Error::TooFar { offset, bits } => Diagnostic::error(
+            range,
+            format!("the label is {offset} words away, it does not fit in {bits} bits"),
+        )
+        .with_hint(signed(bits)),
+        Error::NotRotated(value) => Diagnostic::error(
+            range,
+            format!("{value:#X} can't be encoded as an immediate"),
+        )
+        .with_hint(
+            "immediates are an 8-bit value rotated by an even amount, \
+                    try `LDR rX, =value`",
+        ),
+        Error::ProgramCounter => Diagnostic::error(range, "pc can't be used by this instruction")
+            .with_hint("its result would be unpredictable, try another register"),
+        Error::NotPair(first, second) => Diagnostic::error(
+            range,
+            format!("r{first} and r{second} aren't a pair of registers"),
+        )
+        .with_hint("pairs are an even register and the one after it, like `r2, r3`"),
+        Error::Conditional => Diagnostic::error(range, "this instruction can't be conditional")
+            .with_hint("it is always executed, try branching around it"),
+        Error::Fields => Diagnostic::error(range, "fields can't be named here")
+            .with_hint("all of the register is read, try `CPSR` or `SPSR` on its own"),
+        Error::BitField { lsb, width } => Diagnostic::error(
+            range,
+            format!("a field of {width} bits from bit {lsb} can't be used"),
+        )
+        .with_hint("fields have at least one bit, and end by bit 31"),
+        Error::Offset => Diagnostic::error(range, "this address can't have an offset")
+            .with_hint("only a base register is used, like `[r1]`"),
+        Error::SameRegister(r) => {
+            Diagnostic::error(range, format!("r{r} can't be used twice here"))
+                .with_hint("these registers must be different")
+        }
+    }
+}
+
+enum Statement<'a> {
+    /// The arguments of an instruction, `[Char.., Condition, ..args]`
+    Instruction(&'a [CIR]),
+    /// Data placed as is
+    Data(CIR),
+}
+
+fn statements(cir: &[CIR]) -> impl Iterator<Item = Statement<'_>> {
+    fn starts_statement(cir: &CIR) -> bool {
+        matches!(
+            cir,
+            CIR::Instruction(_) | CIR::Byte(_) | CIR::Half(_) | CIR::Word(_)
+        )
+    }
+
+    cir.chunk_by(|_, b| !starts_statement(b))
+        .filter_map(|chunk| match chunk.split_first()? {
+            (CIR::Instruction(_), args) => Some(Statement::Instruction(args)),
+            (data, _) => Some(Statement::Data(*data)),
+        })
 }
 
 fn build_matcher() -> matcher::Matcher<CB> {
+    use matcher::Pattern;
+
     fn add_pattern<T: ConstPattern + Encodable + Structured + 'static>(
         p: &mut matcher::Patterns<CB>,
     ) {
+        let mut patterns = vec![T::PATTERN.to_vec()];
+        // the `S` suffix and the `!` of a base register are optional,
+        // without them the flags and the base aren't updated
+        for optional in [Pattern::SetFlags, Pattern::Bang] {
+            if T::PATTERN.contains(&optional) {
+                patterns.push(
+                    T::PATTERN
+                        .iter()
+                        .copied()
+                        .filter(|p| *p != optional)
+                        .collect(),
+                );
+            }
+        }
+
+        for pattern in patterns {
+            p.push(
+                |cir| Ok(Box::new(structured::parse_from_args::<T>(cir)?)),
+                &pattern,
+            );
+
+            // a register without a shift is shifted by `LSL #0`
+            if let [unshifted @ .., Pattern::Register, Pattern::Shift, Pattern::Number] =
+                &pattern[..]
+            {
+                p.push(
+                    |cir| {
+                        let mut cir = cir.to_vec();
+                        cir.extend([CIR::Shift(cir::Shift::LSL), CIR::Number(0)]);
+                        Ok(Box::new(structured::parse_from_args::<T>(&cir)?))
+                    },
+                    &[unshifted, &[Pattern::Register]].concat(),
+                );
+            }
+        }
+    }
+
+    /// Adds `T` under another name, like `SWI` for `SVC`.
+    fn add_alias<T: ConstPattern + Encodable + Structured + 'static>(
+        p: &mut matcher::Patterns<CB>,
+        name: &str,
+    ) {
+        let operands = T::PATTERN
+            .iter()
+            .skip_while(|p| matches!(p, Pattern::Char(_)));
+        let pattern = name
+            .chars()
+            .map(Pattern::Char)
+            .chain(operands.copied())
+            .collect::<Vec<_>>();
         p.push(
-            |cir| {
-                Box::new(structured::parse_from_args::<T>(cir).expect("CIR matches this pattern"))
-            },
-            T::PATTERN,
+            |cir| Ok(Box::new(structured::parse_from_args::<T>(cir)?)),
+            &pattern,
         );
     }
 
     let mut p = matcher::Patterns::<CB>::new();
 
+    add_pattern::<Adr>(&mut p);
+    add_pattern::<AndImm>(&mut p);
+    add_pattern::<AndReg>(&mut p);
+    add_pattern::<AndRegShiftReg>(&mut p);
+    add_pattern::<EorImm>(&mut p);
+    add_pattern::<EorReg>(&mut p);
+    add_pattern::<EorRegShiftReg>(&mut p);
+    add_pattern::<SubImm>(&mut p);
+    add_pattern::<SubReg>(&mut p);
+    add_pattern::<SubRegShiftReg>(&mut p);
+    add_pattern::<RsbImm>(&mut p);
+    add_pattern::<RsbReg>(&mut p);
+    add_pattern::<RsbRegShiftReg>(&mut p);
     add_pattern::<AddImm>(&mut p);
+    add_pattern::<AddReg>(&mut p);
+    add_pattern::<AddRegShiftReg>(&mut p);
+    add_pattern::<AdcImm>(&mut p);
+    add_pattern::<AdcReg>(&mut p);
+    add_pattern::<AdcRegShiftReg>(&mut p);
+    add_pattern::<SbcImm>(&mut p);
+    add_pattern::<SbcReg>(&mut p);
+    add_pattern::<SbcRegShiftReg>(&mut p);
+    add_pattern::<RscImm>(&mut p);
+    add_pattern::<RscReg>(&mut p);
+    add_pattern::<RscRegShiftReg>(&mut p);
+    add_pattern::<TstImm>(&mut p);
+    add_pattern::<TstReg>(&mut p);
+    add_pattern::<TstRegShiftReg>(&mut p);
+    add_pattern::<TeqImm>(&mut p);
+    add_pattern::<TeqReg>(&mut p);
+    add_pattern::<TeqRegShiftReg>(&mut p);
+    add_pattern::<CmpImm>(&mut p);
+    add_pattern::<CmpReg>(&mut p);
+    add_pattern::<CmpRegShiftReg>(&mut p);
+    add_pattern::<CmnImm>(&mut p);
+    add_pattern::<CmnReg>(&mut p);
+    add_pattern::<CmnRegShiftReg>(&mut p);
+    add_pattern::<OrrImm>(&mut p);
+    add_pattern::<OrrReg>(&mut p);
+    add_pattern::<OrrRegShiftReg>(&mut p);
+    add_pattern::<MovImm>(&mut p);
+    add_pattern::<MovReg>(&mut p);
+    add_pattern::<MovRegShiftReg>(&mut p);
+    add_pattern::<BicImm>(&mut p);
+    add_pattern::<BicReg>(&mut p);
+    add_pattern::<BicRegShiftReg>(&mut p);
+    add_pattern::<MvnImm>(&mut p);
+    add_pattern::<MvnReg>(&mut p);
+    add_pattern::<MvnRegShiftReg>(&mut p);
+    add_pattern::<Mul>(&mut p);
+    add_pattern::<Mla>(&mut p);
+    add_pattern::<Mls>(&mut p);
+    add_pattern::<Umull>(&mut p);
+    add_pattern::<Umlal>(&mut p);
+    add_pattern::<Smull>(&mut p);
+    add_pattern::<Smlal>(&mut p);
+    add_pattern::<Qadd>(&mut p);
+    add_pattern::<Qsub>(&mut p);
+    add_pattern::<Clz>(&mut p);
+    add_pattern::<Rev>(&mut p);
+    add_pattern::<Rev16>(&mut p);
+    add_pattern::<Sbfx>(&mut p);
+    add_pattern::<Ubfx>(&mut p);
+    add_pattern::<Bfc>(&mut p);
+    add_pattern::<Bfi>(&mut p);
     add_pattern::<B>(&mut p);
+    add_pattern::<Bl>(&mut p);
+    add_pattern::<Bx>(&mut p);
+    add_pattern::<BlxReg>(&mut p);
+    add_pattern::<BlxImm>(&mut p);
+    add_pattern::<Mrs>(&mut p);
+    add_pattern::<MsrReg>(&mut p);
+    add_pattern::<MsrImm>(&mut p);
+    add_pattern::<Nop>(&mut p);
+    add_pattern::<Svc>(&mut p);
+    add_alias::<Svc>(&mut p, "SWI");
+    add_pattern::<Bkpt>(&mut p);
+    add_pattern::<Hlt>(&mut p);
+    add_pattern::<LdrImmLit>(&mut p);
+    add_pattern::<LdrImmOffset>(&mut p);
+    add_pattern::<LdrImmPreIndex>(&mut p);
+    add_pattern::<LdrImmPostIndex>(&mut p);
+    add_pattern::<LdrRegOffset>(&mut p);
+    add_pattern::<LdrRegPreIndex>(&mut p);
+    add_pattern::<LdrRegPostIndex>(&mut p);
+    add_pattern::<StrImmOffset>(&mut p);
+    add_pattern::<StrImmPreIndex>(&mut p);
+    add_pattern::<StrImmPostIndex>(&mut p);
+    add_pattern::<StrRegOffset>(&mut p);
+    add_pattern::<StrRegPreIndex>(&mut p);
+    add_pattern::<StrRegPostIndex>(&mut p);
+    add_pattern::<LdrbImmOffset>(&mut p);
+    add_pattern::<LdrbImmPreIndex>(&mut p);
+    add_pattern::<LdrbImmPostIndex>(&mut p);
+    add_pattern::<LdrbRegOffset>(&mut p);
+    add_pattern::<LdrbRegPreIndex>(&mut p);
+    add_pattern::<LdrbRegPostIndex>(&mut p);
+    add_pattern::<StrbImmOffset>(&mut p);
+    add_pattern::<StrbImmPreIndex>(&mut p);
+    add_pattern::<StrbImmPostIndex>(&mut p);
+    add_pattern::<StrbRegOffset>(&mut p);
+    add_pattern::<StrbRegPreIndex>(&mut p);
+    add_pattern::<StrbRegPostIndex>(&mut p);
+    add_pattern::<LdrhImmOffset>(&mut p);
+    add_pattern::<LdrhImmPreIndex>(&mut p);
+    add_pattern::<LdrhImmPostIndex>(&mut p);
+    add_pattern::<LdrhRegOffset>(&mut p);
+    add_pattern::<LdrhRegPreIndex>(&mut p);
+    add_pattern::<LdrhRegPostIndex>(&mut p);
+    add_pattern::<StrhImmOffset>(&mut p);
+    add_pattern::<StrhImmPreIndex>(&mut p);
+    add_pattern::<StrhImmPostIndex>(&mut p);
+    add_pattern::<StrhRegOffset>(&mut p);
+    add_pattern::<StrhRegPreIndex>(&mut p);
+    add_pattern::<StrhRegPostIndex>(&mut p);
+    add_pattern::<LdrsbImmOffset>(&mut p);
+    add_pattern::<LdrsbImmPreIndex>(&mut p);
+    add_pattern::<LdrsbImmPostIndex>(&mut p);
+    add_pattern::<LdrsbRegOffset>(&mut p);
+    add_pattern::<LdrsbRegPreIndex>(&mut p);
+    add_pattern::<LdrsbRegPostIndex>(&mut p);
+    add_pattern::<LdrshImmOffset>(&mut p);
+    add_pattern::<LdrshImmPreIndex>(&mut p);
+    add_pattern::<LdrshImmPostIndex>(&mut p);
+    add_pattern::<LdrshRegOffset>(&mut p);
+    add_pattern::<LdrshRegPreIndex>(&mut p);
+    add_pattern::<LdrshRegPostIndex>(&mut p);
+    add_pattern::<LdrdImmOffset>(&mut p);
+    add_pattern::<LdrdImmPreIndex>(&mut p);
+    add_pattern::<LdrdImmPostIndex>(&mut p);
+    add_pattern::<LdrdRegOffset>(&mut p);
+    add_pattern::<LdrdRegPreIndex>(&mut p);
+    add_pattern::<LdrdRegPostIndex>(&mut p);
+    add_pattern::<StrdImmOffset>(&mut p);
+    add_pattern::<StrdImmPreIndex>(&mut p);
+    add_pattern::<StrdImmPostIndex>(&mut p);
+    add_pattern::<StrdRegOffset>(&mut p);
+    add_pattern::<StrdRegPreIndex>(&mut p);
+    add_pattern::<StrdRegPostIndex>(&mut p);
+    add_pattern::<Push>(&mut p);
+    add_pattern::<Pop>(&mut p);
+    add_pattern::<Ldm>(&mut p);
+    add_pattern::<Ldmib>(&mut p);
+    add_pattern::<Ldmda>(&mut p);
+    add_pattern::<Ldmdb>(&mut p);
+    add_pattern::<Stm>(&mut p);
+    add_pattern::<Stmib>(&mut p);
+    add_pattern::<Stmda>(&mut p);
+    add_pattern::<Stmdb>(&mut p);
+    add_pattern::<Swp>(&mut p);
+    add_pattern::<Swpb>(&mut p);
 
     p.finish()
 }
@@ -0,0 +1,60 @@
+//! Renders diagnostics against the source they came from, in the style of rustc:
+//!
+//! ```text
+//! error: label `a` is defined more than once
+//!  --> main.s:2:1
+//!   |
+//! 2 | a: HLT
+//!   | ^
+//!   = hint: each label can only be defined once, try renaming this one
+//! ```
+
+use std::fmt::Write as _;
+
+use hand::{Diagnostic, Severity};
+
+/// Renders `diagnostic` against `source`, which was read from `path`.
+///
+/// Only the first line of the range is shown, a range that spans lines is
+/// underlined to the end of its first line.
+pub fn render(path: &str, source: &str, diagnostic: &Diagnostic) -> String {
+    let start = usize::from(diagnostic.range.start());
+    let end = usize::from(diagnostic.range.end());
+
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let line = source[line_start..line_end].trim_end_matches('\r');
+
+    let line_number = source[..line_start].matches('\n').count() + 1;
+    let column = source[line_start..start].chars().count() + 1;
+
+    // keep tabs so the underline lines up however they are displayed
+    let padding = source[line_start..start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    let underline = source[start..end.min(line_end)].chars().count().max(1);
+
+    let severity = match diagnostic.severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    };
+    let gutter = " ".repeat(line_number.to_string().len());
+
+    let mut text = format!(
+        "{severity}: {message}\n\
+        {gutter}--> {path}:{line_number}:{column}\n\
+        {gutter} |\n\
+        {line_number} | {line}\n\
+        {gutter} | {padding}{carets}\n",
+        message = diagnostic.message,
+        carets = "^".repeat(underline),
+    );
+    if let Some(hint) = &diagnostic.hint {
+        writeln!(text, "{gutter} = hint: {hint}").expect("Writing to a String can't fail");
+    }
+
+    text
+}
@@ -0,0 +1,589 @@
+use super::*;
+
+fn roundtrip(text: &str) -> String {
+    let assembly = assemble(Arc::from(text));
+    assert!(!assembly.has_errors(), "{:?}", assembly.diagnostics());
+    disassemble(assembly.binary())
+}
+
+#[test]
+fn disassemble_add() {
+    assert_eq!(roundtrip("ADD r0, r1, #1"), "        ADD r0, r1, #1\n");
+}
+
+#[test]
+fn disassemble_labels() {
+    assert_eq!(
+        roundtrip("start: ADD r0, r0, #1\nB start\nB end\nend: ADD r1, r1, #255"),
+        "L0000:  ADD r0, r0, #1\n\
+        \x20       B L0000\n\
+        \x20       B L000C\n\
+        L000C:  ADD r1, r1, #255\n"
+    );
+}
+
+#[test]
+fn disassemble_branches() {
+    assert_eq!(
+        roundtrip("BL f\nBXNE lr\nBLX r2\nf: BLX f"),
+        "        BL L000C\n\
+        \x20       BXNE lr\n\
+        \x20       BLX r2\n\
+        L000C:  BLX L000C\n"
+    );
+}
+
+#[test]
+fn disassemble_status_registers() {
+    assert_eq!(
+        roundtrip("MRS r0, CPSR\nMSR SPSR_fsxc, r1\nMSR cpsr, r2\nMSRNE CPSR_f, #0xF0000000"),
+        "        MRS r0, CPSR\n\
+        \x20       MSR SPSR_fsxc, r1\n\
+        \x20       MSR CPSR_fc, r2\n\
+        \x20       MSRNE CPSR_f, #4026531840\n"
+    );
+}
+
+#[test]
+fn disassemble_miscellaneous() {
+    assert_eq!(
+        roundtrip(
+            "CLZ r0, r1\nREV16 r2, r3\nUBFX r0, r1, #4, #8\nBFC r0, #0, #32\n\
+            QADDNE r0, r1, r2\nNOP\nBKPT #0x12\nSWPB r0, r1, [r2]"
+        ),
+        "        CLZ r0, r1\n\
+        \x20       REV16 r2, r3\n\
+        \x20       UBFX r0, r1, #4, #8\n\
+        \x20       BFC r0, #0, #32\n\
+        \x20       QADDNE r0, r1, r2\n\
+        \x20       NOP\n\
+        \x20       BKPT #18\n\
+        \x20       SWPB r0, r1, [r2]\n"
+    );
+}
+
+#[test]
+fn disassemble_wide_shifts() {
+    // LSR and ASR encode a shift by 32 as 0
+    assert_eq!(
+        roundtrip("LDR r0, [r1, r2, LSR #32]!\nLDR r0, [r1, r2, ASR #32]!"),
+        "        LDR r0, [r1, r2, LSR #32]!\n\
+        \x20       LDR r0, [r1, r2, ASR #32]!\n"
+    );
+}
+
+#[test]
+fn disassemble_subtracted_offsets() {
+    assert_eq!(
+        roundtrip("LDR r0, [r1, #-4]!\nLDR r0, [r1, -r2, LSL #1]!"),
+        "        LDR r0, [r1, #-4]!\n\
+        \x20       LDR r0, [r1, -r2, LSL #1]!\n"
+    );
+}
+
+#[test]
+fn disassemble_loads_and_stores() {
+    assert_eq!(
+        roundtrip(
+            "STR r0, [r1]\n\
+            LDRB r0, [r1], #1\n\
+            STRH r0, [r1, -r2]!\n\
+            LDRSBEQ r0, [r1, #-1]\n\
+            LDRNESH r0, [r1, r2]\n\
+            LDRD r2, r3, [sp, #8]!\n\
+            STRD r0, r1, [r2], r3"
+        ),
+        "        STR r0, [r1]\n\
+        \x20       LDRB r0, [r1], #1\n\
+        \x20       STRH r0, [r1, -r2]!\n\
+        \x20       LDRSBEQ r0, [r1, #-1]\n\
+        \x20       LDRSHNE r0, [r1, r2]\n\
+        \x20       LDRD r2, r3, [sp, #8]!\n\
+        \x20       STRD r0, r1, [r2], r3\n"
+    );
+}
+
+#[test]
+fn disassemble_block_transfers() {
+    assert_eq!(
+        roundtrip(
+            "LDMIA r0!, {r1-r3}\n\
+            STMED r0, {r1, r5}\n\
+            LDMEQDB r4, {r0}\n\
+            STMFD sp!, {r4, lr}\n\
+            LDMFD sp!, {r4, pc}\n\
+            PUSHNE {r0}"
+        ),
+        "        LDM r0!, {r1-r3}\n\
+        \x20       STMDA r0, {r1, r5}\n\
+        \x20       LDMDBEQ r4, {r0}\n\
+        \x20       PUSH {r4, lr}\n\
+        \x20       POP {r4, pc}\n\
+        \x20       PUSHNE {r0}\n"
+    );
+}
+
+#[test]
+fn disassemble_data_processing() {
+    assert_eq!(
+        roundtrip(
+            "SUB r0, r1, #0x3FC\n\
+            MOV r0, r1\n\
+            MVN r2, r3, ASR #4\n\
+            EOR r4, r4, r5, ROR r6\n\
+            CMP r7, r8, RRX\n\
+            TST r9, #0xFF000000"
+        ),
+        "        SUB r0, r1, #1020\n\
+        \x20       MOV r0, r1\n\
+        \x20       MVN r2, r3, ASR #4\n\
+        \x20       EOR r4, r4, r5, ROR r6\n\
+        \x20       CMP r7, r8, RRX\n\
+        \x20       TST r9, #4278190080\n"
+    );
+}
+
+#[test]
+fn disassemble_conditions() {
+    assert_eq!(
+        roundtrip(
+            "loop: BNE loop\nADDGT r0, r0, #1\nTEQLS r1, r2\nBICCS r3, r3, #1\nLDRLO r0, [r1, #4]!"
+        ),
+        "L0000:  BNE L0000\n\
+        \x20       ADDGT r0, r0, #1\n\
+        \x20       TEQLS r1, r2\n\
+        \x20       BICCS r3, r3, #1\n\
+        \x20       LDRCC r0, [r1, #4]!\n"
+    );
+}
+
+#[test]
+fn disassemble_multiplies() {
+    assert_eq!(
+        roundtrip(
+            "MULS r0, r1, r2\nMLA r0, r1, r2, r3\nMLSNE r0, r1, r2, r3\nSMLALS r4, r5, r6, r7"
+        ),
+        "        MULS r0, r1, r2\n\
+        \x20       MLA r0, r1, r2, r3\n\
+        \x20       MLSNE r0, r1, r2, r3\n\
+        \x20       SMLALS r4, r5, r6, r7\n"
+    );
+}
+
+#[test]
+fn disassemble_set_flags() {
+    assert_eq!(
+        roundtrip("ADDS r0, r0, #1\nMOVSNE r1, r2\nBICGTS r3, r3, r4, LSL r5"),
+        "        ADDS r0, r0, #1\n\
+        \x20       MOVSNE r1, r2\n\
+        \x20       BICSGT r3, r3, r4, LSL r5\n"
+    );
+}
+
+#[test]
+fn disassemble_unknown() {
+    let binary = 0xF000_0000_u32.to_le_bytes();
+    assert_eq!(
+        disassemble(&binary),
+        "        ; unknown encoding 0xF0000000\n"
+    );
+}
+
+#[test]
+fn decode_struct() {
+    use cir::Convert;
+    use dec::{Decodable, Word};
+
+    let word = Word::base(0b1110_0010_1000_0001_0000_0000_0000_0001);
+    let inst = Instruction::decode(word).expect("Valid encoding");
+    let Instruction::AddImm(add) = inst else {
+        panic!("ADD r0, r1, #1 decodes as an `AddImm`");
+    };
+    assert_eq!(add.encode(), word);
+    assert_eq!(
+        inst.to_cir(),
+        [
+            CIR::Char('A'),
+            CIR::Char('D'),
+            CIR::Char('D'),
+            CIR::Condition(cir::Condition::AL),
+            CIR::Register(0),
+            CIR::Register(1),
+            CIR::Number(1),
+        ]
+    );
+}
+
+fn report(text: &str) -> String {
+    let assembly = assemble(Arc::from(text));
+    assembly
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| report::render("main.s", text, diagnostic))
+        .collect()
+}
+
+#[test]
+fn report_duplicate_label() {
+    assert_eq!(
+        report("a: HLT\na: HLT"),
+        "error: label `a` is defined more than once\n\
+        \x20--> main.s:2:1\n\
+        \x20 |\n\
+        2 | a: HLT\n\
+        \x20 | ^\n\
+        \x20 = hint: each label can only be defined once, try renaming this one\n"
+    );
+}
+
+#[test]
+fn report_no_match() {
+    let assembly = assemble(Arc::from("HLT\n  ADD r0, #1, r1 ; bad\n"));
+    assert!(assembly.has_errors());
+    assert!(assembly.binary().is_empty());
+    assert_eq!(
+        report("HLT\n  ADD r0, #1, r1 ; bad\n"),
+        "error: no matching instruction form for `ADD`: \
+        expected Register after Register, found Number\n\
+        \x20--> main.s:2:3\n\
+        \x20 |\n\
+        2 |   ADD r0, #1, r1 ; bad\n\
+        \x20 |   ^^^^^^^^^^^^^^\n\
+        \x20 = hint: ADD expects Register, Register, Register \
+        or Register, Register, Register, Shift, Register \
+        or Register, Register, Register, Shift, Number \
+        or Register, Register, Number; \
+        found Register, Number, Register\n"
+    );
+}
+
+#[test]
+fn report_unknown_instruction() {
+    assert_eq!(
+        report("FOO r0"),
+        "error: unknown instruction `FOO`\n\
+        \x20--> main.s:1:1\n\
+        \x20 |\n\
+        1 | FOO r0\n\
+        \x20 | ^^^^^^\n"
+    );
+}
+
+#[test]
+fn report_missing() {
+    // an empty range still gets a caret, and tabs are kept to line it up
+    assert_eq!(
+        report("\tLDR r0, [r1\n"),
+        "error: expected `]`\n\
+        \x20--> main.s:1:13\n\
+        \x20 |\n\
+        1 | \tLDR r0, [r1\n\
+        \x20 | \t           ^\n"
+    );
+}
+
+#[test]
+fn report_wide_gutter() {
+    let text = format!("{}ADD r0, %", "HLT\n".repeat(9));
+    assert_eq!(
+        report(&text),
+        "error: unexpected `%`\n\
+        \x20 --> main.s:10:9\n\
+        \x20  |\n\
+        10 | ADD r0, %\n\
+        \x20  |         ^\n"
+    );
+}
+
+#[test]
+fn report_unencodable_immediate() {
+    assert_eq!(
+        report("ADD r0, r0, #0x1000\nCMP r0, #0x101"),
+        "error: 0x101 can't be encoded as an immediate\n\
+        \x20--> main.s:2:1\n\
+        \x20 |\n\
+        2 | CMP r0, #0x101\n\
+        \x20 | ^^^^^^^^^^^^^^\n\
+        \x20 = hint: immediates are an 8-bit value rotated by an even amount, \
+        try `LDR rX, =value`\n"
+    );
+}
+
+#[test]
+fn report_unencodable_adr() {
+    assert_eq!(
+        report("ADR r0, far\n.space 0x1008\nfar: HLT"),
+        "error: 0x1004 can't be encoded as an immediate\n\
+        \x20--> main.s:1:1\n\
+        \x20 |\n\
+        1 | ADR r0, far\n\
+        \x20 | ^^^^^^^^^^^\n\
+        \x20 = hint: immediates are an 8-bit value rotated by an even amount, \
+        try `LDR rX, =value`\n"
+    );
+}
+
+#[test]
+fn report_multiply_registers() {
+    assert_eq!(
+        report("UMULL r0, r0, r1, r2\nMUL pc, r0, r1"),
+        "error: r0 can't be used twice here\n\
+        \x20--> main.s:1:1\n\
+        \x20 |\n\
+        1 | UMULL r0, r0, r1, r2\n\
+        \x20 | ^^^^^^^^^^^^^^^^^^^^\n\
+        \x20 = hint: these registers must be different\n\
+        error: pc can't be used by this instruction\n\
+        \x20--> main.s:2:1\n\
+        \x20 |\n\
+        2 | MUL pc, r0, r1\n\
+        \x20 | ^^^^^^^^^^^^^^\n\
+        \x20 = hint: its result would be unpredictable, try another register\n"
+    );
+}
+
+#[test]
+fn report_not_pair() {
+    assert_eq!(
+        report("LDRD r1, r2, [r0]"),
+        "error: r1 and r2 aren't a pair of registers\n\
+        \x20--> main.s:1:1\n\
+        \x20 |\n\
+        1 | LDRD r1, r2, [r0]\n\
+        \x20 | ^^^^^^^^^^^^^^^^^\n\
+        \x20 = hint: pairs are an even register and the one after it, like `r2, r3`\n"
+    );
+}
+
+#[test]
+fn report_writeback_of_listed_base() {
+    let text = "LDM r0!, {r0, r1}";
+    let assembly = assemble(Arc::from(text));
+    assert!(!assembly.has_errors());
+    assert_eq!(
+        report(text),
+        "warning: `r0` is written back, but it is also in the list\n\
+        \x20--> main.s:1:5\n\
+        \x20 |\n\
+        1 | LDM r0!, {r0, r1}\n\
+        \x20 |     ^^\n\
+        \x20 = hint: the value it is left with is unpredictable, try leaving out the `!`\n"
+    );
+}
+
+#[test]
+fn report_conditional_blx() {
+    assert_eq!(
+        report("f: BLXEQ f"),
+        "error: this instruction can't be conditional\n\
+        \x20--> main.s:1:4\n\
+        \x20 |\n\
+        1 | f: BLXEQ f\n\
+        \x20 |    ^^^^^^^\n\
+        \x20 = hint: it is always executed, try branching around it\n"
+    );
+}
+
+#[test]
+fn report_fields_of_mrs() {
+    assert_eq!(
+        report("MRS r0, CPSR_f"),
+        "error: fields can't be named here\n\
+        \x20--> main.s:1:1\n\
+        \x20 |\n\
+        1 | MRS r0, CPSR_f\n\
+        \x20 | ^^^^^^^^^^^^^^\n\
+        \x20 = hint: all of the register is read, try `CPSR` or `SPSR` on its own\n"
+    );
+}
+
+#[test]
+fn report_bit_fields_and_offsets() {
+    assert_eq!(
+        report("BFI r0, r1, #24, #16\nSWP r0, r1, [r2, #4]"),
+        "error: a field of 16 bits from bit 24 can't be used\n\
+        \x20--> main.s:1:1\n\
+        \x20 |\n\
+        1 | BFI r0, r1, #24, #16\n\
+        \x20 | ^^^^^^^^^^^^^^^^^^^^\n\
+        \x20 = hint: fields have at least one bit, and end by bit 31\n\
+        error: this address can't have an offset\n\
+        \x20--> main.s:2:1\n\
+        \x20 |\n\
+        2 | SWP r0, r1, [r2, #4]\n\
+        \x20 | ^^^^^^^^^^^^^^^^^^^^\n\
+        \x20 = hint: only a base register is used, like `[r1]`\n"
+    );
+}
+
+#[test]
+fn report_out_of_range() {
+    assert_eq!(
+        report("LDR r0, [r1, #0x1000]!\nLDR r0, [r1, r2, LSL #1 - 2]!"),
+        "error: 4096 does not fit in 12 bits\n\
+        \x20--> main.s:1:1\n\
+        \x20 |\n\
+        1 | LDR r0, [r1, #0x1000]!\n\
+        \x20 | ^^^^^^^^^^^^^^^^^^^^^^\n\
+        \x20 = hint: this value goes from 0 to 4095\n\
+        error: -1 is negative, it can't be encoded\n\
+        \x20--> main.s:2:1\n\
+        \x20 |\n\
+        2 | LDR r0, [r1, r2, LSL #1 - 2]!\n\
+        \x20 | ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^\n\
+        \x20 = hint: this value goes from 0 to 31\n"
+    );
+}
+
+#[test]
+fn report_far_branch() {
+    let text = format!("B far\n{}far: HLT", ".space 0x100000\n".repeat(33));
+    assert_eq!(
+        report(&text),
+        "error: the label is 8650751 words away, it does not fit in 24 bits\n\
+        \x20--> main.s:1:1\n\
+        \x20 |\n\
+        1 | B far\n\
+        \x20 | ^^^^^\n\
+        \x20 = hint: this offset goes from -8388608 to 8388607 words\n"
+    );
+}
+
+#[test]
+fn report_far_call() {
+    let text = format!("far: HLT\n{}BL far", ".space 0x100000\n".repeat(33));
+    assert_eq!(
+        report(&text),
+        "error: the label is -8650755 words away, it does not fit in 24 bits\n\
+        \x20 --> main.s:35:1\n\
+        \x20  |\n\
+        35 | BL far\n\
+        \x20  | ^^^^^^\n\
+        \x20  = hint: this offset goes from -8388608 to 8388607 words\n"
+    );
+}
+
+#[test]
+fn report_far_literal() {
+    assert_eq!(
+        report("LDR r1, far\n.space 0x1390\nfar: .word 1"),
+        "error: 5004 does not fit in 12 bits\n\
+        \x20--> main.s:1:1\n\
+        \x20 |\n\
+        1 | LDR r1, far\n\
+        \x20 | ^^^^^^^^^^^\n\
+        \x20 = hint: this value goes from 0 to 4095\n"
+    );
+}
+
+fn binary(text: &str) -> Vec<u8> {
+    let assembly = assemble(Arc::from(text));
+    assert!(!assembly.has_errors(), "{:?}", assembly.diagnostics());
+    assembly.into_binary()
+}
+
+#[test]
+fn data() {
+    assert_eq!(
+        binary(
+            ".word 0x12345678\n\
+            .half 0xABCD, 1\n\
+            .byte 1, 2\n\
+            .space 3, 0xFF\n\
+            .align\n\
+            HLT"
+        ),
+        [
+            0x78, 0x56, 0x34, 0x12, // .word
+            0xCD, 0xAB, 0x01, 0x00, // .half
+            0x01, 0x02, // .byte
+            0xFF, 0xFF, 0xFF, // .space
+            0x00, 0x00, 0x00, // .align
+            0x70, 0x00, 0x00, 0xE1, // HLT
+        ]
+    );
+}
+
+#[test]
+fn labels_after_data() {
+    let binary = binary(
+        "B end\n\
+        table: .word 1, 2\n\
+        .byte 3\n\
+        .align 3\n\
+        end: HLT\n\
+        .word table, end",
+    );
+    assert_eq!(binary.len(), 16 + 4 + 8);
+    // the padding puts `end` at 0x10
+    assert_eq!(disassemble(&binary[..4]), "        B L0010\n");
+    assert_eq!(binary[20..], [4, 0, 0, 0, 0x10, 0, 0, 0]);
+}
+
+#[test]
+fn strings() {
+    assert_eq!(
+        binary(".ascii \"Hi\\n\"\n.asciz \"a\", \"\\x7F\"\n.byte '\\t'"),
+        b"Hi\na\0\x7F\0\t"
+    );
+}
+
+#[test]
+fn swi() {
+    assert_eq!(binary("SWIEQ #2"), binary("SVCEQ #2"));
+    assert_eq!(disassemble(&binary("SWI #0x10")), "        SVC #16\n");
+}
+
+#[test]
+fn character_immediates() {
+    assert_eq!(binary("CMP r0, #'A'"), binary("CMP r0, #65"));
+}
+
+#[test]
+fn constants() {
+    assert_eq!(
+        binary(
+            ".equ COUNT, 3\n\
+            STEP EQU 2\n\
+            .space #COUNT, #STEP\n\
+            .align\n\
+            ADD r0, r0, #STEP"
+        ),
+        binary(".space 3, 2\n.align\nADD r0, r0, #2")
+    );
+}
+
+#[test]
+fn expressions() {
+    assert_eq!(
+        binary(
+            ".equ WORDS, 2\n\
+            start: .word start + 4, -1, 1 << 4 | 3 * 2 - 1\n\
+            end: ADD r0, r0, #(end - start) / WORDS\n\
+            B end + 8"
+        ),
+        binary(
+            ".word 4, 0xFFFF_FFFF, 21\n\
+            ADD r0, r0, #6\n\
+            B after\n\
+            after:"
+        )
+    );
+}
+
+#[test]
+fn literal_pools() {
+    let binary = binary(
+        "LDR r0, =0x12345678\n\
+        .byte 1\n\
+        .ltorg\n\
+        LDR r1, =end\n\
+        end:",
+    );
+    // the first pool is aligned after the byte, the second goes at the end
+    assert_eq!(binary.len(), 4 + 4 + 4 + 4 + 4);
+    assert_eq!(binary[8..12], 0x1234_5678_u32.to_le_bytes());
+    assert_eq!(binary[16..], 16_u32.to_le_bytes());
+    // the load at 0xC reads 0x10, 4 bytes behind the PC
+    assert_eq!(disassemble(&binary[12..16]), "        LDR r1, L0004\n");
+}
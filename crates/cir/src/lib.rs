@@ -15,7 +15,11 @@ pub enum CIR {
     Char(char),
     Register(u32),
     RegisterList(u16),
+    StatusRegister(StatusRegister),
     Condition(Condition),
+    /// The `S` suffix, the instruction updates the flags
+    SetFlags,
+    Sign(Sign),
     Shift(Shift),
     Number(u32),
     Label(i32),
@@ -23,11 +27,33 @@ pub enum CIR {
     PreIndexAddress,
     PostIndexAddress,
     Bang,
+    /// Data placed as is, outside of any instruction
+    Byte(u8),
+    Half(u16),
+    Word(u32),
 }
 
+/// `CPSR` or `SPSR`, with the fields of it that are named, like `CPSR_fc`
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StatusRegister {
+    pub spsr: bool,
+    /// A bit for each of the `fsxc` fields from the highest,
+    /// `None` when there is no `_`
+    pub fields: Option<u8>,
+}
+
+/// Whether an offset is added to or subtracted from the base
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Sign {
+    #[default]
+    Plus,
+    Minus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub enum Shift {
     /// Logical shift left
+    #[default]
     LSL,
     /// Logical shift right
     LSR,
@@ -39,13 +65,7 @@ pub enum Shift {
     RRX,
 }
 
-impl Default for Shift {
-    fn default() -> Self {
-        Self::LSL
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 #[repr(u8)]
 pub enum Condition {
     /// Equal
@@ -77,11 +97,6 @@ pub enum Condition {
     /// Signed less than or equal
     LE = 0b1101,
     /// Always (unconditional)
+    #[default]
     AL = 0b1110,
 }
-
-impl Default for Condition {
-    fn default() -> Self {
-        Self::AL
-    }
-}
@@ -9,7 +9,7 @@ pub use buffer::Buffer;
 
 use crate::CIR;
 
-pub fn parse_from_args<T: Structured>(cir: &[CIR]) -> Option<T> {
+pub fn parse_from_args<T: Structured>(cir: &[CIR]) -> Result<T, Error> {
     let mut buffer = Buffer::new(cir);
     // skip till arguments
     while let Some(CIR::Char(_)) = buffer.peek() {
@@ -17,54 +17,122 @@ pub fn parse_from_args<T: Structured>(cir: &[CIR]) -> Option<T> {
     }
     let res = T::parse(&mut buffer)?;
     assert!(buffer.is_empty());
-    Some(res)
+    Ok(res)
 }
 
 macro_rules! match_buffer {
     ($buffer:ident: $pattern:pat => $res:expr) => {{
-        match $buffer.peek()? {
-            $pattern => {
+        match $buffer.peek() {
+            Some($pattern) => {
                 $buffer.bump();
-                Some($res)
+                Ok($res)
             }
-            _ => None,
+            _ => Err(Error::Mismatch),
         }
     }};
 }
 
+/// Why some CIR can't be parsed as a [`Structured`] type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The CIR doesn't have the shape of the type
+    Mismatch,
+    /// `value` needs more than `bits` unsigned bits
+    TooLarge { value: u32, bits: u8 },
+    /// `value` is negative, but only `bits` unsigned bits are encoded
+    Negative { value: i32, bits: u8 },
+    /// The label is `offset` words away, which needs more than `bits` signed bits
+    TooFar { offset: i32, bits: u8 },
+    /// `value` isn't an 8-bit value rotated by an even amount
+    NotRotated(u32),
+    /// The PC is used where the result would be unpredictable
+    ProgramCounter,
+    /// The register is used twice where the registers must differ
+    SameRegister(u32),
+    /// The registers aren't an even register followed by the next one
+    NotPair(u32, u32),
+    /// The instruction has a condition, but is always executed
+    Conditional,
+    /// Fields of a status register are named, but all of it is read
+    Fields,
+    /// The field of `width` bits from `lsb` is empty, or goes past bit 31
+    BitField { lsb: u32, width: u32 },
+    /// The address has an offset, but only a base register is used
+    Offset,
+}
+
 pub trait Structured {
-    fn parse(buffer: &mut Buffer) -> Option<Self>
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error>
     where
         Self: Sized;
+
+    /// The inverse of [`Structured::parse`], writes `self` back out as CIR.
+    fn unparse(&self, cir: &mut Vec<CIR>);
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Label(pub u32, pub bool);
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Condition(pub crate::Condition);
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Register<T: RegName>(pub u32, PhantomData<T>);
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RegisterList(pub u16);
-#[derive(Debug)]
+/// Two registers in a row starting at an even one, like `r2, r3`, held as the first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterPair(pub u32);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Number<const BITS: u8>(pub u32);
-#[derive(Debug)]
+/// A field of bits in a register, its lowest bit and width, like `#8, #4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitField<T>(pub u32, pub u32, PhantomData<T>);
+/// A data-processing immediate, an 8-bit value rotated right by an even amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModImm(pub u32);
+/// Whether an instruction updates the flags, from an optional `S` suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetFlags(pub bool);
+/// Whether the base register is updated, from an optional `!` after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Writeback(pub bool);
+/// A whole status register, set for the `SPSR`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Psr(pub bool);
+/// The fields of a status register that are written, a bit for each of `fsxc`.
+///
+/// Without any fields named, the `f` and `c` fields are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PsrFields(pub bool, pub u32);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sign(pub crate::Sign);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Shift(pub crate::Shift);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Offset;
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PreIndex;
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PostIndex;
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Address<T>(PhantomData<T>);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Bang;
 
+/// A [`BitField`] encoded as its width minus one, by the instructions that extract it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extract;
+/// A [`BitField`] encoded as its highest bit, by the instructions that insert into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Insert;
+
+/// An address that is only a base register, `[rn]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseAddress(pub u32);
+
 impl Structured for Label {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
         match_buffer!(buffer: CIR::Label(lbl) => {
             let signed = lbl.is_negative();
             // value
@@ -75,30 +143,310 @@ impl Structured for Label {
             }
         })
     }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        let Self(value, signed) = *self;
+        let value = if signed {
+            (value as i32).wrapping_neg()
+        } else {
+            value as i32
+        };
+        cir.push(CIR::Label(value));
+    }
+}
+
+/// Instructions without a condition field are always executed.
+impl Default for Condition {
+    fn default() -> Self {
+        Self(crate::Condition::AL)
+    }
 }
 
 impl Structured for Condition {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
         match_buffer!(buffer: CIR::Condition(cond) => Self(cond))
     }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::Condition(self.0));
+    }
 }
 
 impl Structured for RegisterList {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
         match_buffer!(buffer: CIR::RegisterList(mask) => Self(mask))
     }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::RegisterList(self.0));
+    }
+}
+
+impl RegisterPair {
+    /// Whether `first` can start a pair, `r14` can't as the PC would be the second.
+    pub fn starts_pair(first: u32) -> bool {
+        first.is_multiple_of(2) && first < 14
+    }
+}
+
+impl Structured for RegisterPair {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        let first = match_buffer!(buffer: CIR::Register(r) => r)?;
+        let second = match_buffer!(buffer: CIR::Register(r) => r)?;
+        if Self::starts_pair(first) && second == first + 1 {
+            Ok(Self(first))
+        } else {
+            Err(Error::NotPair(first, second))
+        }
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::Register(self.0));
+        cir.push(CIR::Register(self.0 + 1));
+    }
 }
 
 impl<const BITS: u8> Structured for Number<BITS> {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
-        match_buffer!(buffer: CIR::Number(number) => Self(number & ((1 << BITS) - 1)))
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        let number = match_buffer!(buffer: CIR::Number(number) => number)?;
+        Self::new(number)
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::Number(self.0));
+    }
+}
+
+impl<const BITS: u8> Number<BITS> {
+    /// The largest value that fits in `BITS` bits.
+    pub const MAX: u32 = u32::MAX >> (32 - BITS);
+
+    pub fn new(value: u32) -> Result<Self, Error> {
+        if value <= Self::MAX {
+            Ok(Self(value))
+        } else if (value as i32).is_negative() {
+            Err(Error::Negative {
+                value: value as i32,
+                bits: BITS,
+            })
+        } else {
+            Err(Error::TooLarge { value, bits: BITS })
+        }
+    }
+}
+
+impl Number<8> {
+    /// The high and low halves, which some encodings keep apart.
+    pub fn split(self) -> (Number<4>, Number<4>) {
+        (Number(self.0 >> 4), Number(self.0 & 0xF))
+    }
+}
+
+impl From<(Number<4>, Number<4>)> for Number<8> {
+    fn from((high, low): (Number<4>, Number<4>)) -> Self {
+        Self(high.0 << 4 | low.0)
+    }
+}
+
+impl Number<16> {
+    /// The high 12 bits and low 4 bits, which some encodings keep apart.
+    pub fn split(self) -> (Number<12>, Number<4>) {
+        (Number(self.0 >> 4), Number(self.0 & 0xF))
+    }
+}
+
+impl From<(Number<12>, Number<4>)> for Number<16> {
+    fn from((high, low): (Number<12>, Number<4>)) -> Self {
+        Self(high.0 << 4 | low.0)
+    }
+}
+
+impl<T> BitField<T> {
+    pub fn new(lsb: u32, width: u32) -> Result<Self, Error> {
+        if lsb < 32 && (1..=32 - lsb).contains(&width) {
+            Ok(Self(lsb, width, PhantomData))
+        } else {
+            Err(Error::BitField { lsb, width })
+        }
+    }
+}
+
+impl BitField<Extract> {
+    /// The width minus one and the lowest bit, which are apart in the encoding.
+    pub fn split(self) -> (Number<5>, Number<5>) {
+        (Number(self.1 - 1), Number(self.0))
+    }
+}
+
+impl From<(Number<5>, Number<5>)> for BitField<Extract> {
+    fn from((widthm1, lsb): (Number<5>, Number<5>)) -> Self {
+        Self(lsb.0, widthm1.0 + 1, PhantomData)
+    }
+}
+
+impl BitField<Insert> {
+    /// The highest and lowest bits, which are apart in the encoding.
+    pub fn split(self) -> (Number<5>, Number<5>) {
+        (Number(self.0 + self.1 - 1), Number(self.0))
+    }
+}
+
+impl From<(Number<5>, Number<5>)> for BitField<Insert> {
+    fn from((msb, lsb): (Number<5>, Number<5>)) -> Self {
+        // a highest bit below the lowest is unpredictable, so there are no bits
+        Self(lsb.0, (msb.0 + 1).saturating_sub(lsb.0), PhantomData)
+    }
+}
+
+impl<T> Structured for BitField<T> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        let lsb = match_buffer!(buffer: CIR::Number(number) => number)?;
+        let width = match_buffer!(buffer: CIR::Number(number) => number)?;
+        Self::new(lsb, width)
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::Number(self.0));
+        cir.push(CIR::Number(self.1));
+    }
+}
+
+impl ModImm {
+    pub fn new(value: u32) -> Result<Self, Error> {
+        match Self::split(value) {
+            Some(_) => Ok(Self(value)),
+            None => Err(Error::NotRotated(value)),
+        }
+    }
+
+    /// The `(rotation, imm8)` that make `value`, with the smallest rotation.
+    ///
+    /// `value` is `imm8` rotated right by `2 * rotation`.
+    pub fn split(value: u32) -> Option<(u32, u32)> {
+        (0..16).find_map(|rotation| {
+            let imm8 = value.rotate_left(2 * rotation);
+            (imm8 <= 0xFF).then_some((rotation, imm8))
+        })
+    }
+}
+
+impl Structured for ModImm {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        let number = match_buffer!(buffer: CIR::Number(number) => number)?;
+        Self::new(number)
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::Number(self.0));
+    }
+}
+
+impl Structured for SetFlags {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        match buffer.peek() {
+            Some(CIR::SetFlags) => {
+                buffer.bump();
+                Ok(Self(true))
+            }
+            // the suffix is optional
+            _ => Ok(Self(false)),
+        }
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        if self.0 {
+            cir.push(CIR::SetFlags);
+        }
+    }
+}
+
+impl Structured for Writeback {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        match buffer.peek() {
+            Some(CIR::Bang) => {
+                buffer.bump();
+                Ok(Self(true))
+            }
+            // the base is left as is without it
+            _ => Ok(Self(false)),
+        }
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        if self.0 {
+            cir.push(CIR::Bang);
+        }
+    }
+}
+
+impl Structured for Psr {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        match match_buffer!(buffer: CIR::StatusRegister(psr) => psr)? {
+            crate::StatusRegister { spsr, fields: None } => Ok(Self(spsr)),
+            crate::StatusRegister {
+                fields: Some(_), ..
+            } => Err(Error::Fields),
+        }
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::StatusRegister(crate::StatusRegister {
+            spsr: self.0,
+            fields: None,
+        }));
+    }
+}
+
+impl PsrFields {
+    /// The fields written when none are named, `c` and `f`.
+    pub const DEFAULT_MASK: u32 = 0b1001;
+
+    /// The register and mask, which are apart in the encoding.
+    pub fn split(self) -> (Psr, Number<4>) {
+        (Psr(self.0), Number(self.1))
+    }
+}
+
+impl From<(Psr, Number<4>)> for PsrFields {
+    fn from((psr, mask): (Psr, Number<4>)) -> Self {
+        Self(psr.0, mask.0)
+    }
+}
+
+impl Structured for PsrFields {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        match_buffer!(buffer: CIR::StatusRegister(psr) => Self(
+            psr.spsr,
+            psr.fields.map_or(Self::DEFAULT_MASK, u32::from),
+        ))
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::StatusRegister(crate::StatusRegister {
+            spsr: self.0,
+            fields: Some(self.1 as u8),
+        }));
+    }
+}
+
+impl Structured for Sign {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        match_buffer!(buffer: CIR::Sign(sign) => Self(sign))
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::Sign(self.0));
     }
 }
 
 impl Structured for Shift {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
         match_buffer!(buffer: CIR::Shift(shift) => Self(shift))
     }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::Shift(self.0));
+    }
 }
 
 mod private {
@@ -110,22 +458,38 @@ mod private {
     impl Sealed for super::R {}
     impl Sealed for super::S {}
     impl Sealed for super::T {}
+    impl Sealed for super::A {}
+    impl Sealed for super::DLo {}
+    impl Sealed for super::DHi {}
+    impl Sealed for super::T2 {}
 }
 
 pub trait RegName: private::Sealed {}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct D;
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct N;
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct M;
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct R;
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct S;
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct T;
+/// The accumulator of a multiply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct A;
+/// The low word of a long result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DLo;
+/// The high word of a long result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DHi;
+/// The second register transferred
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct T2;
 
 impl RegName for D {}
 impl RegName for N {}
@@ -133,33 +497,98 @@ impl RegName for M {}
 impl RegName for R {}
 impl RegName for S {}
 impl RegName for T {}
+impl RegName for A {}
+impl RegName for DLo {}
+impl RegName for DHi {}
+impl RegName for T2 {}
+
+impl<T: RegName> Register<T> {
+    pub const fn new(value: u32) -> Self {
+        Self(value, PhantomData)
+    }
+}
 
 impl<T: RegName> Structured for Register<T> {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
         match_buffer!(buffer: CIR::Register(value) => Self(value, PhantomData))
     }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::Register(self.0));
+    }
+}
+
+impl<T> Address<T> {
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for Address<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Structured for Address<Offset> {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
         match_buffer!(buffer: CIR::OffsetAddress => Self(PhantomData))
     }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::OffsetAddress);
+    }
 }
 
 impl Structured for Address<PreIndex> {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
         match_buffer!(buffer: CIR::PreIndexAddress => Self(PhantomData))
     }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::PreIndexAddress);
+    }
 }
 
 impl Structured for Address<PostIndex> {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
         match_buffer!(buffer: CIR::PostIndexAddress => Self(PhantomData))
     }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::PostIndexAddress);
+    }
+}
+
+impl Structured for BaseAddress {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
+        match_buffer!(buffer: CIR::OffsetAddress => ())?;
+        let base = match_buffer!(buffer: CIR::Register(value) => value)?;
+        let sign = match_buffer!(buffer: CIR::Sign(sign) => sign)?;
+        let offset = match_buffer!(buffer: CIR::Number(number) => number)?;
+        // `[rn]` is `[rn, #0]`
+        match (sign, offset) {
+            (crate::Sign::Plus, 0) => Ok(Self(base)),
+            _ => Err(Error::Offset),
+        }
+    }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.extend([
+            CIR::OffsetAddress,
+            CIR::Register(self.0),
+            CIR::Sign(crate::Sign::Plus),
+            CIR::Number(0),
+        ]);
+    }
 }
 
 impl Structured for Bang {
-    fn parse(buffer: &mut Buffer) -> Option<Self> {
+    fn parse(buffer: &mut Buffer) -> Result<Self, Error> {
         match_buffer!(buffer: CIR::Bang => Self)
     }
+
+    fn unparse(&self, cir: &mut Vec<CIR>) {
+        cir.push(CIR::Bang);
+    }
 }
@@ -1,6 +1,6 @@
 use crate::CIR;
 
-use super::Structured;
+use super::{Error, Structured};
 
 pub struct Buffer<'a> {
     pos: usize,
@@ -27,7 +27,7 @@ impl<'a> Buffer<'a> {
         self.pos += 1;
     }
 
-    pub fn parse<T: Structured>(&mut self) -> Option<T> {
+    pub fn parse<T: Structured>(&mut self) -> Result<T, Error> {
         T::parse(self)
     }
 }
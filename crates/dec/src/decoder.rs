@@ -0,0 +1,53 @@
+use std::marker::PhantomData;
+
+use byteorder::{ByteOrder, BE, LE};
+
+use enc::Word;
+
+/// Splits a byte slice, as produced by an `Encoder`, back up into [`Word`]s.
+pub struct Decoder<'a, ORDER> {
+    buffer: &'a [u8],
+    _order: PhantomData<ORDER>,
+}
+
+impl<'a, ORDER> Decoder<'a, ORDER> {
+    /// Trailing bytes that do not make up a whole [`Word`].
+    pub fn remainder(&self) -> &'a [u8] {
+        self.buffer
+    }
+}
+
+impl<'a> Decoder<'a, BE> {
+    pub fn new_be(buffer: &'a [u8]) -> Self {
+        Self::new(buffer)
+    }
+}
+
+impl<'a> Decoder<'a, LE> {
+    pub fn new_le(buffer: &'a [u8]) -> Self {
+        Self::new(buffer)
+    }
+}
+
+impl<'a, ORDER: ByteOrder> Decoder<'a, ORDER> {
+    fn new(buffer: &'a [u8]) -> Self {
+        Self {
+            buffer,
+            _order: PhantomData,
+        }
+    }
+}
+
+impl<ORDER: ByteOrder> Iterator for Decoder<'_, ORDER> {
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.len() < 4 {
+            return None;
+        }
+
+        let (word, rest) = self.buffer.split_at(4);
+        self.buffer = rest;
+        Some(Word::base(ORDER::read_u32(word)))
+    }
+}
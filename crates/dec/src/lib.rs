@@ -0,0 +1,196 @@
+#[cfg(feature = "macros")]
+extern crate encode_proc;
+
+mod decoder;
+mod reader;
+pub mod render;
+
+use cir::structured;
+pub use decoder::Decoder;
+pub use enc::Word;
+pub use reader::WordReader;
+
+#[cfg(feature = "macros")]
+pub use encode_proc::decodable;
+
+pub trait Decodable: Sized {
+    /// The number of bits `Self` is decoded from.
+    const SIZE: u8 = 32;
+
+    /// Decodes `Self` from the lowest [`Decodable::SIZE`] bits of `word`,
+    /// returning `None` if the bits are not a valid encoding.
+    fn decode(word: Word) -> Option<Self>;
+}
+
+/// The result of decoding a [`Word`] that may not be claimed by `T`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoded<T> {
+    Known(T),
+    Unknown(Word),
+}
+
+pub fn decode<T: Decodable>(word: Word) -> Decoded<T> {
+    match T::decode(word) {
+        Some(it) => Decoded::Known(it),
+        None => Decoded::Unknown(word),
+    }
+}
+
+impl Decodable for bool {
+    const SIZE: u8 = 1;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(word.get() == 1)
+    }
+}
+
+impl Decodable for structured::Label {
+    const SIZE: u8 = 12;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self(word.get(), false))
+    }
+}
+
+impl Decodable for structured::Condition {
+    const SIZE: u8 = 4;
+
+    fn decode(word: Word) -> Option<Self> {
+        use cir::Condition::*;
+
+        let cond = match word.get() {
+            0b0000 => EQ,
+            0b0001 => NE,
+            0b0010 => CS,
+            0b0011 => CC,
+            0b0100 => MI,
+            0b0101 => PL,
+            0b0110 => VS,
+            0b0111 => VC,
+            0b1000 => HI,
+            0b1001 => LS,
+            0b1010 => GE,
+            0b1011 => LT,
+            0b1100 => GT,
+            0b1101 => LE,
+            0b1110 => AL,
+            // unconditional instruction space
+            _ => return None,
+        };
+
+        Some(Self(cond))
+    }
+}
+
+impl<T: structured::RegName> Decodable for structured::Register<T> {
+    const SIZE: u8 = 4;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self::new(word.get()))
+    }
+}
+
+impl Decodable for structured::RegisterList {
+    const SIZE: u8 = 16;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self(word.get() as u16))
+    }
+}
+
+impl Decodable for structured::RegisterPair {
+    const SIZE: u8 = 4;
+
+    fn decode(word: Word) -> Option<Self> {
+        structured::RegisterPair::starts_pair(word.get()).then_some(Self(word.get()))
+    }
+}
+
+impl<const BITS: u8> Decodable for structured::Number<BITS> {
+    const SIZE: u8 = BITS;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self(word.get()))
+    }
+}
+
+impl Decodable for structured::ModImm {
+    const SIZE: u8 = 12;
+
+    fn decode(word: Word) -> Option<Self> {
+        let (rotation, imm8) = (word.get() >> 8, word.get() & 0xFF);
+        Some(Self(imm8.rotate_right(2 * rotation)))
+    }
+}
+
+impl Decodable for structured::SetFlags {
+    const SIZE: u8 = 1;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self(word.get() == 1))
+    }
+}
+
+impl Decodable for structured::Writeback {
+    const SIZE: u8 = 1;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self(word.get() == 1))
+    }
+}
+
+impl Decodable for structured::BaseAddress {
+    const SIZE: u8 = 4;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self(word.get()))
+    }
+}
+
+impl Decodable for structured::Psr {
+    const SIZE: u8 = 1;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self(word.get() == 1))
+    }
+}
+
+impl Decodable for structured::Sign {
+    const SIZE: u8 = 1;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self(match word.get() {
+            1 => cir::Sign::Plus,
+            _ => cir::Sign::Minus,
+        }))
+    }
+}
+
+impl Decodable for structured::Shift {
+    const SIZE: u8 = 2;
+
+    fn decode(word: Word) -> Option<Self> {
+        Some(Self(match word.get() {
+            0b00 => cir::Shift::LSL,
+            0b01 => cir::Shift::LSR,
+            0b10 => cir::Shift::ASR,
+            _ => cir::Shift::ROR,
+        }))
+    }
+}
+
+impl<T> Decodable for structured::Address<T> {
+    const SIZE: u8 = 0;
+
+    fn decode(_: Word) -> Option<Self> {
+        Some(Self::new())
+    }
+}
+
+impl Decodable for structured::Bang {
+    const SIZE: u8 = 0;
+
+    fn decode(_: Word) -> Option<Self> {
+        Some(Self)
+    }
+}
@@ -0,0 +1,38 @@
+use enc::Word;
+
+use crate::Decodable;
+
+/// Reads fields out of a [`Word`] from the most significant bit downwards,
+/// the inverse of [`enc::WordBuilder`].
+pub struct WordReader {
+    cursor: u8,
+    word: Word,
+}
+
+impl WordReader {
+    pub const fn new(word: Word) -> Self {
+        Self { cursor: 32, word }
+    }
+
+    /// Have all 32 bits been read.
+    pub const fn is_empty(&self) -> bool {
+        self.cursor == 0
+    }
+
+    pub fn read<T: Decodable>(&mut self) -> Option<T> {
+        let size = T::SIZE;
+        if size == 0 {
+            // takes up no bits, decode from nothing
+            return T::decode(Word::empty());
+        }
+
+        self.cursor = self.cursor.checked_sub(size)?;
+        T::decode(Word::base(self.word.extract(self.cursor, size)))
+    }
+
+    /// Reads `size` bits, failing if they are not equal to `bits`.
+    pub fn expect(&mut self, bits: u32, size: u8) -> Option<()> {
+        self.cursor = self.cursor.checked_sub(size)?;
+        (self.word.extract(self.cursor, size) == bits).then_some(())
+    }
+}
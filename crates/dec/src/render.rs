@@ -0,0 +1,159 @@
+//! Renders decoded [`CIR`] back into HAND source text.
+
+use cir::{Condition, Shift, Sign, StatusRegister, CIR};
+
+/// Renders a single instruction, `[Char.., Condition, SetFlags?, ..args]`, as HAND.
+///
+/// Labels cannot be recovered from an encoding, so `label` is used to name
+/// the PC-relative offset of each [`CIR::Label`].
+pub fn render(cir: &[CIR], label: impl Fn(i32) -> String) -> String {
+    let mut text = String::new();
+    let mut parts = cir.iter().copied().peekable();
+
+    // mnemonic
+    while let Some(CIR::Char(c)) = parts.peek() {
+        text.push(*c);
+        parts.next();
+    }
+
+    // condition suffix, AL is implied
+    let mut condition = String::new();
+    if let Some(CIR::Condition(cond)) = parts.peek() {
+        if *cond != Condition::AL {
+            condition = format!("{cond:?}");
+        }
+        parts.next();
+    }
+
+    // the `S` suffix comes before the condition
+    if let Some(CIR::SetFlags) = parts.peek() {
+        text.push('S');
+        parts.next();
+    }
+    text.push_str(&condition);
+
+    let mut operands = Vec::new();
+    // addresses are always the last argument to an instruction
+    let mut address = None;
+    // a subtracted offset is written with a `-`
+    let mut sign = "";
+
+    while let Some(part) = parts.next() {
+        match part {
+            CIR::Register(r) => {
+                operands.push(format!("{}{}", std::mem::take(&mut sign), register(r)))
+            }
+            CIR::Bang => {
+                if let Some(last) = operands.last_mut() {
+                    last.push('!');
+                }
+            }
+            CIR::RegisterList(mask) => operands.push(register_list(mask)),
+            CIR::StatusRegister(psr) => operands.push(status_register(psr)),
+            CIR::Number(n) => operands.push(format!("#{}{n}", std::mem::take(&mut sign))),
+            CIR::Sign(Sign::Plus) => (),
+            CIR::Sign(Sign::Minus) => sign = "-",
+            CIR::Label(offset) => operands.push(label(offset)),
+            CIR::Shift(kind) => match (kind, parts.next()) {
+                // no shift at all
+                (Shift::LSL, Some(CIR::Number(0))) => (),
+                (Shift::RRX, _) | (Shift::ROR, Some(CIR::Number(0))) => {
+                    operands.push("RRX".to_string())
+                }
+                // a shift by 32 is encoded as 0
+                (Shift::LSR | Shift::ASR, Some(CIR::Number(0))) => {
+                    operands.push(format!("{kind:?} #32"))
+                }
+                (kind, Some(CIR::Number(n))) => operands.push(format!("{kind:?} #{n}")),
+                (kind, Some(CIR::Register(r))) => {
+                    operands.push(format!("{kind:?} {}", register(r)))
+                }
+                (kind, _) => operands.push(format!("{kind:?}")),
+            },
+            CIR::OffsetAddress | CIR::PreIndexAddress | CIR::PostIndexAddress => {
+                address = Some((part, operands.len()))
+            }
+            CIR::Instruction(_)
+            | CIR::Char(_)
+            | CIR::Condition(_)
+            | CIR::SetFlags
+            | CIR::Byte(_)
+            | CIR::Half(_)
+            | CIR::Word(_) => (),
+        }
+    }
+
+    if let Some((kind, start)) = address {
+        let mut inner = operands.split_off(start);
+        // `[rn, #0]` is written as `[rn]`
+        if kind == CIR::OffsetAddress && inner.len() == 2 && inner[1] == "#0" {
+            inner.pop();
+        }
+        match (kind, inner.split_first()) {
+            (CIR::PostIndexAddress, Some((base, offset))) => {
+                operands.push(format!("[{base}]"));
+                operands.extend_from_slice(offset);
+            }
+            (CIR::PreIndexAddress, _) => operands.push(format!("[{}]!", inner.join(", "))),
+            _ => operands.push(format!("[{}]", inner.join(", "))),
+        }
+    }
+
+    if !operands.is_empty() {
+        text.push(' ');
+        text.push_str(&operands.join(", "));
+    }
+
+    text
+}
+
+fn register(r: u32) -> String {
+    match r {
+        13 => "sp".to_string(),
+        14 => "lr".to_string(),
+        15 => "pc".to_string(),
+        r => format!("r{r}"),
+    }
+}
+
+fn status_register(StatusRegister { spsr, fields }: StatusRegister) -> String {
+    let mut text = if spsr { "SPSR" } else { "CPSR" }.to_string();
+    if let Some(mask) = fields {
+        text.push('_');
+        for (bit, field) in [(0b1000, 'f'), (0b0100, 's'), (0b0010, 'x'), (0b0001, 'c')] {
+            if mask & bit != 0 {
+                text.push(field);
+            }
+        }
+    }
+    text
+}
+
+/// Groups runs of three or more registers into a range.
+fn register_list(mask: u16) -> String {
+    let mut items = Vec::new();
+    let mut reg = 0;
+    while reg < 16 {
+        if mask & (1 << reg) == 0 {
+            reg += 1;
+            continue;
+        }
+
+        let start = reg;
+        while reg < 16 && mask & (1 << reg) != 0 {
+            reg += 1;
+        }
+        let end = reg - 1;
+
+        match end - start {
+            0 => items.push(register(start)),
+            1 => {
+                items.push(register(start));
+                items.push(register(end));
+            }
+            _ => items.push(format!("{}-{}", register(start), register(end))),
+        }
+    }
+
+    format!("{{{}}}", items.join(", "))
+}
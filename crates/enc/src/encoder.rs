@@ -44,4 +44,14 @@ impl<ORDER: ByteOrder> Encoder<ORDER> {
             .write_u32::<ORDER>(word.get())
             .expect("Buffer can be written to");
     }
+
+    pub fn push_half(&mut self, half: u16) {
+        self.buffer
+            .write_u16::<ORDER>(half)
+            .expect("Buffer can be written to");
+    }
+
+    pub fn push_byte(&mut self, byte: u8) {
+        self.buffer.push(byte);
+    }
 }
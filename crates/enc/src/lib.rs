@@ -99,6 +99,17 @@ impl Encodable for structured::RegisterList {
     }
 }
 
+/// Only the first register of a pair is encoded.
+impl Encodable for structured::RegisterPair {
+    fn encode(&self) -> Word {
+        Word::base(self.0)
+    }
+
+    fn size(&self) -> u8 {
+        4
+    }
+}
+
 impl<const BITS: u8> Encodable for structured::Number<BITS> {
     fn encode(&self) -> Word {
         Word::base(self.0)
@@ -109,6 +120,75 @@ impl<const BITS: u8> Encodable for structured::Number<BITS> {
     }
 }
 
+impl Encodable for structured::ModImm {
+    fn encode(&self) -> Word {
+        let (rotation, imm8) =
+            structured::ModImm::split(self.0).expect("ModImm is always encodable");
+        Word::base(rotation << 8 | imm8)
+    }
+
+    fn size(&self) -> u8 {
+        12
+    }
+}
+
+impl Encodable for structured::SetFlags {
+    fn encode(&self) -> Word {
+        self.0.encode()
+    }
+
+    fn size(&self) -> u8 {
+        1
+    }
+}
+
+/// The W bit.
+impl Encodable for structured::Writeback {
+    fn encode(&self) -> Word {
+        self.0.encode()
+    }
+
+    fn size(&self) -> u8 {
+        1
+    }
+}
+
+/// Only the base register is encoded.
+impl Encodable for structured::BaseAddress {
+    fn encode(&self) -> Word {
+        Word::base(self.0)
+    }
+
+    fn size(&self) -> u8 {
+        4
+    }
+}
+
+/// The R bit, set for the `SPSR`.
+impl Encodable for structured::Psr {
+    fn encode(&self) -> Word {
+        self.0.encode()
+    }
+
+    fn size(&self) -> u8 {
+        1
+    }
+}
+
+/// The U bit, set when the offset is added.
+impl Encodable for structured::Sign {
+    fn encode(&self) -> Word {
+        Word::base(match self.0 {
+            cir::Sign::Plus => 1,
+            cir::Sign::Minus => 0,
+        })
+    }
+
+    fn size(&self) -> u8 {
+        1
+    }
+}
+
 impl Encodable for structured::Shift {
     fn encode(&self) -> Word {
         Word::base(match self.0 {
@@ -4,7 +4,7 @@ use super::*;
 
 #[derive(Pattern, Structured)]
 #[name = "ADD"]
-pub struct AddImm(Condition, Register<D>, Register<N>, Number<12>);
+pub struct AddImm(Condition, Register<D>, Register<N>, ModImm);
 
 impl Encodable for AddImm {
     fn encode(&self) -> Word {
@@ -15,3 +15,5 @@ impl Encodable for AddImm {
 }
 
 macros::test_encoding!(add_imm of AddImm; "ADD r0, r0, #0" => 0b1110_0010_1000_0000_0000_0000_0000_0000);
+macros::test_encoding!(add_imm_rotated of AddImm; "ADD r0, r0, #0x1000" => 0b1110_0010_1000_0000_0000_1010_0000_0001);
+macros::test_encoding!(add_imm_wrapped of AddImm; "ADD r0, r0, #0xF000000F" => 0b1110_0010_1000_0000_0000_0010_1111_1111);
@@ -26,7 +26,9 @@ impl WordBuilder {
 
         let word = enc.encode();
         self.cursor -= size;
-        self.word = self.word.with(word.get(), self.cursor);
+        // only take the bits that fit in the field
+        let bits = word.extract(0, size);
+        self.word = self.word.with(bits, self.cursor);
         self
     }
 }
@@ -59,6 +61,15 @@ impl Word {
         self.0 |= bits << offset;
         self
     }
+
+    /// Reads `size` bits starting at `offset`, the inverse of [`Word::with`].
+    pub const fn extract(&self, offset: u8, size: u8) -> u32 {
+        if size >= 32 {
+            self.0 >> offset
+        } else {
+            (self.0 >> offset) & ((1 << size) - 1)
+        }
+    }
 }
 
 impl std::fmt::Debug for Word {
@@ -0,0 +1,290 @@
+use std::collections::{HashMap, HashSet};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    spanned::Spanned, Expr, ExprLit, ExprUnary, Ident, ImplItem, ItemImpl, Lit, Macro, Pat, Path,
+    Stmt, UnOp,
+};
+
+use crate::{EncodeStream, Item};
+
+type Error = (Span, String);
+
+/// A `let` in `encode` that converts between bindings,
+/// which has to be undone once the fields are decoded.
+enum Conversion {
+    /// `let to = Path(*from);`
+    Wrap { to: Ident, path: Path, from: Ident },
+    /// `let to = !from;`
+    Not { to: Ident, from: Ident },
+    /// `let (high, low) = from.split();`
+    Split {
+        high: Ident,
+        low: Ident,
+        from: Ident,
+    },
+}
+
+pub(crate) fn derive(item: &ItemImpl) -> Result<TokenStream, Error> {
+    let module = crate::crate_name("dec");
+    let self_ty = &item.self_ty;
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+
+    let encode = item
+        .items
+        .iter()
+        .find_map(|item| match item {
+            ImplItem::Fn(f) if f.sig.ident == "encode" => Some(f),
+            _ => None,
+        })
+        .ok_or((item.span(), "Expected an `encode` function".to_string()))?;
+
+    let mut fields = None;
+    let mut bound = HashSet::new();
+    let mut constants = HashMap::new();
+    let mut conversions = Vec::new();
+    let mut schema = None;
+
+    for stmt in &encode.block.stmts {
+        match stmt {
+            Stmt::Local(local) => {
+                let Some(init) = &local.init else {
+                    return Err((local.span(), "Expected a value".to_string()));
+                };
+
+                match (&local.pat, &*init.expr) {
+                    (pat, Expr::Path(path)) if path.path.is_ident("self") => {
+                        fields = Some(construct(pat, &mut bound)?);
+                    }
+                    (Pat::Ident(id), Expr::Lit(lit)) => {
+                        constants.insert(id.ident.clone(), constant(lit)?);
+                    }
+                    (
+                        Pat::Ident(id),
+                        Expr::Unary(ExprUnary {
+                            op: UnOp::Not(_),
+                            expr,
+                            ..
+                        }),
+                    ) => conversions.push(Conversion::Not {
+                        to: id.ident.clone(),
+                        from: binding(expr)?,
+                    }),
+                    (Pat::Tuple(tuple), Expr::MethodCall(call))
+                        if call.method == "split" && call.args.is_empty() =>
+                    {
+                        let [Pat::Ident(high), Pat::Ident(low)] =
+                            tuple.elems.iter().collect::<Vec<_>>()[..]
+                        else {
+                            return Err((tuple.span(), "Expected `(high, low)`".to_string()));
+                        };
+                        conversions.push(Conversion::Split {
+                            high: high.ident.clone(),
+                            low: low.ident.clone(),
+                            from: binding(&call.receiver)?,
+                        })
+                    }
+                    (Pat::Ident(id), Expr::Call(call)) if call.args.len() == 1 => {
+                        let Expr::Path(func) = &*call.func else {
+                            return Err((call.func.span(), "Expected a tuple struct".to_string()));
+                        };
+                        conversions.push(Conversion::Wrap {
+                            to: id.ident.clone(),
+                            path: func.path.clone(),
+                            from: binding(&call.args[0])?,
+                        });
+                    }
+                    _ => {
+                        return Err((
+                            local.span(),
+                            "Cannot derive a decoder from this statement".to_string(),
+                        ))
+                    }
+                }
+            }
+            Stmt::Macro(stmt) if is_encode(&stmt.mac) => schema = Some(&stmt.mac),
+            Stmt::Expr(Expr::Macro(expr), None) if is_encode(&expr.mac) => schema = Some(&expr.mac),
+            _ => {
+                return Err((
+                    stmt.span(),
+                    "Cannot derive a decoder from this statement".to_string(),
+                ))
+            }
+        }
+    }
+
+    let Some(fields) = fields else {
+        return Err((encode.span(), "Expected `let Self(..) = self;`".to_string()));
+    };
+    let Some(schema) = schema else {
+        return Err((encode.span(), "Expected an `encode![]` schema".to_string()));
+    };
+    let EncodeStream { items } = schema
+        .parse_body::<EncodeStream>()
+        .map_err(|e| (e.span(), e.to_string()))?;
+
+    let reads = reads(&items, &constants)?;
+
+    // every field must come from the encoding
+    let mut decoded = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Field(id) if !constants.contains_key(id) => Some(id.clone()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+    // a field that was already decoded must agree with its conversion
+    let conversions = conversions
+        .iter()
+        .rev()
+        .map(|conversion| match conversion {
+            Conversion::Split { from, .. } if decoded.contains(from) => {
+                Err((from.span(), format!("`{from}` is split more than once")))
+            }
+            // the halves are joined back together
+            Conversion::Split { high, low, from } => {
+                decoded.insert(from.clone());
+                Ok(quote! { let #from = ::core::convert::From::from((#high, #low)); })
+            }
+            Conversion::Wrap { to, path, from } if !decoded.insert(from.clone()) => Ok(quote! {
+                let #path(converted) = #to;
+                if converted != #from {
+                    return None;
+                }
+            }),
+            Conversion::Wrap { to, path, from } => Ok(quote! { let #path(#from) = #to; }),
+            // only flags can be negated
+            Conversion::Not { to, from } if !decoded.insert(from.clone()) => Ok(quote! {
+                if #from != <bool as ::core::ops::Not>::not(#to) {
+                    return None;
+                }
+            }),
+            Conversion::Not { to, from } => Ok(quote! {
+                let #from = <bool as ::core::ops::Not>::not(#to);
+            }),
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    if let Some(missing) = bound.iter().find(|id| !decoded.contains(*id)) {
+        return Err((
+            missing.span(),
+            format!("`{missing}` is not part of the encoding"),
+        ));
+    }
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #module::Decodable for #self_ty #where_clause {
+            fn decode(word: #module::Word) -> Option<Self> {
+                let mut reader = #module::WordReader::new(word);
+                #(#reads)*
+                #(#conversions)*
+                Some(#fields)
+            }
+        }
+    })
+}
+
+/// Reads each field in order, checking that fixed bits match.
+fn reads(items: &[Item], constants: &HashMap<Ident, (u32, u8)>) -> Result<Vec<TokenStream>, Error> {
+    let mut reads = Vec::new();
+    let mut seen = HashSet::new();
+    // consecutive fixed bits are checked together
+    let mut fixed = (0_u32, 0_u8);
+
+    fn flush(reads: &mut Vec<TokenStream>, fixed: &mut (u32, u8)) {
+        let (bits, size) = std::mem::take(fixed);
+        if size != 0 {
+            reads.push(quote! { reader.expect(#bits, #size)?; });
+        }
+    }
+
+    for item in items {
+        let (bits, size) = match item {
+            Item::Bit(bit) => (*bit, 1),
+            Item::Field(id) => match constants.get(id) {
+                Some(constant) => *constant,
+                None => {
+                    if !seen.insert(id) {
+                        return Err((id.span(), format!("`{id}` is encoded more than once")));
+                    }
+                    flush(&mut reads, &mut fixed);
+                    reads.push(quote! { let #id = reader.read()?; });
+                    continue;
+                }
+            },
+        };
+
+        let (value, width) = fixed;
+        fixed = ((value << size) | bits, width + size);
+    }
+    flush(&mut reads, &mut fixed);
+
+    Ok(reads)
+}
+
+/// Turns the pattern that destructures `self` back into an expression that builds it.
+fn construct(pat: &Pat, bound: &mut HashSet<Ident>) -> Result<TokenStream, Error> {
+    match pat {
+        Pat::TupleStruct(tuple) => {
+            let path = &tuple.path;
+            let elems = tuple
+                .elems
+                .iter()
+                .map(|pat| construct(pat, bound))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(quote! { #path(#(#elems),*) })
+        }
+        Pat::Ident(id) => {
+            let id = &id.ident;
+            bound.insert(id.clone());
+            Ok(quote! { #id })
+        }
+        // not encoded, so it can't be recovered
+        Pat::Wild(_) => Ok(quote! { ::core::default::Default::default() }),
+        _ => Err((pat.span(), "Expected a tuple struct pattern".to_string())),
+    }
+}
+
+/// The value and size of a fixed field, sized the same way `Encodable` sizes it.
+fn constant(lit: &ExprLit) -> Result<(u32, u8), Error> {
+    match &lit.lit {
+        Lit::Bool(b) => Ok((b.value as u32, 1)),
+        Lit::Int(int) => {
+            let value = int
+                .base10_parse::<u32>()
+                .map_err(|e| (e.span(), e.to_string()))?;
+            let size = if value == 0 {
+                1
+            } else {
+                value.ilog2() as u8 + 1
+            };
+            Ok((value, size))
+        }
+        _ => Err((lit.span(), "Expected an int or bool literal".to_string())),
+    }
+}
+
+/// `binding` or `*binding`
+fn binding(expr: &Expr) -> Result<Ident, Error> {
+    match expr {
+        Expr::Unary(ExprUnary {
+            op: UnOp::Deref(_),
+            expr,
+            ..
+        }) => binding(expr),
+        Expr::Path(path) => path
+            .path
+            .get_ident()
+            .cloned()
+            .ok_or((path.span(), "Expected a binding".to_string())),
+        _ => Err((expr.span(), "Expected a binding".to_string())),
+    }
+}
+
+fn is_encode(mac: &Macro) -> bool {
+    mac.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "encode")
+}
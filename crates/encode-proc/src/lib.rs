@@ -1,24 +1,31 @@
+mod decode;
+
 use proc_macro::TokenStream;
 use proc_macro_error2::{abort, proc_macro_error};
 use quote::quote;
-use syn::{ext::IdentExt, parse::Parse, parse_macro_input, Ident, LitInt, Token};
+use syn::{ext::IdentExt, parse::Parse, parse_macro_input, Ident, ItemImpl, LitInt, Token};
+
+/// A single part of an encoding schema.
+pub(crate) enum Item {
+    /// A value that knows how to encode itself.
+    Field(Ident),
+    /// A fixed bit.
+    Bit(u32),
+}
 
-struct EncodeStream {
-    items: Vec<proc_macro2::TokenStream>,
+pub(crate) struct EncodeStream {
+    items: Vec<Item>,
 }
 
 impl Parse for EncodeStream {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut this = EncodeStream { items: Vec::new() };
-        for _ in 0..32 {
-            if input.is_empty() {
-                break;
-            }
+        while !input.is_empty() {
             // could be either ident, '|', or int literal
             let lookahead = input.lookahead1();
             if lookahead.peek(Ident::peek_any) {
                 let id: Ident = input.parse()?;
-                this.items.push(quote! { encode(#id) });
+                this.items.push(Item::Field(id));
             } else if lookahead.peek(Token![|]) {
                 let _: Token![|] = input.parse()?;
                 // ignore this!
@@ -30,7 +37,7 @@ impl Parse for EncodeStream {
                     abort!(lit, "Int literals must be a binary value, 0 or 1");
                 }
 
-                this.items.push(quote! { encode(#value) });
+                this.items.push(Item::Bit(value));
             } else {
                 return Err(lookahead.error());
             }
@@ -40,13 +47,14 @@ impl Parse for EncodeStream {
     }
 }
 
-pub(crate) fn crate_name() -> proc_macro2::TokenStream {
+pub(crate) fn crate_name(name: &str) -> proc_macro2::TokenStream {
     use proc_macro2::Span;
     use proc_macro_crate::FoundCrate;
     use quote::quote;
     use syn::Ident;
 
-    let found_crate = proc_macro_crate::crate_name("enc").expect("enc is present in `Cargo.toml`");
+    let found_crate = proc_macro_crate::crate_name(name)
+        .unwrap_or_else(|_| panic!("{name} is present in `Cargo.toml`"));
 
     match found_crate {
         FoundCrate::Itself => quote!(crate),
@@ -62,7 +70,12 @@ pub(crate) fn crate_name() -> proc_macro2::TokenStream {
 #[proc_macro]
 pub fn encode(stream: TokenStream) -> TokenStream {
     let EncodeStream { items, .. } = parse_macro_input!(stream as EncodeStream);
-    let module = crate_name();
+    let module = crate_name("enc");
+
+    let items = items.iter().map(|item| match item {
+        Item::Field(id) => quote! { encode(#id) },
+        Item::Bit(value) => quote! { encode(#value) },
+    });
 
     quote! {
         {
@@ -73,3 +86,26 @@ pub fn encode(stream: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+/// Derives `Decodable` from the `encode![]` schema of an `Encodable` impl,
+/// so that an instruction's encoder and decoder can never drift apart.
+///
+/// The body of `encode` may only contain:
+/// * `let Self(a, b, Label(c, _)) = self;` to name the fields,
+/// * `let s = 0;` or `let u = true;` for fixed bits,
+/// * `let imm12 = Number::<12>(*c);` or `let u = !c;` to convert a field,
+/// * and finally the `encode![]` schema.
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn decodable(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemImpl);
+
+    match decode::derive(&item) {
+        Ok(decoder) => quote! {
+            #item
+            #decoder
+        }
+        .into(),
+        Err((span, msg)) => abort!(span, msg),
+    }
+}
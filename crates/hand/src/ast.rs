@@ -4,6 +4,7 @@ mod validate;
 
 pub use node::*;
 pub use token::*;
+pub use validate::validate;
 
 use crate::{
     grammar::{SyntaxNode, SyntaxToken},
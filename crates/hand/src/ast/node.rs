@@ -1,12 +1,17 @@
-use super::{AstNode, AstToken, Bang, Ident, PunctKind};
+use parser::rowan::TextRange;
+
+use super::{AstNode, AstToken, Bang, Char, Hash, Ident, LiteralError, PunctKind, Str};
 use crate::{
-    grammar::{SyntaxElement, SyntaxNode},
+    grammar::{SyntaxElement, SyntaxNode, SyntaxToken},
+    lowering::MAX_SPACE,
     syntax::SyntaxKind,
 };
 
 macros::node!(pub struct Root(SyntaxKind::Root));
 macros::node!(pub struct Stmt(SyntaxKind::Statement));
 macros::node!(pub struct Instr(SyntaxKind::Instruction));
+macros::node!(pub struct Directive(SyntaxKind::Directive));
+macros::node!(pub struct Constant(SyntaxKind::Constant));
 macros::node!(pub struct Args(SyntaxKind::Arguments));
 macros::node!(pub struct Item(SyntaxKind::Item));
 macros::node!(pub struct OffsetAddress(SyntaxKind::OffsetAddress));
@@ -17,8 +22,14 @@ macros::node!(pub struct Shift(SyntaxKind::Shift));
 macros::node!(pub struct Register(SyntaxKind::Register));
 macros::node!(pub struct RegList(SyntaxKind::RegisterList));
 macros::node!(pub struct RegRange(SyntaxKind::RegisterRange));
+macros::node!(pub struct StatusReg(SyntaxKind::StatusRegister));
 macros::node!(pub struct Label(SyntaxKind::Label));
 macros::node!(pub struct Number(SyntaxKind::Number));
+macros::node!(pub struct PoolLiteral(SyntaxKind::PoolLiteral));
+macros::node!(pub struct BinaryExpr(SyntaxKind::BinaryExpr));
+macros::node!(pub struct UnaryExpr(SyntaxKind::UnaryExpr));
+macros::node!(pub struct ParenExpr(SyntaxKind::ParenExpr));
+macros::node!(pub struct Text(SyntaxKind::Text));
 macros::node!(pub struct Name(SyntaxKind::Name));
 macros::node!(pub struct Punct(SyntaxKind::Punct));
 macros::node!(pub struct Error(SyntaxKind::Error));
@@ -26,11 +37,15 @@ macros::node!(pub struct Error(SyntaxKind::Error));
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ItemKind {
     Register(Register),
+    StatusReg(StatusReg),
     Name(Name),
     Number(Number),
     Punct(Punct),
     Address(Address),
+    Shift(Shift),
     RegList(RegList),
+    Text(Text),
+    PoolLiteral(PoolLiteral),
     Error(Error),
 }
 
@@ -39,7 +54,16 @@ impl AstNode for ItemKind {
         use SyntaxKind::*;
         matches!(
             kind,
-            Register | Name | Number | Punct | RegisterList | Error
+            Register
+                | StatusRegister
+                | Name
+                | Number
+                | Punct
+                | Shift
+                | RegisterList
+                | Text
+                | PoolLiteral
+                | Error
         ) || Address::castable(kind)
     }
 
@@ -50,10 +74,14 @@ impl AstNode for ItemKind {
 
         let res = match node.kind() {
             SyntaxKind::Register => Self::Register(Register(node)),
+            SyntaxKind::StatusRegister => Self::StatusReg(StatusReg(node)),
             SyntaxKind::Name => Self::Name(Name(node)),
             SyntaxKind::Number => Self::Number(Number(node)),
             SyntaxKind::Punct => Self::Punct(Punct(node)),
+            SyntaxKind::Shift => Self::Shift(Shift(node)),
             SyntaxKind::RegisterList => Self::RegList(RegList(node)),
+            SyntaxKind::Text => Self::Text(Text(node)),
+            SyntaxKind::PoolLiteral => Self::PoolLiteral(PoolLiteral(node)),
             SyntaxKind::Error => Self::Error(Error(node)),
             _ => return None,
         };
@@ -64,11 +92,15 @@ impl AstNode for ItemKind {
     fn syntax(&self) -> &SyntaxNode {
         match self {
             ItemKind::Register(n) => n.syntax(),
+            ItemKind::StatusReg(n) => n.syntax(),
             ItemKind::Name(n) => n.syntax(),
             ItemKind::Number(n) => n.syntax(),
             ItemKind::Punct(n) => n.syntax(),
+            ItemKind::Shift(n) => n.syntax(),
             ItemKind::Address(n) => n.syntax(),
             ItemKind::RegList(n) => n.syntax(),
+            ItemKind::Text(n) => n.syntax(),
+            ItemKind::PoolLiteral(n) => n.syntax(),
             ItemKind::Error(n) => n.syntax(),
         }
     }
@@ -165,6 +197,129 @@ impl AstNode for RegListItem {
     }
 }
 
+/// The value of a number, literals and names are tokens and nodes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr {
+    Binary(BinaryExpr),
+    Unary(UnaryExpr),
+    Paren(ParenExpr),
+    Name(Name),
+    /// Decimal | Hex | Octal | Binary | Char
+    Literal(SyntaxToken),
+}
+
+impl Expr {
+    pub fn cast(element: SyntaxElement) -> Option<Self> {
+        let res = match element {
+            SyntaxElement::Node(node) => match node.kind() {
+                SyntaxKind::BinaryExpr => Self::Binary(BinaryExpr(node)),
+                SyntaxKind::UnaryExpr => Self::Unary(UnaryExpr(node)),
+                SyntaxKind::ParenExpr => Self::Paren(ParenExpr(node)),
+                SyntaxKind::Name => Self::Name(Name(node)),
+                _ => return None,
+            },
+            SyntaxElement::Token(token) => {
+                use SyntaxKind::*;
+                match token.kind() {
+                    Decimal | Hex | Octal | Binary | Char => Self::Literal(token),
+                    _ => return None,
+                }
+            }
+        };
+
+        Some(res)
+    }
+
+    /// The range of the expression, without the trivia around it.
+    pub fn text_range(&self) -> TextRange {
+        let node = match self {
+            Self::Binary(n) => n.syntax(),
+            Self::Unary(n) => n.syntax(),
+            Self::Paren(n) => n.syntax(),
+            Self::Name(n) => n.syntax(),
+            Self::Literal(t) => return t.text_range(),
+        };
+
+        let mut tokens = node
+            .descendants_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .filter(|token| !matches!(token.kind(), SyntaxKind::Whitespace | SyntaxKind::Comment))
+            .map(|token| token.text_range());
+        let first = tokens.next().unwrap_or_else(|| node.text_range());
+        tokens.fold(first, TextRange::cover)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOp {
+    /// +
+    Add,
+    /// -
+    Sub,
+    /// *
+    Mul,
+    /// /
+    Div,
+    /// %
+    Rem,
+    /// <<
+    Shl,
+    /// >>
+    Shr,
+    /// &
+    And,
+    /// |
+    Or,
+    /// ^
+    Xor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnaryOp {
+    /// -
+    Neg,
+    /// +
+    Plus,
+    /// ~
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DirectiveKind {
+    /// .word value(, value)*
+    Word,
+    /// .half value(, value)*
+    Half,
+    /// .byte value(, value)*
+    Byte,
+    /// .space size(, fill)?
+    Space,
+    /// .align power?
+    Align,
+    /// .ascii text(, text)*
+    Ascii,
+    /// .asciz text(, text)*, each followed by a 0
+    Asciz,
+    /// .ltorg, places the literal pool
+    Ltorg,
+}
+
+impl DirectiveKind {
+    /// The largest the `index`th value can be, strings have no limit
+    pub fn limit(self, index: usize) -> Option<u32> {
+        let max = match (self, index) {
+            (Self::Word, _) => u32::MAX,
+            (Self::Half, _) => u16::MAX as u32,
+            (Self::Byte, _) | (Self::Space, 1) => u8::MAX as u32,
+            (Self::Space, _) => MAX_SPACE,
+            (Self::Align, _) => 16,
+            (Self::Ascii | Self::Asciz | Self::Ltorg, _) => return None,
+        };
+
+        Some(max)
+    }
+}
+
 #[allow(clippy::upper_case_acronyms)]
 pub enum ShiftKind {
     LSL { amount: Option<NumOrReg> },
@@ -188,6 +343,14 @@ impl Stmt {
     pub fn instruction(&self) -> Option<Instr> {
         self.syntax().children().find_map(Instr::cast)
     }
+
+    pub fn directive(&self) -> Option<Directive> {
+        self.syntax().children().find_map(Directive::cast)
+    }
+
+    pub fn constant(&self) -> Option<Constant> {
+        self.syntax().children().find_map(Constant::cast)
+    }
 }
 
 impl Instr {
@@ -200,6 +363,92 @@ impl Instr {
     }
 }
 
+impl Directive {
+    pub fn name(&self) -> Option<Name> {
+        self.syntax().children().find_map(Name::cast)
+    }
+
+    pub fn kind(&self) -> Option<DirectiveKind> {
+        let kind = match self.name()?.ident()?.text().to_ascii_lowercase().as_str() {
+            "word" => DirectiveKind::Word,
+            "half" => DirectiveKind::Half,
+            "byte" => DirectiveKind::Byte,
+            "space" => DirectiveKind::Space,
+            "align" => DirectiveKind::Align,
+            "ascii" => DirectiveKind::Ascii,
+            "asciz" => DirectiveKind::Asciz,
+            "ltorg" => DirectiveKind::Ltorg,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    pub fn args(&self) -> Args {
+        self.syntax().children().find_map(Args::cast).unwrap()
+    }
+
+    /// The arguments that aren't punctuation.
+    pub fn values(&self) -> impl Iterator<Item = ItemKind> {
+        self.args()
+            .iter()
+            .map(|item| item.kind())
+            .filter(|kind| !matches!(kind, ItemKind::Punct(_)))
+    }
+}
+
+impl Constant {
+    /// `.equ name, value` has its name in the arguments,
+    /// `name EQU value` has it before the keyword.
+    pub fn is_directive(&self) -> bool {
+        self.syntax()
+            .children_with_tokens()
+            .any(|element| element.kind() == SyntaxKind::Dot)
+    }
+
+    /// The keyword, `equ` or `EQU`.
+    pub fn keyword(&self) -> Option<Name> {
+        let mut names = self.syntax().children().filter_map(Name::cast);
+        if self.is_directive() {
+            names.next()
+        } else {
+            names.nth(1)
+        }
+    }
+
+    pub fn name(&self) -> Option<Name> {
+        if self.is_directive() {
+            match self.values().next()? {
+                ItemKind::Name(name) => Some(name),
+                _ => None,
+            }
+        } else {
+            self.syntax().children().find_map(Name::cast)
+        }
+    }
+
+    pub fn value(&self) -> Option<ItemKind> {
+        let mut values = self.values();
+        if self.is_directive() {
+            values.nth(1)
+        } else {
+            values.next()
+        }
+    }
+
+    pub fn args(&self) -> Args {
+        self.syntax().children().find_map(Args::cast).unwrap()
+    }
+
+    /// The arguments that aren't punctuation, including the name of a `.equ`.
+    pub fn values(&self) -> impl Iterator<Item = ItemKind> {
+        self.args()
+            .iter()
+            .map(|item| item.kind())
+            .filter(|kind| !matches!(kind, ItemKind::Punct(_)))
+    }
+}
+
 impl Args {
     pub fn iter(&self) -> impl Iterator<Item = Item> {
         self.syntax().children().filter_map(Item::cast)
@@ -216,11 +465,8 @@ impl Item {
 }
 
 impl Address {
-    pub fn base(&self) -> Register {
-        self.syntax()
-            .first_child()
-            .and_then(Register::cast)
-            .unwrap()
+    pub fn base(&self) -> Option<Register> {
+        self.syntax().first_child().and_then(Register::cast)
     }
 
     pub fn offset(&self) -> Option<Offset> {
@@ -229,11 +475,14 @@ impl Address {
 }
 
 impl Offset {
-    pub fn amount(&self) -> NumOrReg {
-        self.syntax()
-            .first_child()
-            .and_then(NumOrReg::cast)
-            .unwrap()
+    pub fn amount(&self) -> Option<NumOrReg> {
+        self.syntax().children().find_map(NumOrReg::cast)
+    }
+
+    /// Whether the offset register is subtracted from the base, as in `[r1, -r2]`.
+    pub fn is_subtracted(&self) -> bool {
+        let sign = self.syntax().first_child().and_then(Punct::cast);
+        sign.is_some_and(|sign| matches!(sign.kind(), PunctKind::Minus(_)))
     }
 
     pub fn shift(&self) -> Option<Shift> {
@@ -243,7 +492,7 @@ impl Offset {
 
 impl Shift {
     pub fn kind(&self) -> Option<ShiftKind> {
-        let name = self.name().ident()?;
+        let name = self.name()?.ident()?;
         let amount = self.amount();
 
         let kind = match name.text() {
@@ -252,14 +501,14 @@ impl Shift {
             "ASR" => ShiftKind::ASR { amount },
             "ROR" => ShiftKind::ROR { amount },
             "RRX" => ShiftKind::RRX,
-            _ => unreachable!(),
+            _ => return None,
         };
 
         Some(kind)
     }
 
-    pub fn name(&self) -> Name {
-        self.syntax().first_child().and_then(Name::cast).unwrap()
+    pub fn name(&self) -> Option<Name> {
+        self.syntax().first_child().and_then(Name::cast)
     }
 
     pub fn amount(&self) -> Option<NumOrReg> {
@@ -277,6 +526,16 @@ impl RegList {
     pub fn items(&self) -> impl Iterator<Item = RegListItem> {
         self.syntax().children().filter_map(RegListItem::cast)
     }
+
+    /// Whether the register is in the list, on its own or in a range.
+    pub fn contains(&self, value: u32) -> bool {
+        self.items().any(|item| match item {
+            RegListItem::Group(group) => group
+                .range()
+                .is_some_and(|(low, high)| (low..=high).contains(&value)),
+            RegListItem::Single(register) => register.value() == Some(value),
+        })
+    }
 }
 
 impl RegRange {
@@ -303,8 +562,12 @@ impl RegRange {
 }
 
 impl Register {
+    pub fn ident(&self) -> Option<Ident> {
+        self.syntax().first_token().and_then(Ident::cast)
+    }
+
     pub fn value(&self) -> Option<u32> {
-        let id = self.syntax().first_token().and_then(Ident::cast)?;
+        let id = self.ident()?;
         let text = id.text().to_lowercase();
 
         if let Some(rest) = text.strip_prefix('r') {
@@ -329,6 +592,47 @@ impl Register {
     }
 }
 
+impl StatusReg {
+    pub fn ident(&self) -> Option<Ident> {
+        self.syntax().first_token().and_then(Ident::cast)
+    }
+
+    /// Whether this is the `SPSR`, rather than the `CPSR`.
+    pub fn is_spsr(&self) -> bool {
+        self.ident()
+            .is_some_and(|id| id.text().to_uppercase().starts_with("SPSR"))
+    }
+
+    /// The fields after the `_`, like the `fc` of `CPSR_fc`.
+    pub fn fields(&self) -> Option<String> {
+        let id = self.ident()?;
+        let (_, fields) = id.text().split_once('_')?;
+        Some(fields.to_lowercase())
+    }
+
+    /// A bit for each field, from `f` as the highest to `c` as the lowest.
+    ///
+    /// `None` if there are no fields, or they aren't each one of `fsxc`.
+    pub fn mask(&self) -> Option<u8> {
+        let fields = self.fields()?;
+        let mut mask = 0;
+        for field in fields.chars() {
+            let bit = match field {
+                'f' => 0b1000,
+                's' => 0b0100,
+                'x' => 0b0010,
+                'c' => 0b0001,
+                _ => return None,
+            };
+            if mask & bit != 0 {
+                return None;
+            }
+            mask |= bit;
+        }
+        (mask != 0).then_some(mask)
+    }
+}
+
 impl Name {
     pub fn ident(&self) -> Option<Ident> {
         self.syntax()
@@ -348,17 +652,126 @@ impl Punct {
 }
 
 impl Number {
+    pub fn hash(&self) -> Option<Hash> {
+        self.syntax()
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find_map(Hash::cast)
+    }
+
+    pub fn expr(&self) -> Option<Expr> {
+        self.syntax().children_with_tokens().find_map(Expr::cast)
+    }
+
+    /// The literal, if the number is only a literal
+    pub fn literal(&self) -> Option<SyntaxToken> {
+        match self.expr()? {
+            Expr::Literal(token) => Some(token),
+            _ => None,
+        }
+    }
+
+    /// The value of a literal, expressions are folded once lowered.
     pub fn value(&self) -> Option<u32> {
-        let number_token = self.syntax().last_token()?;
-        let number_text = number_token.text();
-        let radix = match number_token.kind() {
-            SyntaxKind::Decimal => 10,
-            SyntaxKind::Hex => 16,
-            SyntaxKind::Octal => 8,
-            SyntaxKind::Binary => 2,
-            _ => unreachable!(),
+        literal_value(&self.literal()?)
+    }
+}
+
+/// The value of a Decimal, Hex, Octal, Binary or Char token.
+pub fn literal_value(token: &SyntaxToken) -> Option<u32> {
+    let text = token.text();
+    let (radix, digits) = match token.kind() {
+        SyntaxKind::Char => {
+            return Char::cast(token.clone())?.value().ok().map(u32::from);
+        }
+        SyntaxKind::Decimal => (10, text),
+        SyntaxKind::Hex => (16, &text[2..]),
+        SyntaxKind::Octal => (8, &text[2..]),
+        SyntaxKind::Binary => (2, &text[2..]),
+        _ => return None,
+    };
+    let digits = digits.replace('_', "");
+    u32::from_str_radix(&digits, radix).ok()
+}
+
+impl PoolLiteral {
+    pub fn number(&self) -> Option<Number> {
+        self.syntax().children().find_map(Number::cast)
+    }
+}
+
+impl BinaryExpr {
+    pub fn op(&self) -> Option<(BinaryOp, SyntaxToken)> {
+        self.syntax()
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find_map(|token| {
+                let op = match token.kind() {
+                    SyntaxKind::Plus => BinaryOp::Add,
+                    SyntaxKind::Minus => BinaryOp::Sub,
+                    SyntaxKind::Star => BinaryOp::Mul,
+                    SyntaxKind::Slash => BinaryOp::Div,
+                    SyntaxKind::Percent => BinaryOp::Rem,
+                    SyntaxKind::ShiftLeft => BinaryOp::Shl,
+                    SyntaxKind::ShiftRight => BinaryOp::Shr,
+                    SyntaxKind::Ampersand => BinaryOp::And,
+                    SyntaxKind::Pipe => BinaryOp::Or,
+                    SyntaxKind::Caret => BinaryOp::Xor,
+                    _ => return None,
+                };
+                Some((op, token))
+            })
+    }
+
+    /// The operand before the operator.
+    pub fn lhs(&self) -> Option<Expr> {
+        let (_, op) = self.op()?;
+        self.syntax()
+            .children_with_tokens()
+            .take_while(|element| element.as_token() != Some(&op))
+            .find_map(Expr::cast)
+    }
+
+    /// The operand after the operator.
+    pub fn rhs(&self) -> Option<Expr> {
+        let (_, op) = self.op()?;
+        self.syntax()
+            .children_with_tokens()
+            .skip_while(|element| element.as_token() != Some(&op))
+            .find_map(Expr::cast)
+    }
+}
+
+impl UnaryExpr {
+    pub fn op(&self) -> Option<UnaryOp> {
+        let op = match self.syntax().first_token()?.kind() {
+            SyntaxKind::Minus => UnaryOp::Neg,
+            SyntaxKind::Plus => UnaryOp::Plus,
+            SyntaxKind::Tilde => UnaryOp::Not,
+            _ => return None,
         };
-        u32::from_str_radix(number_text, radix).ok()
+
+        Some(op)
+    }
+
+    pub fn operand(&self) -> Option<Expr> {
+        self.syntax().children_with_tokens().find_map(Expr::cast)
+    }
+}
+
+impl ParenExpr {
+    pub fn inner(&self) -> Option<Expr> {
+        self.syntax().children_with_tokens().find_map(Expr::cast)
+    }
+}
+
+impl Text {
+    pub fn str(&self) -> Option<Str> {
+        self.syntax().first_token().and_then(Str::cast)
+    }
+
+    pub fn value(&self) -> Result<Vec<u8>, LiteralError> {
+        self.str().expect("text holds a string").value()
     }
 }
 
@@ -375,7 +788,7 @@ mod macros {
                 }
 
                 fn cast(node: SyntaxNode) -> Option<Self> {
-                    if node.kind() == $kind {
+                    if Self::castable(node.kind()) {
                         Some(Self(node))
                     } else {
                         None
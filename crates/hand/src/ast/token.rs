@@ -1,21 +1,17 @@
+use parser::rowan::{TextRange, TextSize};
+
 use super::AstToken;
 use crate::grammar::SyntaxToken;
 use crate::syntax::SyntaxKind;
 
 macros::token!(pub struct Ident(SyntaxKind::Ident));
+macros::token!(pub struct Str(SyntaxKind::Str));
+macros::token!(pub struct Char(SyntaxKind::Char));
 macros::token!(pub struct Hash(SyntaxKind::Hash));
 macros::token!(pub struct Comma(SyntaxKind::Comma));
 macros::token!(pub struct Plus(SyntaxKind::Plus));
 macros::token!(pub struct Minus(SyntaxKind::Minus));
 macros::token!(pub struct Bang(SyntaxKind::Bang));
-macros::token!(pub struct Colon(SyntaxKind::Colon));
-macros::token!(pub struct Equals(SyntaxKind::Equals));
-macros::token!(pub struct OpenCurly(SyntaxKind::OpenCurly));
-macros::token!(pub struct CloseCurly(SyntaxKind::CloseCurly));
-macros::token!(pub struct OpenSquare(SyntaxKind::OpenSquare));
-macros::token!(pub struct CloseSquare(SyntaxKind::CloseSquare));
-macros::token!(pub struct CurlyBrace(SyntaxKind::OpenCurly | SyntaxKind::CloseCurly));
-macros::token!(pub struct SquareBrace(SyntaxKind::OpenSquare | SyntaxKind::CloseSquare));
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PunctKind {
@@ -53,6 +49,113 @@ impl AstToken for PunctKind {
     }
 }
 
+/// A problem inside a string or character literal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralError {
+    pub range: TextRange,
+    pub message: &'static str,
+    pub hint: Option<&'static str>,
+}
+
+impl Str {
+    /// The bytes between the quotes, with escapes replaced.
+    pub fn value(&self) -> Result<Vec<u8>, LiteralError> {
+        unescape(self.syntax(), '"')
+    }
+}
+
+impl Char {
+    /// The byte between the quotes, with escapes replaced.
+    pub fn value(&self) -> Result<u8, LiteralError> {
+        match unescape(self.syntax(), '\'')?.as_slice() {
+            [byte] => Ok(*byte),
+            _ => Err(LiteralError {
+                range: self.syntax().text_range(),
+                message: "a character literal holds exactly one character",
+                hint: None,
+            }),
+        }
+    }
+}
+
+/// Replaces the escapes in a quoted literal:
+/// `\n`, `\t`, `\r`, `\0`, `\\`, `\"`, `\'` and `\xHH`.
+fn unescape(token: &SyntaxToken, quote: char) -> Result<Vec<u8>, LiteralError> {
+    let start = token.text_range().start();
+    let text = token.text();
+    let error = |from: usize, to: usize, message| LiteralError {
+        range: TextRange::new(
+            start + TextSize::from(from as u32),
+            start + TextSize::from(to as u32),
+        ),
+        message,
+        hint: None,
+    };
+
+    let mut bytes = Vec::new();
+    // skip the opening quote
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        let byte = match c {
+            c if c == quote => {
+                return match chars.next() {
+                    None => Ok(bytes),
+                    Some(_) => unreachable!("the lexer ends literals at the closing quote"),
+                };
+            }
+            '\\' => match chars.next() {
+                Some((_, 'n')) => b'\n',
+                Some((_, 't')) => b'\t',
+                Some((_, 'r')) => b'\r',
+                Some((_, '0')) => b'\0',
+                Some((_, '\\')) => b'\\',
+                Some((_, '"')) => b'"',
+                Some((_, '\'')) => b'\'',
+                Some((_, 'x')) => {
+                    let digits = chars
+                        .next_if(|(_, c)| c.is_ascii_hexdigit())
+                        .zip(chars.next_if(|(_, c)| c.is_ascii_hexdigit()));
+                    match digits {
+                        Some(((j, _), _)) => {
+                            u8::from_str_radix(&text[j..j + 2], 16).expect("two hex digits")
+                        }
+                        None => {
+                            let end = chars.peek().map_or(text.len(), |(j, _)| *j);
+                            return Err(error(i, end, "`\\x` is followed by two hex digits"));
+                        }
+                    }
+                }
+                Some((j, c)) => {
+                    return Err(LiteralError {
+                        hint: Some(r#"the escapes are \n, \t, \r, \0, \\, \", \' and \xHH"#),
+                        ..error(i, j + c.len_utf8(), "unknown escape")
+                    });
+                }
+                None => break,
+            },
+            c if c.is_ascii() => c as u8,
+            c => {
+                return Err(error(
+                    i,
+                    i + c.len_utf8(),
+                    "only ASCII characters are supported",
+                ))
+            }
+        };
+        bytes.push(byte);
+    }
+
+    let (message, hint) = match quote {
+        '"' => ("unterminated string", "strings end with a `\"`"),
+        _ => ("unterminated character", "characters end with a `'`"),
+    };
+    Err(LiteralError {
+        range: token.text_range(),
+        message,
+        hint: Some(hint),
+    })
+}
+
 mod macros {
     macro_rules! token {
         ($v:vis struct $ast:ident($kind:pat)) => {
@@ -1,5 +1,480 @@
-use crate::ast::Root;
+use std::collections::HashSet;
 
-pub fn validate(root: Root) {
-    todo!()
+use crate::{
+    ast::{
+        literal_value, AstNode, AstToken, Char, Constant, Directive, DirectiveKind, Ident, Instr,
+        Item, ItemKind, LiteralError, Name, NumOrReg, Number, RegList, RegRange, Register, Root,
+        Shift, ShiftKind, StatusReg, Str,
+    },
+    grammar::SyntaxToken,
+    syntax::SyntaxKind,
+    Diagnostic,
+};
+
+/// Checks the meaning of a tree that parsed, before it is lowered.
+pub fn validate(root: Root, diagnostics: &mut Vec<Diagnostic>) {
+    let nodes = || root.syntax().descendants();
+
+    for register in nodes().filter_map(Register::cast) {
+        validate_register(register, diagnostics);
+    }
+    for range in nodes().filter_map(RegRange::cast) {
+        validate_register_range(range, diagnostics);
+    }
+    for register in nodes().filter_map(StatusReg::cast) {
+        validate_status_register(register, diagnostics);
+    }
+    for list in nodes().filter_map(RegList::cast) {
+        validate_register_list(list, diagnostics);
+    }
+    for instr in nodes().filter_map(Instr::cast) {
+        validate_writeback(instr, diagnostics);
+    }
+    for shift in nodes().filter_map(Shift::cast) {
+        validate_shift(shift, diagnostics);
+    }
+    for number in nodes().filter_map(Number::cast) {
+        validate_number(number, diagnostics);
+    }
+    for token in root
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+    {
+        validate_literal(token, diagnostics);
+    }
+    for directive in nodes().filter_map(Directive::cast) {
+        validate_directive(directive, diagnostics);
+    }
+    for constant in nodes().filter_map(Constant::cast) {
+        validate_constant(constant, diagnostics);
+    }
+    validate_names(&root, diagnostics);
+}
+
+/// r0 to r15
+fn validate_register(register: Register, diagnostics: &mut Vec<Diagnostic>) {
+    if register.value().is_some_and(|value| value <= 15) {
+        return;
+    }
+
+    let Some(id) = register.ident() else {
+        return;
+    };
+    diagnostics.push(
+        Diagnostic::error(
+            id.syntax().text_range(),
+            format!("register `{}` does not exist", id.text()),
+        )
+        .with_hint("registers go from r0 to r15"),
+    );
+}
+
+/// {low-high}
+fn validate_register_range(range: RegRange, diagnostics: &mut Vec<Diagnostic>) {
+    let Some((low, high)) = range.range() else {
+        return;
+    };
+
+    if low > high {
+        diagnostics.push(
+            Diagnostic::error(range.syntax().text_range(), "register range goes backwards")
+                .with_hint(format!("ranges go from low to high, try `r{high}-r{low}`")),
+        );
+    }
+}
+
+/// CPSR_fsxc, each field at most once
+fn validate_status_register(register: StatusReg, diagnostics: &mut Vec<Diagnostic>) {
+    if register.fields().is_none() || register.mask().is_some() {
+        return;
+    }
+
+    let Some(id) = register.ident() else {
+        return;
+    };
+    diagnostics.push(
+        Diagnostic::error(
+            id.syntax().text_range(),
+            format!("`{}` doesn't name fields of the register", id.text()),
+        )
+        .with_hint("the fields are f, s, x and c, each used once, like `CPSR_fc`"),
+    );
+}
+
+/// {registers}, with at least one
+fn validate_register_list(list: RegList, diagnostics: &mut Vec<Diagnostic>) {
+    if list.items().next().is_none() {
+        diagnostics.push(
+            Diagnostic::error(list.syntax().text_range(), "register list is empty")
+                .with_hint("lists hold at least one register, like `{r0}`"),
+        );
+    }
+}
+
+/// rn!, {registers} where rn isn't in the list
+fn validate_writeback(instr: Instr, diagnostics: &mut Vec<Diagnostic>) {
+    let args = instr
+        .args()
+        .iter()
+        .map(|item| item.kind())
+        .collect::<Vec<_>>();
+    let Some(base) = args.iter().find_map(|kind| match kind {
+        ItemKind::Register(register) if register.bang().is_some() => Some(register),
+        _ => None,
+    }) else {
+        return;
+    };
+    let (Some(id), Some(value)) = (base.ident(), base.value()) else {
+        return;
+    };
+
+    let in_list = args.iter().any(|kind| match kind {
+        ItemKind::RegList(list) => list.contains(value),
+        _ => false,
+    });
+    if in_list {
+        diagnostics.push(
+            Diagnostic::warning(
+                id.syntax().text_range(),
+                format!(
+                    "`{}` is written back, but it is also in the list",
+                    id.text()
+                ),
+            )
+            .with_hint("the value it is left with is unpredictable, try leaving out the `!`"),
+        );
+    }
+}
+
+/// LSL #0-31, LSR #1-32, ASR #1-32, ROR #1-31
+fn validate_shift(shift: Shift, diagnostics: &mut Vec<Diagnostic>) {
+    let (name, amount, range) = match shift.kind() {
+        Some(ShiftKind::LSL { amount }) => ("LSL", amount, 0..=31),
+        Some(ShiftKind::LSR { amount }) => ("LSR", amount, 1..=32),
+        Some(ShiftKind::ASR { amount }) => ("ASR", amount, 1..=32),
+        Some(ShiftKind::ROR { amount }) => ("ROR", amount, 1..=31),
+        Some(ShiftKind::RRX) | None => return,
+    };
+
+    // shifting by a register is checked when it runs
+    let Some(NumOrReg::Num(number)) = amount else {
+        return;
+    };
+    let Some(value) = number.value() else {
+        return;
+    };
+
+    if !range.contains(&value) {
+        diagnostics.push(
+            Diagnostic::error(
+                number.syntax().text_range(),
+                format!("cannot shift by {value} with {name}"),
+            )
+            .with_hint(format!(
+                "{name} shifts by #{} to #{}",
+                range.start(),
+                range.end()
+            )),
+        );
+    }
+}
+
+/// Literals that don't fit are dropped while lowering
+fn validate_number(number: Number, diagnostics: &mut Vec<Diagnostic>) {
+    let literals = number
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| {
+            use SyntaxKind::*;
+            matches!(token.kind(), Decimal | Hex | Octal | Binary)
+        });
+    for literal in literals {
+        if literal_value(&literal).is_none() {
+            diagnostics.push(
+                Diagnostic::error(literal.text_range(), "number does not fit in 32 bits")
+                    .with_hint("the largest number is 0xFFFF_FFFF"),
+            );
+        }
+    }
+}
+
+/// Strings and characters are closed and only hold known escapes
+fn validate_literal(token: SyntaxToken, diagnostics: &mut Vec<Diagnostic>) {
+    let value = match token.kind() {
+        SyntaxKind::Str => Str::cast(token).map(|s| s.value().map(drop)),
+        SyntaxKind::Char => Char::cast(token).map(|c| c.value().map(drop)),
+        _ => None,
+    };
+
+    if let Some(Err(LiteralError {
+        range,
+        message,
+        hint,
+    })) = value
+    {
+        let diagnostic = Diagnostic::error(range, message);
+        diagnostics.push(match hint {
+            Some(hint) => diagnostic.with_hint(hint),
+            None => diagnostic,
+        });
+    }
+}
+
+/// Known directives, with the values they take
+fn validate_directive(directive: Directive, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(name) = directive.name().and_then(|name| name.ident()) else {
+        return;
+    };
+    let Some(kind) = directive.kind() else {
+        diagnostics.push(
+            Diagnostic::error(
+                name.syntax().text_range(),
+                format!("unknown directive `.{}`", name.text()),
+            )
+            .with_hint(
+                "the directives are .word, .half, .byte, .space, .align, .ascii, .asciz, .ltorg and .equ",
+            ),
+        );
+        return;
+    };
+
+    let values = directive.values().collect::<Vec<_>>();
+    let count = match kind {
+        DirectiveKind::Word
+        | DirectiveKind::Half
+        | DirectiveKind::Byte
+        | DirectiveKind::Ascii
+        | DirectiveKind::Asciz => 1..=usize::MAX,
+        DirectiveKind::Space => 1..=2,
+        DirectiveKind::Align => 0..=1,
+        DirectiveKind::Ltorg => 0..=0,
+    };
+    if !count.contains(&values.len()) {
+        let expected = match kind {
+            DirectiveKind::Word | DirectiveKind::Half | DirectiveKind::Byte => "at least one value",
+            DirectiveKind::Ascii | DirectiveKind::Asciz => "at least one string",
+            DirectiveKind::Space => "a size and an optional fill byte",
+            DirectiveKind::Align => "at most one power of two",
+            DirectiveKind::Ltorg => "no values",
+        };
+        diagnostics.push(Diagnostic::error(
+            directive.syntax().text_range(),
+            format!("`.{}` takes {expected}", name.text()),
+        ));
+        return;
+    }
+
+    if matches!(kind, DirectiveKind::Ascii | DirectiveKind::Asciz) {
+        for value in values {
+            if !matches!(value, ItemKind::Text(_)) {
+                diagnostics.push(Diagnostic::error(
+                    value.syntax().text_range(),
+                    format!("`.{}` only takes strings", name.text()),
+                ));
+            }
+        }
+        return;
+    }
+
+    for (index, value) in values.into_iter().enumerate() {
+        // labels are only allowed in words
+        let max = kind.limit(index).expect("strings were checked above");
+        let labels = kind == DirectiveKind::Word;
+
+        match value {
+            ItemKind::Number(number) => match number.value() {
+                Some(value) if value > max => diagnostics.push(Diagnostic::error(
+                    number.syntax().text_range(),
+                    format!(
+                        "`.{}` can't be given {value}, the largest is {max}",
+                        name.text()
+                    ),
+                )),
+                _ => (),
+            },
+            ItemKind::Name(label) if !labels => diagnostics.push(
+                Diagnostic::error(
+                    label.syntax().text_range(),
+                    format!("`.{}` only takes numbers", name.text()),
+                )
+                .with_hint("labels are addresses, which need a `.word`"),
+            ),
+            ItemKind::Text(text) => diagnostics.push(
+                Diagnostic::error(
+                    text.syntax().text_range(),
+                    format!("`.{}` only takes numbers", name.text()),
+                )
+                .with_hint("strings are placed with `.ascii` or `.asciz`"),
+            ),
+            _ => (),
+        }
+    }
+}
+
+/// .equ name, value | name EQU value
+fn validate_constant(constant: Constant, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(keyword) = constant.keyword().and_then(|name| name.ident()) else {
+        return;
+    };
+
+    let (expected, usage) = if constant.is_directive() {
+        (2, format!(".{} NAME, value", keyword.text()))
+    } else {
+        (1, format!("NAME {} value", keyword.text()))
+    };
+    if constant.name().is_none() || constant.values().count() != expected {
+        diagnostics.push(
+            Diagnostic::error(
+                constant.syntax().text_range(),
+                format!("`{}` takes a name and a value", keyword.text()),
+            )
+            .with_hint(format!("constants are defined like `{usage}`")),
+        );
+        return;
+    }
+
+    // a name is another constant
+    match constant.value() {
+        Some(ItemKind::Number(_) | ItemKind::Name(_)) | None => (),
+        Some(value) => diagnostics.push(Diagnostic::error(
+            value.syntax().text_range(),
+            format!("`{}` only takes a number", keyword.text()),
+        )),
+    }
+}
+
+/// Labels and constants are defined once, and every name used is defined.
+///
+/// Labels can be used anywhere, constants have to be defined before they are used.
+fn validate_names(root: &Root, diagnostics: &mut Vec<Diagnostic>) {
+    let mut labels = HashSet::new();
+    for stmt in root.statements() {
+        let Some(id) = stmt.label().and_then(|label| label.name().ident()) else {
+            continue;
+        };
+
+        if !labels.insert(id.text().to_owned()) {
+            diagnostics.push(
+                Diagnostic::error(
+                    id.syntax().text_range(),
+                    format!("label `{}` is defined more than once", id.text()),
+                )
+                .with_hint("each label can only be defined once, try renaming this one"),
+            );
+        }
+    }
+
+    let constants = root
+        .statements()
+        .filter_map(|stmt| stmt.constant()?.name()?.ident())
+        .map(|id| id.text().to_owned())
+        .collect::<HashSet<_>>();
+
+    // the constants defined above the current statement
+    let mut defined = HashSet::new();
+    // a name in a number, which is a label unless `constant`
+    let check = |id: &Ident, constant: bool, defined: &HashSet<String>| {
+        let diagnostic = if defined.contains(id.text()) {
+            return None;
+        } else if labels.contains(id.text()) {
+            if !constant {
+                return None;
+            }
+            Diagnostic::error(
+                id.syntax().text_range(),
+                format!("`{}` is a label, not a constant", id.text()),
+            )
+            .with_hint("addresses aren't known until the code is laid out")
+        } else if constants.contains(id.text()) {
+            Diagnostic::error(
+                id.syntax().text_range(),
+                format!("constant `{}` is used before it is defined", id.text()),
+            )
+            .with_hint("constants have to be defined above where they are used")
+        } else {
+            Diagnostic::error(
+                id.syntax().text_range(),
+                format!("`{}` is not defined", id.text()),
+            )
+            .with_hint("names in numbers are labels, or constants defined with `.equ`")
+        };
+        Some(diagnostic)
+    };
+
+    for stmt in root.statements() {
+        // constants and sizes are known before the code is laid out
+        let constant = stmt.constant().is_some()
+            || stmt
+                .directive()
+                .and_then(|directive| directive.kind())
+                .is_some_and(|kind| matches!(kind, DirectiveKind::Space | DirectiveKind::Align));
+
+        for number in stmt.syntax().descendants().filter_map(Number::cast) {
+            for name in number.syntax().descendants().filter_map(Name::cast) {
+                if let Some(id) = name.ident() {
+                    diagnostics.extend(check(&id, constant, &defined));
+                }
+            }
+        }
+
+        if let Some(constant) = stmt.constant() {
+            // the value can be another constant
+            if let Some(ItemKind::Name(name)) = constant.value() {
+                if let Some(id) = name.ident() {
+                    diagnostics.extend(check(&id, true, &defined));
+                }
+            }
+
+            let Some(id) = constant.name().and_then(|name| name.ident()) else {
+                continue;
+            };
+            if labels.contains(id.text()) {
+                diagnostics.push(
+                    Diagnostic::error(
+                        id.syntax().text_range(),
+                        format!("`{}` is already a label", id.text()),
+                    )
+                    .with_hint("labels and constants share names, try renaming this one"),
+                );
+            } else if !defined.insert(id.text().to_owned()) {
+                diagnostics.push(
+                    Diagnostic::error(
+                        id.syntax().text_range(),
+                        format!("constant `{}` is defined more than once", id.text()),
+                    )
+                    .with_hint("constants can't be changed once they are defined"),
+                );
+            }
+            continue;
+        }
+
+        let used = stmt
+            .syntax()
+            .descendants()
+            .filter_map(Item::cast)
+            .filter_map(|item| match item.kind() {
+                ItemKind::Name(name) => name.ident(),
+                _ => None,
+            });
+        for id in used {
+            if labels.contains(id.text()) {
+                continue;
+            }
+            let diagnostic = if constants.contains(id.text()) {
+                Diagnostic::error(
+                    id.syntax().text_range(),
+                    format!("constant `{}` is used without a `#`", id.text()),
+                )
+                .with_hint(format!("constants are used like `#{}`", id.text()))
+            } else {
+                Diagnostic::error(
+                    id.syntax().text_range(),
+                    format!("label `{}` is not defined", id.text()),
+                )
+            };
+            diagnostics.push(diagnostic);
+        }
+    }
 }
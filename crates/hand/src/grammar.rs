@@ -1,6 +1,6 @@
 use std::{mem, sync::Arc};
 
-use parser::{rowan, Marker};
+use parser::{rowan, Marker, SyntaxError};
 
 use crate::{syntax::SyntaxKind, HAND};
 
@@ -12,7 +12,7 @@ use crate::syntax::SyntaxKind::*;
 
 type Parser = parser::Parser<HAND>;
 
-pub fn parse(text: Arc<str>) -> SyntaxNode {
+pub fn parse(text: Arc<str>) -> (SyntaxNode, Vec<SyntaxError>) {
     let tokens = crate::lexer::lex(Arc::clone(&text));
     let mut parser = Parser::new(text, tokens);
     root(&mut parser);
@@ -22,13 +22,16 @@ pub fn parse(text: Arc<str>) -> SyntaxNode {
 /// statement(s)
 fn root(p: &mut Parser) {
     let m = p.start();
-    while p.peek().is_some() {
-        statement(p);
-
+    loop {
         // clean up empty lines
         while let Some(NewLine) = p.peek() {
             p.bump(NewLine);
         }
+
+        if p.at_end() {
+            break;
+        }
+        statement(p);
     }
     m.finish(p, Root);
 }
@@ -37,14 +40,30 @@ fn root(p: &mut Parser) {
 fn statement(p: &mut Parser) {
     let m = p.start();
 
-    // label?
-    let label = label(p);
-
-    // instr?
-    instruction(p, label.err());
+    if p.at(Ident) {
+        // label?
+        match label(p) {
+            // directive
+            Ok(()) if p.at(Dot) => directive(p),
+            // name EQU value
+            Err(m) if is_keyword(p, "EQU") => constant(p, m),
+            // instr?
+            label => instruction(p, label.err()),
+        }
+    } else if p.at(Dot) {
+        directive(p);
+    } else {
+        // skip the rest of the line
+        p.error("expected a label or an instruction");
+        let e = p.start();
+        while !p.at_end() && !p.at(NewLine) {
+            p.bump_any();
+        }
+        e.finish(p, Error);
+    }
 
     // \n
-    if !p.eat(NewLine) {
+    if !p.eat(NewLine) && !p.at_end() {
         unexpected(p);
     }
 
@@ -63,6 +82,68 @@ fn instruction(p: &mut Parser, name: Option<Marker>) {
     m.finish(p, Instruction);
 }
 
+/// .name values
+///
+/// .equ name, value is a constant
+fn directive(p: &mut Parser) {
+    assert!(p.at(Dot));
+    let m = p.start();
+    // .
+    p.bump(Dot);
+    // name
+    let kind = if is_keyword(p, "EQU") {
+        Constant
+    } else {
+        Directive
+    };
+    if p.at(Ident) {
+        name(p);
+    } else {
+        error(p, "expected the name of a directive");
+    }
+    values(p);
+    m.finish(p, kind);
+}
+
+/// name EQU value
+fn constant(p: &mut Parser, name: Marker) {
+    // EQU
+    self::name(p);
+    values(p);
+    name.finish(p, Constant);
+}
+
+/// value(s)
+fn values(p: &mut Parser) {
+    let m = p.start();
+    while let Some(kind) = p.peek() {
+        if kind == NewLine {
+            break;
+        }
+        value(p);
+    }
+    m.finish(p, Arguments);
+}
+
+fn is_keyword(p: &mut Parser, keyword: &str) -> bool {
+    p.at(Ident)
+        && p.text()
+            .is_some_and(|text| text.eq_ignore_ascii_case(keyword))
+}
+
+/// number | text | name | comma
+fn value(p: &mut Parser) {
+    let m = p.start();
+    match p.peek() {
+        Some(Ident) => name_or_number(p),
+        Some(kind) if is_number_start(kind) => number(p),
+        Some(Str) => text(p),
+        Some(Comma) => punct(p),
+        _ => unexpected(p),
+    }
+    m.finish(p, Item);
+}
+
 /// item(s)
 fn arguments(p: &mut Parser) {
     let m = p.start();
@@ -78,17 +159,31 @@ fn arguments(p: &mut Parser) {
 fn item(p: &mut Parser) {
     let m = p.start();
     match p.peek() {
-        Some(Ident) if is_register(p) => assert!(register(p)),
-        Some(Ident) => name(p),
+        Some(Ident) if is_register(p) => register(p),
+        Some(Ident) if is_status_register(p) => status_register(p),
+        Some(Ident) if is_shift(p) => shift(p),
+        Some(Ident) => name_or_number(p),
         Some(Hash) => number(p),
         Some(Comma) => punct(p),
         Some(OpenSquare) => address(p),
         Some(OpenCurly) => register_list(p),
+        Some(Equals) => pool_literal(p),
         _ => unexpected(p),
     }
     m.finish(p, Item);
 }
 
+/// = expression
+///
+/// A value placed in the literal pool
+fn pool_literal(p: &mut Parser) {
+    assert!(p.at(Equals));
+    let m = p.start();
+    p.bump(Equals);
+    number(p);
+    m.finish(p, PoolLiteral);
+}
+
 /// { (register | comma) (s) }
 fn register_list(p: &mut Parser) {
     enum PartialGroup {
@@ -109,28 +204,21 @@ fn register_list(p: &mut Parser) {
     let mut group = PartialGroup::None;
     while let Some(kind) = p.peek() {
         match kind {
-            CloseCurly => {
-                // make sure to abandon any markers weren't finished
-                match group {
-                    PartialGroup::None => (),
-                    PartialGroup::Reg(m) | PartialGroup::RegAnd(m) => m.abandon(),
-                }
-                break;
-            }
+            CloseCurly | NewLine => break,
             Ident if is_register(p) => match mem::replace(&mut group, PartialGroup::None) {
                 PartialGroup::None => {
                     group = PartialGroup::Reg(p.start());
-                    assert!(register(p));
+                    register(p);
                 }
                 PartialGroup::Reg(m) => {
                     // so far it is [Reg Reg], this isn't a group
                     // make sure to abandon the marker
                     m.abandon();
                     group = PartialGroup::Reg(p.start());
-                    assert!(register(p));
+                    register(p);
                 }
                 PartialGroup::RegAnd(m) => {
-                    assert!(register(p));
+                    register(p);
                     m.finish(p, RegisterRange);
                 }
             },
@@ -165,6 +253,12 @@ fn register_list(p: &mut Parser) {
             }
         }
     }
+    // make sure to abandon any markers weren't finished,
+    // the list can end at a new line or the end of the input
+    match group {
+        PartialGroup::None => (),
+        PartialGroup::Reg(m) | PartialGroup::RegAnd(m) => m.abandon(),
+    }
     // }
     expect(p, CloseCurly);
     m.finish(p, RegisterList);
@@ -178,8 +272,10 @@ fn address(p: &mut Parser) {
     // [
     p.bump(OpenSquare);
     // register
-    if !register(p) {
-        unexpected(p);
+    if is_register(p) {
+        register(p);
+    } else {
+        recover(p, "expected a register", &[Comma, CloseSquare]);
     }
     // (, offset)?
     let has_offset = if p.at(Comma) {
@@ -203,50 +299,59 @@ fn address(p: &mut Parser) {
     m.finish(p, address_kind);
 }
 
-/// number | register (, shift)
+/// number | (+ | -)? register (, shift)
 fn offset(p: &mut Parser) {
     let m = p.start();
 
     match p.peek() {
         Some(Hash) => number(p),
-        Some(Ident) if is_register(p) => {
-            assert!(register(p));
-            // we dont have to worry about accidentally consuming more arguments here
-            // addresses are always the last argument to an instruction
-            if p.at(Comma) {
-                punct(p);
-                shift(p);
+        Some(Minus | Plus) => {
+            punct(p);
+            if is_register(p) {
+                offset_register(p);
+            } else {
+                recover(p, "expected a register", &[CloseSquare]);
             }
         }
-        _ => unexpected(p),
+        Some(Ident) if is_register(p) => offset_register(p),
+        _ => recover(p, "expected a number or a register", &[CloseSquare]),
     }
 
     m.finish(p, Offset);
 }
 
+/// register (, shift)
+fn offset_register(p: &mut Parser) {
+    register(p);
+    // we dont have to worry about accidentally consuming more arguments here
+    // addresses are always the last argument to an instruction
+    if p.at(Comma) {
+        punct(p);
+        shift(p);
+    }
+}
+
 /// (LSL | LSR | ASR | ROR) (number | register)
 /// RRX
 fn shift(p: &mut Parser) {
     let m = p.start();
 
-    if !p.at(Ident) {
-        unexpected(p);
-    } else {
-        let text = p.text().unwrap();
-        match text {
-            "LSL" | "LSR" | "ASR" | "ROR" => {
-                name(p);
-                match p.peek() {
-                    Some(Hash) => number(p),
-                    Some(Ident) if is_register(p) => assert!(register(p)),
-                    _ => unexpected(p),
-                }
-            }
-            "RRX" => {
-                name(p);
+    let text = if p.at(Ident) { p.text() } else { None };
+    match text {
+        Some("LSL" | "LSR" | "ASR" | "ROR") => {
+            name(p);
+            match p.peek() {
+                Some(Hash) => number(p),
+                Some(Ident) if is_register(p) => register(p),
+                _ => recover(p, "expected a shift amount", &[Comma, CloseSquare]),
             }
-            _ => unexpected(p),
         }
+        Some("RRX") => name(p),
+        _ => recover(
+            p,
+            "expected a shift (LSL, LSR, ASR, ROR or RRX)",
+            &[CloseSquare],
+        ),
     }
 
     m.finish(p, Shift);
@@ -266,21 +371,44 @@ fn label(p: &mut Parser) -> Result<(), Marker> {
 }
 
 /// RN | SP | LR | PC !?
-fn register(p: &mut Parser) -> bool {
-    assert!(p.at(Ident));
+fn register(p: &mut Parser) {
+    assert!(is_register(p));
     let m = p.start();
-    if is_register(p) {
-        p.bump(Ident);
-        // !
-        p.eat(Bang);
-        m.finish(p, Register);
-        true
-    } else {
-        false
+    p.bump(Ident);
+    // !
+    p.eat(Bang);
+    m.finish(p, Register);
+}
+
+/// (CPSR | SPSR)(_fields)?
+fn status_register(p: &mut Parser) {
+    assert!(is_status_register(p));
+    let m = p.start();
+    p.bump(Ident);
+    m.finish(p, StatusRegister);
+}
+
+fn is_status_register(p: &mut Parser) -> bool {
+    if !p.at(Ident) {
+        return false;
     }
+    let Some(txt) = p.text() else {
+        return false;
+    };
+
+    let txt = txt.to_uppercase();
+    let name = txt.split_once('_').map_or(txt.as_str(), |(name, _)| name);
+    matches!(name, "CPSR" | "SPSR")
+}
+
+fn is_shift(p: &mut Parser) -> bool {
+    p.at(Ident) && matches!(p.text(), Some("LSL" | "LSR" | "ASR" | "ROR" | "RRX"))
 }
 
 fn is_register(p: &mut Parser) -> bool {
+    if !p.at(Ident) {
+        return false;
+    }
     let Some(txt) = p.text() else {
         return false;
     };
@@ -295,17 +423,109 @@ fn is_register(p: &mut Parser) -> bool {
     }
 }
 
-/// #(Decimal | Hex | Octal | Binary)
+/// #?expression
+///
+/// Only values in directives can leave out the #
 fn number(p: &mut Parser) {
-    assert!(p.at(Hash));
     let m = p.start();
     // #
-    p.bump(Hash);
+    p.eat(Hash);
+    expression(p, 0);
+    m.finish(p, Number);
+}
+
+/// name | name operator expression
+///
+/// A name on its own is a label, with an operator it starts a number
+fn name_or_number(p: &mut Parser) {
+    let m = p.start();
+    let lhs = p.start();
+    name(p);
+    if p.peek().and_then(binding_power).is_some() {
+        binary(p, lhs, 0);
+        m.finish(p, Number);
+    } else {
+        lhs.abandon();
+        m.abandon();
+    }
+}
+
+fn is_number_start(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        Hash | Decimal | Hex | Octal | Binary | Char | OpenParen | Minus | Plus | Tilde
+    )
+}
+
+/// How tightly an operator holds its operands, from loosest to tightest:
+/// | ^ & (<< >>) (+ -) (* / %)
+fn binding_power(kind: SyntaxKind) -> Option<u8> {
+    let power = match kind {
+        Pipe => 1,
+        Caret => 2,
+        Ampersand => 3,
+        ShiftLeft | ShiftRight => 4,
+        Plus | Minus => 5,
+        Star | Slash | Percent => 6,
+        _ => return None,
+    };
+
+    Some(power)
+}
+
+/// operand (operator operand)*
+fn expression(p: &mut Parser, min_power: u8) {
+    let lhs = p.start();
+    operand(p);
+    binary(p, lhs, min_power);
+}
+
+/// (operator operand)*, after the `lhs` operand
+///
+/// Operators of the same power are left associative
+fn binary(p: &mut Parser, mut lhs: Marker, min_power: u8) {
+    while let Some(power) = p.peek().and_then(binding_power) {
+        if power < min_power {
+            break;
+        }
+        // operator
+        p.bump_any();
+        expression(p, power + 1);
+
+        let next = lhs.precede();
+        lhs.finish(p, BinaryExpr);
+        lhs = next;
+    }
+    lhs.abandon();
+}
+
+/// (- | + | ~) operand | ( expression ) | Decimal | Hex | Octal | Binary | Char | name
+fn operand(p: &mut Parser) {
     match p.peek() {
-        Some(num @ (Decimal | Hex | Octal | Binary)) => p.bump(num),
-        _ => unexpected(p),
+        Some(Minus | Plus | Tilde) => {
+            let m = p.start();
+            p.bump_any();
+            operand(p);
+            m.finish(p, UnaryExpr);
+        }
+        Some(OpenParen) => {
+            let m = p.start();
+            p.bump(OpenParen);
+            expression(p, 0);
+            expect(p, CloseParen);
+            m.finish(p, ParenExpr);
+        }
+        Some(num @ (Decimal | Hex | Octal | Binary | Char)) => p.bump(num),
+        Some(Ident) if !is_register(p) => name(p),
+        _ => error(p, "expected a number"),
     }
-    m.finish(p, Number);
+}
+
+/// Str
+fn text(p: &mut Parser) {
+    let m = p.start();
+    p.bump(Str);
+    m.finish(p, Text);
 }
 
 /// Ident
@@ -327,6 +547,13 @@ fn punct(p: &mut Parser) {
 /// Expect a `kind`, emit an Error otherwise
 fn expect(p: &mut Parser, kind: SyntaxKind) -> bool {
     if !p.eat(kind) {
+        let expected = match kind {
+            CloseCurly => "`}`",
+            CloseSquare => "`]`",
+            CloseParen => "`)`",
+            _ => unreachable!("no description for {kind:?}"),
+        };
+        p.error_before(format!("expected {expected}"));
         p.emit(Error);
         false
     } else {
@@ -336,10 +563,42 @@ fn expect(p: &mut Parser, kind: SyntaxKind) -> bool {
 
 /// Any
 fn unexpected(p: &mut Parser) {
-    if !p.at_end() {
+    let message = match p.peek() {
+        Some(NewLine) | None => "unexpected end of line".to_owned(),
+        Some(_) => format!("unexpected `{}`", p.text().unwrap_or_default()),
+    };
+    error(p, message);
+}
+
+/// Records `message` and wraps the next token in an Error,
+/// the end of the line is left for the statement
+fn error(p: &mut Parser, message: impl Into<String>) {
+    if p.at_end() || p.at(NewLine) {
+        p.error_before(message);
+    } else {
+        p.error(message);
         let m = p.start();
         // Any
         p.bump_any();
         m.finish(p, Error);
     }
 }
+
+/// Records `message` and wraps tokens in an Error until one of `until`,
+/// the end of the line is left for the statement
+fn recover(p: &mut Parser, message: impl Into<String>, until: &[SyntaxKind]) {
+    let stop = |kind| kind == NewLine || until.contains(&kind);
+    match p.peek() {
+        Some(kind) if !stop(kind) => {
+            p.error(message);
+            let m = p.start();
+            // Any
+            while p.peek().is_some_and(|kind| !stop(kind)) {
+                p.bump_any();
+            }
+            m.finish(p, Error);
+        }
+        Some(NewLine) | None => p.error_before(message),
+        Some(_) => p.error(message),
+    }
+}
@@ -53,7 +53,7 @@ impl Lexable for HAND {
 
                     Comment
                 }
-                _ => Unknown,
+                _ => Slash,
             },
 
             c if is_number_start(c) => match (c, lexer.peek()) {
@@ -78,6 +78,15 @@ impl Lexable for HAND {
                 }
             },
 
+            '"' => {
+                quoted(lexer, '"');
+                Str
+            }
+            '\'' => {
+                quoted(lexer, '\'');
+                Char
+            }
+
             '{' => OpenCurly,
             '}' => CloseCurly,
             '[' => OpenSquare,
@@ -89,6 +98,23 @@ impl Lexable for HAND {
             '!' => Bang,
             ':' => Colon,
             '=' => Equals,
+            '.' => Dot,
+            '(' => OpenParen,
+            ')' => CloseParen,
+            '*' => Star,
+            '%' => Percent,
+            '&' => Ampersand,
+            '|' => Pipe,
+            '^' => Caret,
+            '~' => Tilde,
+            '<' if lexer.peek() == Some('<') => {
+                lexer.eat();
+                ShiftLeft
+            }
+            '>' if lexer.peek() == Some('>') => {
+                lexer.eat();
+                ShiftRight
+            }
 
             _ => Unknown,
         }
@@ -105,6 +131,24 @@ impl lexer::Token for SyntaxKind {
     }
 }
 
+/// Eats up to and including the closing `quote`, skipping over escapes.
+/// An unterminated literal stops at the end of the line.
+fn quoted(lexer: &mut lexer::Lexer, quote: char) {
+    while let Some(c) = lexer.peek() {
+        if c == '\n' {
+            break;
+        }
+        lexer.eat();
+        match c {
+            '\\' if lexer.peek() != Some('\n') => {
+                lexer.eat();
+            }
+            c if c == quote => break,
+            _ => (),
+        }
+    }
+}
+
 fn is_number_start(c: char) -> bool {
     c.is_ascii_digit()
 }
@@ -24,7 +24,7 @@ stop:
 #[test]
 fn chars() {
     assert_eq!(
-        &tokens("{}[],#+-=!:"),
+        &tokens("{}[],#+-=!:."),
         &[
             OpenCurly,
             CloseCurly,
@@ -36,7 +36,8 @@ fn chars() {
             Minus,
             Equals,
             Bang,
-            Colon
+            Colon,
+            Dot
         ]
     );
 }
@@ -63,3 +64,34 @@ fn comments() {
         &[Comment, Whitespace, Comment]
     );
 }
+
+#[test]
+fn literals() {
+    assert_eq!(&tokens(r#""hello""#), &[Str]);
+    assert_eq!(&tokens(r#""a \"quote\"" 'b'"#), &[Str, Whitespace, Char]);
+    assert_eq!(&tokens(r"'\''"), &[Char]);
+    assert_eq!(&tokens(r"'\\'"), &[Char]);
+    // unterminated literals stop at the end of the line
+    assert_eq!(&tokens("\"open\nB"), &[Str, NewLine, Ident]);
+    assert_eq!(&tokens("'\\\n"), &[Char, NewLine]);
+}
+
+#[test]
+fn operators() {
+    assert_eq!(
+        &tokens(r"(1+2)-3*4/5%6"),
+        &[
+            OpenParen, Decimal, Plus, Decimal, CloseParen, Minus, Decimal, Star, Decimal, Slash,
+            Decimal, Percent, Decimal
+        ]
+    );
+    assert_eq!(
+        &tokens(r"1<<2>>3&4|5^~6"),
+        &[
+            Decimal, ShiftLeft, Decimal, ShiftRight, Decimal, Ampersand, Decimal, Pipe, Decimal,
+            Caret, Tilde, Decimal
+        ]
+    );
+    // a single angle bracket isn't an operator
+    assert_eq!(&tokens(r"<"), &[Unknown]);
+}
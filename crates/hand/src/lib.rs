@@ -1,14 +1,19 @@
 mod ast;
+mod diagnostic;
 mod grammar;
 mod lexer;
 mod lowering;
 mod syntax;
+#[cfg(test)]
+mod tests;
 
 use std::sync::Arc;
 
 use ast::AstNode as _;
+pub use diagnostic::{Diagnostic, Severity};
 pub use lowering::{AddressKind, Fragment};
 use parser::rowan;
+pub use parser::rowan::{TextRange, TextSize};
 use syntax::SyntaxKind;
 
 #[test]
@@ -29,15 +34,17 @@ fn it_works() {
                 LDR r2, [r3], r4\n\
                 HLT";
     let text = Arc::<str>::from(text);
-    let frags = parse(text);
-    dbg!(frags);
+    let result = parse(text);
+    assert!(!result.has_errors(), "{:?}", result.diagnostics());
+    dbg!(result);
 }
 
 #[derive(Debug)]
 pub struct ParseResult {
     text: Arc<str>,
     fragments: Vec<Fragment>,
-    // errors: Vec<Error>?
+    instructions: Vec<TextRange>,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl ParseResult {
@@ -48,6 +55,21 @@ impl ParseResult {
     pub fn fragments(&self) -> &[Fragment] {
         &self.fragments
     }
+
+    /// The source range of the `index`th instruction, from its name to its last argument.
+    pub fn instruction(&self, index: usize) -> Option<TextRange> {
+        self.instructions.get(index).copied()
+    }
+
+    /// Problems found in the source, in the order they were found.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Whether any diagnostic is an error, the fragments can't be trusted if so.
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
 }
 
 /// loop:
@@ -58,24 +80,21 @@ impl ParseResult {
 pub enum HAND {}
 
 pub fn parse(text: Arc<str>) -> ParseResult {
-    let tree = crate::grammar::parse(text.clone());
+    let (tree, errors) = crate::grammar::parse(text.clone());
     let root = crate::ast::Root::cast(tree).expect("grammar starts at root");
 
-    // TODO: error handling
-    // let mut errors = Vec::new();
-    // crate::ast::validate(root.clone(), &mut errors);
+    let mut diagnostics = errors.into_iter().map(Diagnostic::from).collect();
 
-    let fragments = lowering::lower(root /*, &mut errors*/);
+    crate::ast::validate(root.clone(), &mut diagnostics);
 
-    // TODO: error handling
-    // if !errors.is_empty() {
-    //     return Err(Errors {
-    //         src: text.clone(),
-    //         inner: errors,
-    //     });
-    // }
+    let (fragments, instructions) = lowering::lower(root, &mut diagnostics);
 
-    ParseResult { text, fragments }
+    ParseResult {
+        text,
+        fragments,
+        instructions,
+        diagnostics,
+    }
 }
 
 impl rowan::Language for HAND {
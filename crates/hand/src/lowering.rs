@@ -1,25 +1,45 @@
 mod cir;
+mod fold;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, mem};
 
-use parser::rowan::TextRange;
+use parser::rowan::{TextRange, TextSize};
 
-use crate::ast::{self, AstToken};
+use crate::{
+    ast::{self, AstNode, AstToken, DirectiveKind},
+    grammar::SyntaxNode,
+    syntax::SyntaxKind,
+    Diagnostic,
+};
+use fold::{fold, Symbols};
 
 /// TODO: Use Handles to reduce size?
-/// A statement begins with [Label?, Instruction?, Condition ..args]
+/// A statement begins with [Label?, Instruction?, Size?, Condition, SetFlags? ..args]
+/// or is data placed as is by a directive [Byte | Half | Word]
 #[derive(Debug, Clone, Copy)]
 pub enum Fragment {
     Label(i32),
     Instruction(TextRange),
+    /// The size suffix of an instruction, split from it by a condition in `LDREQB`
+    Size(&'static str),
     Condition(Condition),
+    SetFlags,
     Register(u32),
     RegisterList(u16),
+    /// `CPSR` or `SPSR`, and a bit for each of its fields that are named
+    StatusRegister {
+        spsr: bool,
+        fields: Option<u8>,
+    },
     Name(TextRange),
     Number(u32),
     Address(AddressKind),
+    Sign(Sign),
     Shift(ShiftKind),
     Bang,
+    Byte(u8),
+    Half(u16),
+    Word(u32),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -48,6 +68,13 @@ pub enum AddressKind {
     PostIndex,
 }
 
+/// Whether an offset is added to or subtracted from the base
+#[derive(Debug, Clone, Copy)]
+pub enum Sign {
+    Plus,
+    Minus,
+}
+
 #[derive(Debug, Clone, Copy)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum ShiftKind {
@@ -58,158 +85,665 @@ pub enum ShiftKind {
     RRX,
 }
 
-/// TODO: error handling
-pub fn lower(root: ast::Root) -> Vec<Fragment> {
+/// Lowers the tree into fragments, reporting anything that can't be lowered.
+///
+/// Syntax errors have already been reported by the parser, so they are skipped.
+/// Alongside the fragments is the source range of each instruction, in order.
+pub fn lower(
+    root: ast::Root,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> (Vec<Fragment>, Vec<TextRange>) {
     let mut frags = Vec::new();
+    let mut ranges = Vec::new();
 
     // TODO: this base of the address should be changable
     let mut address = 0x0_u32;
     let mut label_addresses = HashMap::new();
+    let mut constants = HashMap::new();
+    // where each literal is placed in a pool, in order
+    let mut slots = Vec::new();
+    let mut pending = 0;
     for stmt in root.statements() {
-        if let Some(label) = stmt.label() {
-            let id = label.name().ident().unwrap();
-            let text = id.syntax().text().to_string();
-            let old = label_addresses.insert(text, address);
-            // TODO: validate the ast against this
-            assert!(old.is_none(), "Label defined twice");
+        if let Some(id) = stmt.label().and_then(|label| label.name().ident()) {
+            // labels defined twice have been reported by validation
+            label_addresses
+                .entry(id.text().to_owned())
+                .or_insert(address);
         }
-        // instructions are 4 bytes
-        address += 4;
+        if let Some(constant) = stmt.constant() {
+            // constants are only used below where they are defined
+            report_numbers(
+                constant.syntax(),
+                Symbols::constants(&constants),
+                diagnostics,
+            );
+            if let (Some(id), Some(value)) = (
+                constant.name().and_then(|name| name.ident()),
+                constant_value(&constant, Symbols::constants(&constants)),
+            ) {
+                constants.entry(id.text().to_owned()).or_insert(value);
+            }
+        }
+        if is_ltorg(&stmt) {
+            address = pool_slots(address, mem::take(&mut pending), &mut slots);
+        }
+        pending += pool_literals(&stmt).count();
+        address += size(&stmt, address, Symbols::constants(&constants));
     }
+    // the last pool goes after everything else
+    pool_slots(address, pending, &mut slots);
 
+    let symbols = Symbols::new(&constants, &label_addresses);
+    let mut slots = slots.into_iter();
+    let mut pool = Vec::new();
     let mut address = 0x0_u32;
     for stmt in root.statements() {
-        if let Some(body) = stmt.instruction() {
-            let name = body.name();
-            let id = name.ident().unwrap();
-            let token = id.syntax();
-            let text = token.text();
-
-            if let Some((instr, condition)) = strip_condition(text) {
-                frags.push(Fragment::Instruction(TextRange::at(
-                    token.text_range().start(),
-                    (instr.len() as u32).into(),
-                )));
-                frags.push(Fragment::Condition(condition));
-            } else {
-                frags.push(Fragment::Instruction(token.text_range()));
-                frags.push(Fragment::Condition(Condition::AL));
+        let current = address;
+        address += size(&stmt, current, symbols.without_labels());
+
+        if is_ltorg(&stmt) {
+            address = lower_pool(&mut frags, current, mem::take(&mut pool));
+            continue;
+        }
+        if let Some(directive) = stmt.directive() {
+            report_directive(&directive, symbols, diagnostics);
+            lower_directive(&mut frags, directive, current, symbols);
+            continue;
+        }
+
+        // lines that aren't statements have been reported by the parser
+        let Some(body) = stmt.instruction() else {
+            continue;
+        };
+        let Some(id) = body.name().ident() else {
+            // a label on its own refers to the next instruction
+            if let Some(item) = body.args().iter().next() {
+                diagnostics.push(
+                    Diagnostic::error(item.syntax().text_range(), "expected an instruction")
+                        .with_hint(
+                            "a label is followed by an instruction, like `loop: ADD r0, r0, #1`",
+                        ),
+                );
             }
+            continue;
+        };
+        let token = id.syntax();
+        let text = token.text();
+
+        // nodes hold the trivia that follows them, leave it out
+        let end = body
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| !matches!(token.kind(), SyntaxKind::Whitespace | SyntaxKind::Comment))
+            .last()
+            .map_or(token.text_range().end(), |last| last.text_range().end());
+        ranges.push(TextRange::new(token.text_range().start(), end));
+
+        if !current.is_multiple_of(4) {
+            diagnostics.push(
+                Diagnostic::error(token.text_range(), "instruction is not aligned to a word")
+                    .with_hint(
+                        "instructions start at a multiple of 4 bytes, try `.align` before it",
+                    ),
+            );
+        }
 
-            for item in body.args().iter() {
-                let kind = item.kind();
-                match kind {
-                    ast::ItemKind::Register(reg) => lower_register(&mut frags, reg),
-                    ast::ItemKind::Name(name) => {
-                        if let Some(ident) = name.ident() {
-                            let text = ident.syntax().text();
-                            if let Some(&label) = label_addresses.get(text) {
-                                lower_label(&mut frags, label, address);
-                                continue;
-                            }
+        let start = token.text_range().start();
+        let at = |offset: usize, len: usize| {
+            TextRange::at(start + TextSize::from(offset as u32), (len as u32).into())
+        };
+        if let Some(mnemonic) = decompose(text) {
+            frags.push(Fragment::Instruction(at(0, mnemonic.base)));
+            if let Some(size) = mnemonic.size {
+                frags.push(Fragment::Size(size));
+            }
+            frags.push(Fragment::Condition(mnemonic.condition));
+            if mnemonic.set_flags {
+                frags.push(Fragment::SetFlags);
+            }
+        } else {
+            // unknown instructions are reported when they are matched
+            frags.push(Fragment::Instruction(token.text_range()));
+            frags.push(Fragment::Condition(Condition::AL));
+        }
+
+        report_numbers(body.syntax(), symbols, diagnostics);
+        for item in body.args().iter() {
+            let kind = item.kind();
+            match kind {
+                ast::ItemKind::Register(reg) => lower_register(&mut frags, reg),
+                ast::ItemKind::StatusReg(reg) => frags.push(Fragment::StatusRegister {
+                    spsr: reg.is_spsr(),
+                    // fields that aren't known have been reported by validation
+                    fields: reg.fields().map(|_| reg.mask().unwrap_or(0)),
+                }),
+                ast::ItemKind::Name(name) => {
+                    if let Some(ident) = name.ident() {
+                        let text = ident.syntax().text();
+                        if let Some(&label) = label_addresses.get(text) {
+                            lower_label(&mut frags, label, current);
+                            continue;
                         }
+                    }
 
-                        lower_name(&mut frags, name)
+                    lower_name(&mut frags, name)
+                }
+                // an address, like `label + 8`
+                ast::ItemKind::Number(number) if number.hash().is_none() => {
+                    if let Ok(label) = fold(&number, symbols) {
+                        lower_label(&mut frags, label, current);
                     }
-                    ast::ItemKind::Number(number) => lower_number(&mut frags, number),
-                    ast::ItemKind::Address(address) => lower_address(&mut frags, address),
-                    ast::ItemKind::RegList(list) => lower_reg_list(&mut frags, list),
-                    // Ignore punctuation
-                    ast::ItemKind::Punct(_) => (),
-                    // TODO: process errors properly
-                    ast::ItemKind::Error(_error) => panic!("Error in ast"),
                 }
+                ast::ItemKind::Number(number) => lower_number(&mut frags, number, symbols),
+                ast::ItemKind::Address(address) => lower_address(&mut frags, address, symbols),
+                ast::ItemKind::Shift(shift) => lower_shift(&mut frags, Some(shift), symbols),
+                ast::ItemKind::RegList(list) => lower_reg_list(&mut frags, list),
+                ast::ItemKind::PoolLiteral(literal) => {
+                    // both passes see the same literals
+                    let slot = slots.next().unwrap_or(current);
+                    let value = literal
+                        .number()
+                        .and_then(|number| fold(&number, symbols).ok());
+                    pool.push(value.unwrap_or(0));
+
+                    let offset = i64::from(slot) - (i64::from(current) + 8);
+                    if offset.abs() > 4095 {
+                        diagnostics.push(
+                            Diagnostic::error(
+                                literal.syntax().text_range(),
+                                format!("literal pool is {} bytes away", offset.abs()),
+                            )
+                            .with_hint(
+                                "LDR reaches 4095 bytes either way, try `.ltorg` closer to it",
+                            ),
+                        );
+                    }
+                    lower_label(&mut frags, slot, current);
+                }
+                // Ignore punctuation
+                ast::ItemKind::Punct(_) => (),
+                // Already reported by the parser, text is only allowed in directives
+                ast::ItemKind::Error(_) | ast::ItemKind::Text(_) => (),
             }
-        } else {
-            panic!("Statement has no body");
         }
-        address += 4;
     }
 
-    frags
+    lower_pool(&mut frags, address, pool);
+
+    (frags, ranges)
+}
+
+fn is_ltorg(stmt: &ast::Stmt) -> bool {
+    stmt.directive()
+        .is_some_and(|directive| directive.kind() == Some(DirectiveKind::Ltorg))
+}
+
+/// The values of `LDR rX, =value` in a statement
+fn pool_literals(stmt: &ast::Stmt) -> impl Iterator<Item = ast::PoolLiteral> {
+    stmt.instruction()
+        .filter(|body| body.name().ident().is_some())
+        .into_iter()
+        .flat_map(|body| body.args().iter())
+        .filter_map(|item| match item.kind() {
+            ast::ItemKind::PoolLiteral(literal) => Some(literal),
+            _ => None,
+        })
+}
+
+/// Places `count` literals in a pool at `address`, returning the address after it.
+///
+/// Pools are word aligned, an empty pool takes up no space.
+fn pool_slots(address: u32, count: usize, slots: &mut Vec<u32>) -> u32 {
+    if count == 0 {
+        return address;
+    }
+    let start = address.next_multiple_of(4);
+    let end = start + 4 * count as u32;
+    slots.extend((start..end).step_by(4));
+    end
+}
+
+/// Padding then Word(s), returning the address after the pool
+fn lower_pool(frags: &mut Vec<Fragment>, address: u32, pool: Vec<u32>) -> u32 {
+    if pool.is_empty() {
+        return address;
+    }
+    let start = address.next_multiple_of(4);
+    frags.extend((address..start).map(|_| Fragment::Byte(0)));
+    frags.extend(pool.iter().copied().map(Fragment::Word));
+    start + 4 * pool.len() as u32
+}
+
+/// The most bytes `.space` can reserve at once
+pub const MAX_SPACE: u32 = 0x10_0000;
+
+/// The number of bytes a statement takes up when placed at `address`.
+///
+/// Instructions are 4 bytes, a label or constant on its own takes up no space.
+fn size(stmt: &ast::Stmt, address: u32, symbols: Symbols) -> u32 {
+    if let Some(directive) = stmt.directive() {
+        let values = directive.values().count() as u32;
+        match directive.kind() {
+            Some(DirectiveKind::Word) => 4 * values,
+            Some(DirectiveKind::Half) => 2 * values,
+            Some(DirectiveKind::Byte) => values,
+            Some(DirectiveKind::Space) => space(&directive, symbols).0,
+            Some(DirectiveKind::Align) => padding(address, &directive, symbols),
+            Some(DirectiveKind::Ascii) => text(&directive, false).count() as u32,
+            Some(DirectiveKind::Asciz) => text(&directive, true).count() as u32,
+            // depends on the literals before it
+            Some(DirectiveKind::Ltorg) | None => 0,
+        }
+    } else if stmt
+        .instruction()
+        .is_some_and(|body| body.name().ident().is_some())
+    {
+        4
+    } else {
+        0
+    }
+}
+
+/// The value a constant is defined as, a number or another constant.
+fn constant_value(constant: &ast::Constant, symbols: Symbols) -> Option<u32> {
+    match constant.value()? {
+        ast::ItemKind::Number(number) => fold(&number, symbols).ok(),
+        ast::ItemKind::Name(name) => symbols.get(name.ident()?.text()),
+        _ => None,
+    }
 }
 
-fn strip_condition(instruction: &str) -> Option<(&str, Condition)> {
-    // taken from `std::str::pattern` which is not yet stable
-    // these functions are perfectly fine however
+/// The `index`th value of a directive, if it is a number.
+fn directive_value(directive: &ast::Directive, index: usize, symbols: Symbols) -> Option<u32> {
+    match directive.values().nth(index)? {
+        ast::ItemKind::Number(number) => fold(&number, symbols).ok(),
+        _ => None,
+    }
+}
 
-    fn is_suffix_of(a: &str, b: &str) -> bool {
-        a.as_bytes().ends_with(b.as_bytes())
+/// Reports the numbers in `node` that can't be folded.
+fn report_numbers(node: &SyntaxNode, symbols: Symbols, diagnostics: &mut Vec<Diagnostic>) {
+    for number in node.descendants().filter_map(ast::Number::cast) {
+        if let Err(error) = fold(&number, symbols) {
+            diagnostics.extend(error.diagnostic());
+        }
     }
+}
 
-    #[inline]
-    fn strip_suffix_of<'a>(a: &str, b: &'a str) -> Option<&'a str> {
-        if is_suffix_of(a, b) {
-            let i = b.len() - a.as_bytes().len();
-            // SAFETY: suffix was just verified to exist.
-            unsafe { Some(b.get_unchecked(..i)) }
-        } else {
-            None
+/// Reports the values of a directive that can't be folded, or don't fit once they are.
+fn report_directive(
+    directive: &ast::Directive,
+    symbols: Symbols,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(kind) = directive.kind() else {
+        return;
+    };
+    let symbols = match kind {
+        DirectiveKind::Space | DirectiveKind::Align => symbols.without_labels(),
+        _ => symbols,
+    };
+    report_numbers(directive.syntax(), symbols, diagnostics);
+
+    for (index, value) in directive.values().enumerate() {
+        let ast::ItemKind::Number(number) = value else {
+            continue;
+        };
+        // literals have been checked by validation
+        if number.literal().is_some() {
+            continue;
+        }
+        let (Ok(value), Some(max)) = (fold(&number, symbols), kind.limit(index)) else {
+            continue;
+        };
+        if value > max {
+            let name = directive.name().and_then(|name| name.ident());
+            diagnostics.push(Diagnostic::error(
+                number.syntax().text_range(),
+                format!(
+                    "`.{}` can't be given {value}, the largest is {max}",
+                    name.as_ref().map_or("", |id| id.text())
+                ),
+            ));
         }
     }
+}
 
-    strip_suffix_of("EQ", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("NE", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("CS", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("CC", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("MI", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("PL", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("VS", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("VC", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("HI", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("LS", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("GE", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("LT", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("GT", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("LE", instruction).map(|instr| (instr, Condition::EQ))?;
-    strip_suffix_of("AL", instruction).map(|instr| (instr, Condition::EQ))?;
-    // no suffix found
-    None
+/// .space size(, fill)?
+fn space(directive: &ast::Directive, symbols: Symbols) -> (u32, u8) {
+    // anything too large has been reported by validation
+    let size = directive_value(directive, 0, symbols).filter(|&size| size <= MAX_SPACE);
+    let fill = directive_value(directive, 1, symbols).and_then(|fill| u8::try_from(fill).ok());
+    (size.unwrap_or(0), fill.unwrap_or(0))
+}
+
+/// .align power?, pads to the next multiple of 2^power, a word by default
+fn padding(address: u32, directive: &ast::Directive, symbols: Symbols) -> u32 {
+    let power = directive_value(directive, 0, symbols).unwrap_or(2);
+    let alignment = 1_u32.checked_shl(power).unwrap_or(1);
+    (alignment - address % alignment) % alignment
+}
+
+/// The bytes of every string in a directive, each followed by a 0 if `terminate`
+fn text(directive: &ast::Directive, terminate: bool) -> impl Iterator<Item = u8> {
+    directive
+        .values()
+        .filter_map(move |value| match value {
+            // bad strings have been reported by validation
+            ast::ItemKind::Text(text) => {
+                let mut bytes = text.value().unwrap_or_default();
+                if terminate {
+                    bytes.push(0);
+                }
+                Some(bytes)
+            }
+            _ => None,
+        })
+        .flatten()
+}
+
+/// (Byte | Half | Word)(s)
+fn lower_directive(
+    frags: &mut Vec<Fragment>,
+    directive: ast::Directive,
+    address: u32,
+    symbols: Symbols,
+) {
+    let Some(kind) = directive.kind() else {
+        // unknown directives have been reported by validation
+        return;
+    };
+
+    // values that don't fit have been reported
+    let values = directive.values().map(|value| match value {
+        ast::ItemKind::Number(number) => fold(&number, symbols).unwrap_or(0),
+        ast::ItemKind::Name(name) => name
+            .ident()
+            .and_then(|id| symbols.get(id.text()))
+            .unwrap_or(0),
+        _ => 0,
+    });
+
+    match kind {
+        DirectiveKind::Word => frags.extend(values.map(Fragment::Word)),
+        DirectiveKind::Half => frags.extend(values.map(|value| Fragment::Half(value as u16))),
+        DirectiveKind::Byte => frags.extend(values.map(|value| Fragment::Byte(value as u8))),
+        DirectiveKind::Space => {
+            let (size, fill) = space(&directive, symbols.without_labels());
+            frags.extend((0..size).map(|_| Fragment::Byte(fill)));
+        }
+        DirectiveKind::Align => {
+            let padding = padding(address, &directive, symbols.without_labels());
+            frags.extend((0..padding).map(|_| Fragment::Byte(0)));
+        }
+        DirectiveKind::Ascii => frags.extend(text(&directive, false).map(Fragment::Byte)),
+        DirectiveKind::Asciz => frags.extend(text(&directive, true).map(Fragment::Byte)),
+        // pools are placed while lowering the statements
+        DirectiveKind::Ltorg => (),
+    }
+}
+
+/// The suffixes an instruction accepts after its base mnemonic.
+struct Mnemonic {
+    base: &'static str,
+    /// Whether an `S` suffix updates the flags.
+    set_flags: bool,
+    /// Suffixes that pick another form of the instruction, like the `B` of `LDRB`.
+    sizes: &'static [&'static str],
+    /// Suffixes that are another name for a size, like the `FD` of `LDMFD` which is `LDM`.
+    aliases: &'static [(&'static str, &'static str)],
+}
+
+impl Mnemonic {
+    const fn new(base: &'static str) -> Self {
+        Self {
+            base,
+            set_flags: false,
+            sizes: &[],
+            aliases: &[],
+        }
+    }
+
+    const fn set_flags(base: &'static str) -> Self {
+        Self {
+            set_flags: true,
+            ..Self::new(base)
+        }
+    }
+
+    const fn sized(base: &'static str, sizes: &'static [&'static str]) -> Self {
+        Self {
+            sizes,
+            ..Self::new(base)
+        }
+    }
+
+    const fn aliased(self, aliases: &'static [(&'static str, &'static str)]) -> Self {
+        Self { aliases, ..self }
+    }
+
+    /// Splits the suffixes that follow the base mnemonic.
+    ///
+    /// An alias is replaced by the size it names.
+    ///
+    /// The condition comes last in UAL, `ADDSEQ` and `LDRBEQ`,
+    /// but older code puts it first, `ADDEQS` and `LDREQB`.
+    fn split(&self, suffixes: &str) -> Option<Decomposed> {
+        let base = self.base.len();
+        let modifiers = std::iter::once(("", ""))
+            .chain(self.set_flags.then_some(("S", "S")))
+            .chain(self.sizes.iter().map(|size| (*size, *size)))
+            .chain(self.aliases.iter().copied());
+
+        for (modifier, size) in modifiers {
+            let Some(cond) = suffixes
+                .strip_prefix(modifier)
+                .and_then(condition)
+                .or_else(|| suffixes.strip_suffix(modifier).and_then(condition))
+            else {
+                continue;
+            };
+
+            let is_size = !size.is_empty() && modifier != "S";
+            return Some(Decomposed {
+                base,
+                size: is_size.then_some(size),
+                condition: cond,
+                set_flags: modifier == "S",
+            });
+        }
+        None
+    }
+}
+
+/// Every instruction, with the suffixes it accepts.
+const MNEMONICS: &[Mnemonic] = &[
+    Mnemonic::new("ADR"),
+    Mnemonic::set_flags("AND"),
+    Mnemonic::set_flags("EOR"),
+    Mnemonic::set_flags("SUB"),
+    Mnemonic::set_flags("RSB"),
+    Mnemonic::set_flags("ADD"),
+    Mnemonic::set_flags("ADC"),
+    Mnemonic::set_flags("SBC"),
+    Mnemonic::set_flags("RSC"),
+    Mnemonic::new("TST"),
+    Mnemonic::new("TEQ"),
+    Mnemonic::new("CMP"),
+    Mnemonic::new("CMN"),
+    Mnemonic::set_flags("ORR"),
+    Mnemonic::set_flags("MOV"),
+    Mnemonic::set_flags("BIC"),
+    Mnemonic::set_flags("MVN"),
+    Mnemonic::set_flags("MUL"),
+    Mnemonic::set_flags("MLA"),
+    Mnemonic::new("MLS"),
+    Mnemonic::set_flags("UMULL"),
+    Mnemonic::set_flags("UMLAL"),
+    Mnemonic::set_flags("SMULL"),
+    Mnemonic::set_flags("SMLAL"),
+    Mnemonic::new("QADD"),
+    Mnemonic::new("QSUB"),
+    Mnemonic::new("CLZ"),
+    Mnemonic::new("REV"),
+    Mnemonic::new("REV16"),
+    Mnemonic::new("SBFX"),
+    Mnemonic::new("UBFX"),
+    Mnemonic::new("BFC"),
+    Mnemonic::new("BFI"),
+    Mnemonic::new("B"),
+    Mnemonic::new("BL"),
+    Mnemonic::new("BX"),
+    Mnemonic::new("BLX"),
+    Mnemonic::new("MRS"),
+    Mnemonic::new("MSR"),
+    Mnemonic::new("NOP"),
+    Mnemonic::new("SVC"),
+    Mnemonic::new("SWI"),
+    Mnemonic::new("BKPT"),
+    Mnemonic::new("HLT"),
+    Mnemonic::sized("LDR", &["B", "H", "SB", "SH", "D"]),
+    Mnemonic::sized("STR", &["B", "H", "D"]),
+    Mnemonic::sized("SWP", &["B"]),
+    Mnemonic::new("PUSH"),
+    Mnemonic::new("POP"),
+    Mnemonic::sized("LDM", &["IB", "DA", "DB"]).aliased(&[
+        ("IA", ""),
+        ("FD", ""),
+        ("ED", "IB"),
+        ("FA", "DA"),
+        ("EA", "DB"),
+    ]),
+    Mnemonic::sized("STM", &["IB", "DA", "DB"]).aliased(&[
+        ("IA", ""),
+        ("EA", ""),
+        ("FA", "IB"),
+        ("ED", "DA"),
+        ("FD", "DB"),
+    ]),
+];
+
+/// A mnemonic split into its parts, `LDREQB` is `LDR`, `B` and `EQ`.
+struct Decomposed {
+    /// The length of the base mnemonic.
+    base: usize,
+    /// The size suffix, after replacing an alias.
+    size: Option<&'static str>,
+    condition: Condition,
+    set_flags: bool,
+}
+
+/// Splits a mnemonic into its base, size, condition and `S` suffix,
+/// `None` if it isn't a known instruction.
+///
+/// Mnemonics can end in the letters of a condition, like `TEQ` or `BICS`,
+/// so only splits that leave known suffixes are allowed.
+/// When there are a few, the longest base wins.
+fn decompose(text: &str) -> Option<Decomposed> {
+    MNEMONICS
+        .iter()
+        .filter(|mnemonic| text.starts_with(mnemonic.base))
+        .filter_map(|mnemonic| mnemonic.split(&text[mnemonic.base.len()..]))
+        .max_by_key(|decomposed| decomposed.base)
+}
+
+fn condition(text: &str) -> Option<Condition> {
+    Some(match text {
+        // no suffix at all
+        "" | "AL" => Condition::AL,
+        "EQ" => Condition::EQ,
+        "NE" => Condition::NE,
+        "CS" | "HS" => Condition::CS,
+        "CC" | "LO" => Condition::CC,
+        "MI" => Condition::MI,
+        "PL" => Condition::PL,
+        "VS" => Condition::VS,
+        "VC" => Condition::VC,
+        "HI" => Condition::HI,
+        "LS" => Condition::LS,
+        "GE" => Condition::GE,
+        "LT" => Condition::LT,
+        "GT" => Condition::GT,
+        "LE" => Condition::LE,
+        _ => return None,
+    })
 }
 
 /// Address Register Offset?
-fn lower_address(frags: &mut Vec<Fragment>, address: ast::Address) {
+fn lower_address(frags: &mut Vec<Fragment>, address: ast::Address, symbols: Symbols) {
     let kind = match &address {
         ast::Address::Offset(_) => AddressKind::Offset,
         ast::Address::PreIndex(_) => AddressKind::PreIndex,
         ast::Address::PostIndex(_) => AddressKind::PostIndex,
     };
     frags.push(Fragment::Address(kind));
-    let base = address.base();
-    lower_register(frags, base);
-    if let Some(offset) = address.offset() {
-        lower_offset(frags, offset);
+    if let Some(base) = address.base() {
+        lower_register(frags, base);
+    }
+    match address.offset() {
+        Some(offset) => lower_offset(frags, offset, symbols),
+        // `[rn]` is `[rn, #0]`
+        None if !matches!(kind, AddressKind::PostIndex) => {
+            frags.push(Fragment::Sign(Sign::Plus));
+            frags.push(Fragment::Number(0));
+        }
+        None => (),
     }
 }
 
-/// Amount Shift
-fn lower_offset(frags: &mut Vec<Fragment>, offset: ast::Offset) {
-    lower_amount(frags, Some(offset.amount()));
-    lower_shift(frags, offset.shift());
+/// Sign Amount Shift
+fn lower_offset(frags: &mut Vec<Fragment>, offset: ast::Offset, symbols: Symbols) {
+    match offset.amount() {
+        Some(ast::NumOrReg::Num(number)) => {
+            // negative offsets are subtracted, so `#-4` is `Minus 4`
+            if let Ok(value) = fold(&number, symbols) {
+                let sign = if (value as i32).is_negative() {
+                    Sign::Minus
+                } else {
+                    Sign::Plus
+                };
+                frags.push(Fragment::Sign(sign));
+                frags.push(Fragment::Number((value as i32).unsigned_abs()));
+            }
+        }
+        Some(ast::NumOrReg::Reg(register)) => {
+            let sign = if offset.is_subtracted() {
+                Sign::Minus
+            } else {
+                Sign::Plus
+            };
+            frags.push(Fragment::Sign(sign));
+            lower_register(frags, register);
+        }
+        None => (),
+    }
+    lower_shift(frags, offset.shift(), symbols);
 }
 
 /// ShiftKind Amount
-fn lower_shift(frags: &mut Vec<Fragment>, shift: Option<ast::Shift>) {
+fn lower_shift(frags: &mut Vec<Fragment>, shift: Option<ast::Shift>, symbols: Symbols) {
     if let Some(kind) = shift.and_then(|shift| shift.kind()) {
         match kind {
             ast::ShiftKind::LSL { amount } => {
                 frags.push(Fragment::Shift(ShiftKind::LSL));
-                lower_amount(frags, amount)
+                lower_amount(frags, amount, symbols)
             }
             ast::ShiftKind::LSR { amount } => {
                 frags.push(Fragment::Shift(ShiftKind::LSR));
-                lower_amount(frags, amount);
+                lower_wide_amount(frags, amount, symbols);
             }
             ast::ShiftKind::ASR { amount } => {
                 frags.push(Fragment::Shift(ShiftKind::ASR));
-                lower_amount(frags, amount);
+                lower_wide_amount(frags, amount, symbols);
             }
             ast::ShiftKind::ROR { amount } => {
                 frags.push(Fragment::Shift(ShiftKind::ROR));
-                lower_amount(frags, amount);
+                lower_amount(frags, amount, symbols);
             }
+            // RRX is encoded as `ROR #0`
             ast::ShiftKind::RRX => {
-                frags.push(Fragment::Shift(ShiftKind::RRX));
+                frags.push(Fragment::Shift(ShiftKind::ROR));
                 frags.push(Fragment::Number(0x0));
             }
         }
@@ -219,7 +753,8 @@ fn lower_shift(frags: &mut Vec<Fragment>, shift: Option<ast::Shift>) {
 fn lower_reg_list(frags: &mut Vec<Fragment>, list: ast::RegList) {
     let mut regs = 0b0000_0000_0000_0000_u16;
 
-    let mut set_bit = |n| regs |= 1_u16 << n;
+    // registers that don't exist have been reported by validation
+    let mut set_bit = |n: u32| regs |= 1_u16.checked_shl(n).unwrap_or(0);
 
     for item in list.items() {
         match item {
@@ -240,24 +775,34 @@ fn lower_reg_list(frags: &mut Vec<Fragment>, list: ast::RegList) {
 }
 
 /// Number | Register
-fn lower_amount(frags: &mut Vec<Fragment>, amount: Option<ast::NumOrReg>) {
+fn lower_amount(frags: &mut Vec<Fragment>, amount: Option<ast::NumOrReg>, symbols: Symbols) {
     match amount {
-        Some(ast::NumOrReg::Num(number)) => lower_number(frags, number),
+        Some(ast::NumOrReg::Num(number)) => lower_number(frags, number, symbols),
         Some(ast::NumOrReg::Reg(register)) => lower_register(frags, register),
         None => (),
     }
 }
 
+/// Number | Register, where a shift by 32 is encoded as 0
+fn lower_wide_amount(frags: &mut Vec<Fragment>, amount: Option<ast::NumOrReg>, symbols: Symbols) {
+    let start = frags.len();
+    lower_amount(frags, amount, symbols);
+    if let [Fragment::Number(amount @ 32)] = &mut frags[start..] {
+        *amount = 0;
+    }
+}
+
 /// Number
-fn lower_number(frags: &mut Vec<Fragment>, number: ast::Number) {
-    if let Some(val) = number.value() {
+fn lower_number(frags: &mut Vec<Fragment>, number: ast::Number, symbols: Symbols) {
+    if let Ok(val) = fold(&number, symbols) {
         frags.push(Fragment::Number(val));
     }
 }
 
 /// Label
 fn lower_label(frags: &mut Vec<Fragment>, label: u32, current: u32) {
-    let offset = label as i32 + 8 - current as i32;
+    // the PC is 8 bytes ahead of the current instruction
+    let offset = label as i32 - (current as i32 + 8);
     frags.push(Fragment::Label(offset));
 }
 
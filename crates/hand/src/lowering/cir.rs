@@ -48,6 +48,10 @@ impl<'a> HANDCursor<'a> {
                     }
                     continue;
                 }
+                Fragment::Size(size) => {
+                    cir.extend(size.chars().map(CIR::Char));
+                    continue;
+                }
                 Fragment::Condition(cond) => CIR::Condition(match cond {
                     super::Condition::EQ => cir::Condition::EQ,
                     super::Condition::NE => cir::Condition::NE,
@@ -65,14 +69,22 @@ impl<'a> HANDCursor<'a> {
                     super::Condition::LE => cir::Condition::LE,
                     super::Condition::AL => cir::Condition::AL,
                 }),
+                Fragment::SetFlags => CIR::SetFlags,
                 Fragment::Register(r) => CIR::Register(r),
                 Fragment::RegisterList(rl) => CIR::RegisterList(rl),
+                Fragment::StatusRegister { spsr, fields } => {
+                    CIR::StatusRegister(cir::StatusRegister { spsr, fields })
+                }
                 Fragment::Number(num) => CIR::Number(num),
                 Fragment::Address(kind) => match kind {
                     AddressKind::Offset => CIR::OffsetAddress,
                     AddressKind::PreIndex => CIR::PreIndexAddress,
                     AddressKind::PostIndex => CIR::PostIndexAddress,
                 },
+                Fragment::Sign(sign) => CIR::Sign(match sign {
+                    super::Sign::Plus => cir::Sign::Plus,
+                    super::Sign::Minus => cir::Sign::Minus,
+                }),
                 Fragment::Shift(kind) => CIR::Shift(match kind {
                     super::ShiftKind::LSL => cir::Shift::LSL,
                     super::ShiftKind::LSR => cir::Shift::LSR,
@@ -81,6 +93,9 @@ impl<'a> HANDCursor<'a> {
                     super::ShiftKind::RRX => cir::Shift::RRX,
                 }),
                 Fragment::Bang => CIR::Bang,
+                Fragment::Byte(byte) => CIR::Byte(byte),
+                Fragment::Half(half) => CIR::Half(half),
+                Fragment::Word(word) => CIR::Word(word),
                 Fragment::Label(adr) => CIR::Label(adr),
             };
 
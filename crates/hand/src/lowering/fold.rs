@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use parser::rowan::TextRange;
+
+use crate::{
+    ast::{self, literal_value, AstToken, BinaryOp, Expr, UnaryOp},
+    Diagnostic,
+};
+
+/// The names a number can use.
+///
+/// Labels are only known once the code is laid out,
+/// so constants and sizes leave them out.
+#[derive(Clone, Copy)]
+pub struct Symbols<'a> {
+    constants: &'a HashMap<String, u32>,
+    labels: Option<&'a HashMap<String, u32>>,
+}
+
+impl<'a> Symbols<'a> {
+    pub fn new(constants: &'a HashMap<String, u32>, labels: &'a HashMap<String, u32>) -> Self {
+        Self {
+            constants,
+            labels: Some(labels),
+        }
+    }
+
+    pub fn constants(constants: &'a HashMap<String, u32>) -> Self {
+        Self {
+            constants,
+            labels: None,
+        }
+    }
+
+    /// Only the constants, for numbers that are needed to lay out the code.
+    pub fn without_labels(self) -> Self {
+        Self::constants(self.constants)
+    }
+
+    pub fn get(&self, name: &str) -> Option<u32> {
+        let label = || self.labels?.get(name);
+        self.constants.get(name).or_else(label).copied()
+    }
+}
+
+pub enum FoldError {
+    /// Already reported by the parser or validation
+    Reported,
+    Overflow(TextRange),
+    DivideByZero(TextRange),
+    Shift(TextRange, i64),
+    TooLarge(TextRange, i64),
+}
+
+impl FoldError {
+    pub fn diagnostic(self) -> Option<Diagnostic> {
+        let diagnostic = match self {
+            FoldError::Reported => return None,
+            FoldError::Overflow(range) => Diagnostic::error(range, "number overflows")
+                .with_hint("numbers are folded with 64 bits, then have to fit in 32"),
+            FoldError::DivideByZero(range) => Diagnostic::error(range, "cannot divide by zero"),
+            FoldError::Shift(range, amount) => {
+                Diagnostic::error(range, format!("cannot shift by {amount}"))
+                    .with_hint("shifts go from 0 to 31")
+            }
+            FoldError::TooLarge(range, value) => {
+                Diagnostic::error(range, format!("{value} does not fit in 32 bits"))
+                    .with_hint("numbers go from -0x8000_0000 to 0xFFFF_FFFF")
+            }
+        };
+
+        Some(diagnostic)
+    }
+}
+
+/// The value of a number, negative values are two's complement.
+pub fn fold(number: &ast::Number, symbols: Symbols) -> Result<u32, FoldError> {
+    let expr = number.expr().ok_or(FoldError::Reported)?;
+    let value = evaluate(&expr, symbols)?;
+
+    if (-0x8000_0000..=0xFFFF_FFFF).contains(&value) {
+        Ok(value as u32)
+    } else {
+        Err(FoldError::TooLarge(expr.text_range(), value))
+    }
+}
+
+fn evaluate(expr: &Expr, symbols: Symbols) -> Result<i64, FoldError> {
+    let value = match expr {
+        Expr::Literal(token) => literal_value(token).ok_or(FoldError::Reported)?.into(),
+        Expr::Name(name) => {
+            let id = name.ident().ok_or(FoldError::Reported)?;
+            symbols.get(id.text()).ok_or(FoldError::Reported)?.into()
+        }
+        Expr::Paren(paren) => evaluate(&paren.inner().ok_or(FoldError::Reported)?, symbols)?,
+        Expr::Unary(unary) => {
+            let operand = unary.operand().ok_or(FoldError::Reported)?;
+            let value = evaluate(&operand, symbols)?;
+            match unary.op().ok_or(FoldError::Reported)? {
+                UnaryOp::Neg => value
+                    .checked_neg()
+                    .ok_or(FoldError::Overflow(expr.text_range()))?,
+                UnaryOp::Plus => value,
+                // flip the 32 bits that are kept, so `~0` is 0xFFFF_FFFF
+                UnaryOp::Not => (!(value as u32)).into(),
+            }
+        }
+        Expr::Binary(binary) => {
+            let (op, _) = binary.op().ok_or(FoldError::Reported)?;
+            let lhs = binary.lhs().ok_or(FoldError::Reported)?;
+            let rhs = binary.rhs().ok_or(FoldError::Reported)?;
+            let (a, b) = (evaluate(&lhs, symbols)?, evaluate(&rhs, symbols)?);
+
+            let value = match op {
+                BinaryOp::Add => a.checked_add(b),
+                BinaryOp::Sub => a.checked_sub(b),
+                BinaryOp::Mul => a.checked_mul(b),
+                BinaryOp::Div | BinaryOp::Rem if b == 0 => {
+                    return Err(FoldError::DivideByZero(rhs.text_range()))
+                }
+                BinaryOp::Div => a.checked_div(b),
+                BinaryOp::Rem => a.checked_rem(b),
+                BinaryOp::Shl | BinaryOp::Shr if !(0..32).contains(&b) => {
+                    return Err(FoldError::Shift(rhs.text_range(), b))
+                }
+                BinaryOp::Shl => a.checked_mul(1 << b),
+                BinaryOp::Shr => Some(a >> b),
+                BinaryOp::And => Some(a & b),
+                BinaryOp::Or => Some(a | b),
+                BinaryOp::Xor => Some(a ^ b),
+            };
+            value.ok_or(FoldError::Overflow(expr.text_range()))?
+        }
+    };
+
+    Ok(value)
+}
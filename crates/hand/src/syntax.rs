@@ -10,10 +10,15 @@ pub enum SyntaxKind {
     Octal,
     Binary,
 
+    Str,
+    Char,
+
     OpenCurly,
     CloseCurly,
     OpenSquare,
     CloseSquare,
+    OpenParen,
+    CloseParen,
     Comma,
     Hash,
     Plus,
@@ -21,10 +26,22 @@ pub enum SyntaxKind {
     Bang,
     Colon,
     Equals,
+    Dot,
+    Star,
+    Slash,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
 
     Root,
     Statement,
     Instruction,
+    Directive,
+    Constant,
     Arguments,
     Item,
     OffsetAddress,
@@ -35,8 +52,14 @@ pub enum SyntaxKind {
     Register,
     RegisterList,
     RegisterRange,
+    StatusRegister,
     Label,
     Number,
+    PoolLiteral,
+    BinaryExpr,
+    UnaryExpr,
+    ParenExpr,
+    Text,
     Name,
     Punct,
     Error,
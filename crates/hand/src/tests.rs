@@ -0,0 +1,587 @@
+use std::sync::Arc;
+
+use cir::{Condition, Convert, CIR};
+
+use crate::{parse, Diagnostic, ParseResult, Severity};
+
+fn check(text: &str) -> ParseResult {
+    parse(Arc::from(text))
+}
+
+/// The source text each diagnostic points at, with its message.
+fn errors(result: &ParseResult) -> Vec<(&str, &str)> {
+    result
+        .diagnostics()
+        .iter()
+        .map(|Diagnostic { range, message, .. }| (&result.source()[*range], message.as_str()))
+        .collect()
+}
+
+/// The name, condition and `S` suffix of a lone instruction.
+fn mnemonic(text: &str) -> (String, Condition, bool) {
+    let cir = check(text).to_cir();
+    let name = cir
+        .iter()
+        .filter_map(|part| match part {
+            CIR::Char(c) => Some(*c),
+            _ => None,
+        })
+        .collect();
+    let condition = cir
+        .iter()
+        .find_map(|part| match part {
+            CIR::Condition(cond) => Some(*cond),
+            _ => None,
+        })
+        .expect("every instruction has a condition");
+    (name, condition, cir.contains(&CIR::SetFlags))
+}
+
+const CONDITIONS: [(&str, Condition); 17] = [
+    ("EQ", Condition::EQ),
+    ("NE", Condition::NE),
+    ("CS", Condition::CS),
+    ("HS", Condition::CS),
+    ("CC", Condition::CC),
+    ("LO", Condition::CC),
+    ("MI", Condition::MI),
+    ("PL", Condition::PL),
+    ("VS", Condition::VS),
+    ("VC", Condition::VC),
+    ("HI", Condition::HI),
+    ("LS", Condition::LS),
+    ("GE", Condition::GE),
+    ("LT", Condition::LT),
+    ("GT", Condition::GT),
+    ("LE", Condition::LE),
+    ("AL", Condition::AL),
+];
+
+#[test]
+fn no_diagnostics() {
+    let result = check("loop: ADD r0, r1, #1\nB loop");
+    assert!(result.diagnostics().is_empty());
+    assert!(!result.has_errors());
+}
+
+#[test]
+fn label_on_its_own() {
+    let result = check("start:\n    ADD r0, r1, #1\nend:\n");
+    assert!(result.diagnostics().is_empty());
+}
+
+#[test]
+fn comment_lines() {
+    let result = check("; setup\nADD r0, r1, #1 ; add\n/* block */\nHLT");
+    assert!(result.diagnostics().is_empty());
+}
+
+#[test]
+fn label_defined_twice() {
+    let result = check("a: HLT\na: HLT");
+    assert_eq!(
+        errors(&result),
+        [("a", "label `a` is defined more than once")]
+    );
+    assert_eq!(
+        result.diagnostics()[0].range,
+        crate::TextRange::new(7.into(), 8.into())
+    );
+    assert_eq!(result.diagnostics()[0].severity, Severity::Error);
+}
+
+#[test]
+fn statement_without_instruction() {
+    let result = check("#1, r0\nHLT");
+    assert_eq!(
+        errors(&result),
+        [("#", "expected a label or an instruction")]
+    );
+
+    let result = check("loop: #1");
+    assert_eq!(errors(&result), [("#1", "expected an instruction")]);
+}
+
+#[test]
+fn unexpected_token() {
+    let result = check("ADD r0, r1, %");
+    assert_eq!(errors(&result), [("%", "unexpected `%`")]);
+}
+
+#[test]
+fn unclosed_brackets() {
+    let result = check("LDR r0, [r1\nLDM r0, {r1, r2\nHLT");
+    assert_eq!(
+        errors(&result),
+        [("", "expected `]`"), ("", "expected `}`")]
+    );
+}
+
+#[test]
+fn unclosed_at_end_of_input() {
+    let result = check("LDM r0, {r1");
+    assert_eq!(errors(&result), [("", "expected `}`")]);
+    for text in ["X {r1", "X {r1-"] {
+        assert!(check(text).has_errors(), "{text}");
+    }
+}
+
+#[test]
+fn bad_addresses() {
+    let result = check("LDR r0, [#1]");
+    assert_eq!(errors(&result), [("#", "expected a register")]);
+
+    let result = check("LDR r0, [r1, r2, FOO #2]");
+    assert_eq!(
+        errors(&result),
+        [("FOO", "expected a shift (LSL, LSR, ASR, ROR or RRX)")]
+    );
+
+    let result = check("LDR r0, [r1, r2, LSL]");
+    assert_eq!(errors(&result), [("]", "expected a shift amount")]);
+}
+
+#[test]
+fn bad_numbers() {
+    let result = check("ADD r0, r1, #r2");
+    assert_eq!(errors(&result), [("r2", "expected a number")]);
+
+    let result = check("ADD r0, r1, #0x1_0000_0000");
+    assert_eq!(
+        errors(&result),
+        [("0x1_0000_0000", "number does not fit in 32 bits")]
+    );
+}
+
+#[test]
+fn signed_offsets() {
+    let result = check(
+        "LDR r0, [r1, #-4]\n\
+        LDR r0, [r1, -r2, LSL #2]!\n\
+        LDR r0, [r1], -r2\n\
+        LDR r0, [r1, +r2]",
+    );
+    assert!(
+        result.diagnostics().is_empty(),
+        "{:?}",
+        result.diagnostics()
+    );
+
+    let result = check("LDR r0, [r1, -#4]");
+    assert_eq!(errors(&result), [("#", "expected a register")]);
+}
+
+#[test]
+fn bad_register_list() {
+    let result = check("LDM r0, {r1, %, r2}");
+    assert_eq!(errors(&result), [("%", "unexpected `%`")]);
+}
+
+#[test]
+fn reports_every_line() {
+    let result = check("ADD r0, %\nHLT\nLDR r0, [#1]\na: HLT\na: HLT");
+    assert_eq!(
+        errors(&result),
+        [
+            ("%", "unexpected `%`"),
+            ("#", "expected a register"),
+            ("a", "label `a` is defined more than once"),
+        ]
+    );
+}
+
+#[test]
+fn registers_exist() {
+    let result = check("ADD r0, r16, #1\nLDR r0, [r99]\nLDM r0, {r1, R20-r21}");
+    assert_eq!(
+        errors(&result),
+        [
+            ("r16", "register `r16` does not exist"),
+            ("r99", "register `r99` does not exist"),
+            ("R20", "register `R20` does not exist"),
+            ("r21", "register `r21` does not exist"),
+        ]
+    );
+}
+
+#[test]
+fn register_ranges_ascend() {
+    let result = check("LDM r0, {r4-r1}\nLDM r0, {r1-r1, r2-r4}");
+    assert_eq!(
+        errors(&result),
+        [("r4-r1", "register range goes backwards")]
+    );
+    assert_eq!(
+        result.diagnostics()[0].hint.as_deref(),
+        Some("ranges go from low to high, try `r1-r4`")
+    );
+}
+
+#[test]
+fn register_lists_have_registers() {
+    let result = check("LDM r0, {}\nPUSH {r1}");
+    assert_eq!(errors(&result), [("{}", "register list is empty")]);
+}
+
+#[test]
+fn writeback_of_listed_base() {
+    let result =
+        check("LDM r0!, {r0, r1}\nSTMDB sp!, {r1-r3, lr}\nLDM r2, {r1-r3}\nSTM r4!, {r2-r5}");
+    assert_eq!(
+        errors(&result),
+        [
+            ("r0", "`r0` is written back, but it is also in the list"),
+            ("r4", "`r4` is written back, but it is also in the list"),
+        ]
+    );
+    assert!(result
+        .diagnostics()
+        .iter()
+        .all(|diagnostic| diagnostic.severity == Severity::Warning));
+}
+
+#[test]
+fn status_register_fields() {
+    let result = check(
+        "MSR CPSR_fc, r0\n\
+        MSR cpsr_fsxc, #0\n\
+        MRS r0, SPSR\n\
+        MSR CPSR_q, r0\n\
+        MSR SPSR_ff, r1\n\
+        MSR CPSR_, r2",
+    );
+    assert_eq!(
+        errors(&result),
+        [
+            ("CPSR_q", "`CPSR_q` doesn't name fields of the register"),
+            ("SPSR_ff", "`SPSR_ff` doesn't name fields of the register"),
+            ("CPSR_", "`CPSR_` doesn't name fields of the register"),
+        ]
+    );
+}
+
+#[test]
+fn shift_amounts_fit() {
+    let result = check(
+        "LDR r0, [r1, r2, LSL #0]\n\
+        LDR r0, [r1, r2, LSL #31]\n\
+        LDR r0, [r1, r2, LSL #32]\n\
+        LDR r0, [r1, r2, LSR #0]\n\
+        LDR r0, [r1, r2, LSR #32]\n\
+        LDR r0, [r1, r2, ASR #33]\n\
+        LDR r0, [r1, r2, ROR #32]\n\
+        LDR r0, [r1, r2, LSL r3]",
+    );
+    assert_eq!(
+        errors(&result),
+        [
+            ("#32", "cannot shift by 32 with LSL"),
+            ("#0", "cannot shift by 0 with LSR"),
+            ("#33", "cannot shift by 33 with ASR"),
+            ("#32", "cannot shift by 32 with ROR"),
+        ]
+    );
+    assert_eq!(
+        result.diagnostics()[1].hint.as_deref(),
+        Some("LSR shifts by #1 to #32")
+    );
+}
+
+#[test]
+fn shift_operands() {
+    let result = check(
+        "ADD r0, r1, r2, LSL #2\n\
+        MOV r0, r1, RRX\n\
+        MVN r0, r1, ASR r2\n\
+        CMP r0, r1, LSR #32",
+    );
+    assert!(
+        result.diagnostics().is_empty(),
+        "{:?}",
+        result.diagnostics()
+    );
+
+    let result = check("MOV r0, r1, LSL\nMOV r0, r1, ROR #32");
+    assert_eq!(
+        errors(&result),
+        [
+            ("", "expected a shift amount"),
+            ("#32", "cannot shift by 32 with ROR"),
+        ]
+    );
+}
+
+#[test]
+fn condition_suffixes() {
+    for (suffix, cond) in CONDITIONS {
+        let expect = |name: &str, set_flags| (name.to_owned(), cond, set_flags);
+        assert_eq!(mnemonic(&format!("B{suffix}")), expect("B", false));
+        assert_eq!(mnemonic(&format!("TEQ{suffix}")), expect("TEQ", false));
+        assert_eq!(mnemonic(&format!("ADD{suffix}")), expect("ADD", false));
+        assert_eq!(mnemonic(&format!("ADDS{suffix}")), expect("ADD", true));
+        assert_eq!(mnemonic(&format!("ADD{suffix}S")), expect("ADD", true));
+        assert_eq!(mnemonic(&format!("BIC{suffix}S")), expect("BIC", true));
+        assert_eq!(mnemonic(&format!("LDRB{suffix}")), expect("LDRB", false));
+        assert_eq!(mnemonic(&format!("LDR{suffix}SH")), expect("LDRSH", false));
+    }
+    assert_eq!(mnemonic("HLT"), ("HLT".to_owned(), Condition::AL, false));
+}
+
+#[test]
+fn mnemonics_ending_in_conditions() {
+    let expect = |name: &str, cond, set_flags| (name.to_owned(), cond, set_flags);
+    assert_eq!(mnemonic("TEQ"), expect("TEQ", Condition::AL, false));
+    assert_eq!(mnemonic("BICS"), expect("BIC", Condition::AL, true));
+    assert_eq!(mnemonic("BICCS"), expect("BIC", Condition::CS, false));
+    assert_eq!(mnemonic("BLS"), expect("B", Condition::LS, false));
+    assert_eq!(mnemonic("BLE"), expect("B", Condition::LE, false));
+    assert_eq!(mnemonic("BLLE"), expect("BL", Condition::LE, false));
+    assert_eq!(mnemonic("BLXCC"), expect("BLX", Condition::CC, false));
+    // `LDRH` has no `S` suffix, so this is `LDR` if higher or same
+    assert_eq!(mnemonic("LDRHS"), expect("LDR", Condition::CS, false));
+    assert_eq!(mnemonic("LDRSB"), expect("LDRSB", Condition::AL, false));
+    assert_eq!(mnemonic("SWPBCS"), expect("SWPB", Condition::CS, false));
+    assert_eq!(mnemonic("BFCCC"), expect("BFC", Condition::CC, false));
+    assert_eq!(mnemonic("REV16NE"), expect("REV16", Condition::NE, false));
+    // tests always update the flags, they are left for the matcher to report
+    assert_eq!(mnemonic("CMPS"), expect("CMPS", Condition::AL, false));
+    assert_eq!(mnemonic("ADDSS"), expect("ADDSS", Condition::AL, false));
+}
+
+#[test]
+fn stack_aliases() {
+    let expect = |name: &str, cond| (name.to_owned(), cond, false);
+    assert_eq!(mnemonic("LDMIA r0, {r1}"), expect("LDM", Condition::AL));
+    assert_eq!(mnemonic("LDMFD r0, {r1}"), expect("LDM", Condition::AL));
+    assert_eq!(mnemonic("LDMEDNE r0, {r1}"), expect("LDMIB", Condition::NE));
+    assert_eq!(mnemonic("LDMFA r0, {r1}"), expect("LDMDA", Condition::AL));
+    assert_eq!(mnemonic("LDMEQEA r0, {r1}"), expect("LDMDB", Condition::EQ));
+    assert_eq!(mnemonic("STMEA r0, {r1}"), expect("STM", Condition::AL));
+    assert_eq!(mnemonic("STMFA r0, {r1}"), expect("STMIB", Condition::AL));
+    assert_eq!(mnemonic("STMED r0, {r1}"), expect("STMDA", Condition::AL));
+    assert_eq!(mnemonic("STMFDGT r0, {r1}"), expect("STMDB", Condition::GT));
+    assert_eq!(mnemonic("STMDBLS r0, {r1}"), expect("STMDB", Condition::LS));
+}
+
+#[test]
+fn labels_are_defined() {
+    let result = check("B loop\nADR r0, start\nstart: HLT");
+    assert_eq!(errors(&result), [("loop", "label `loop` is not defined")]);
+}
+
+#[test]
+fn directives() {
+    let result = check(
+        "table: .word 1, 0xFFFF_FFFF, table\n\
+        .half 0xFFFF\n\
+        .byte 255, #1\n\
+        .space 4, 0xFF\n\
+        .align\n\
+        .align 4\n\
+        HLT",
+    );
+    assert!(
+        result.diagnostics().is_empty(),
+        "{:?}",
+        result.diagnostics()
+    );
+}
+
+#[test]
+fn bad_directives() {
+    let result = check(
+        ".foo 1\n\
+        .word\n\
+        .byte 256\n\
+        .half label\n\
+        .space 1, 2, 3\n\
+        .align 17\n\
+        label: HLT",
+    );
+    assert_eq!(
+        errors(&result),
+        [
+            ("foo", "unknown directive `.foo`"),
+            (".word", "`.word` takes at least one value"),
+            ("256", "`.byte` can't be given 256, the largest is 255"),
+            ("label", "`.half` only takes numbers"),
+            (
+                ".space 1, 2, 3",
+                "`.space` takes a size and an optional fill byte"
+            ),
+            ("17", "`.align` can't be given 17, the largest is 16"),
+        ]
+    );
+}
+
+#[test]
+fn instructions_are_aligned() {
+    let result = check(".byte 1\nHLT\n.align\nHLT");
+    assert_eq!(
+        errors(&result),
+        [("HLT", "instruction is not aligned to a word")]
+    );
+    assert_eq!(
+        result.diagnostics()[0].range,
+        crate::TextRange::new(8.into(), 11.into())
+    );
+}
+
+#[test]
+fn strings_and_characters() {
+    let result = check(
+        ".ascii \"hello\\n\", \"\\x41\\\"\"\n\
+        .asciz \"\"\n\
+        .byte 'a', '\\0'\n\
+        .align\n\
+        CMP r0, #'\\''",
+    );
+    assert!(
+        result.diagnostics().is_empty(),
+        "{:?}",
+        result.diagnostics()
+    );
+}
+
+#[test]
+fn bad_literals() {
+    let result = check(
+        ".ascii \"open\n\
+        .ascii \"\\q\"\n\
+        .ascii \"\\x4\"\n\
+        .ascii \"caf\u{e9}\"\n\
+        .ascii 1\n\
+        .word \"text\"\n\
+        CMP r0, #'ab'\n\
+        CMP r0, #'",
+    );
+    assert_eq!(
+        errors(&result),
+        [
+            ("\"open", "unterminated string"),
+            ("\\q", "unknown escape"),
+            ("\\x4", "`\\x` is followed by two hex digits"),
+            ("\u{e9}", "only ASCII characters are supported"),
+            ("'ab'", "a character literal holds exactly one character"),
+            ("'", "unterminated character"),
+            ("1", "`.ascii` only takes strings"),
+            ("\"text\"", "`.word` only takes numbers"),
+        ]
+    );
+    assert_eq!(
+        result.diagnostics()[0].hint.as_deref(),
+        Some("strings end with a `\"`")
+    );
+}
+
+#[test]
+fn constants() {
+    let result = check(
+        ".equ SIZE, 16\n\
+        WORDS EQU 4\n\
+        TOTAL equ SIZE\n\
+        .space #SIZE\n\
+        .word #WORDS, start\n\
+        start: ADD r0, r1, #TOTAL\n\
+        LDR r0, [r1, #WORDS]\n\
+        MOV r0, r1, LSL #WORDS",
+    );
+    assert!(
+        result.diagnostics().is_empty(),
+        "{:?}",
+        result.diagnostics()
+    );
+}
+
+#[test]
+fn bad_constants() {
+    let result = check(
+        "ADD r0, r1, #LATER\n\
+        .equ LATER, 1\n\
+        .equ LATER, 2\n\
+        .equ a, 0\n\
+        a: ADD r0, r1, #MISSING\n\
+        .space #a\n\
+        CMP r0, LATER\n\
+        .equ ONLY\n\
+        .equ TEXT, \"text\"",
+    );
+    assert_eq!(
+        errors(&result),
+        [
+            (".equ ONLY", "`equ` takes a name and a value"),
+            ("\"text\"", "`equ` only takes a number"),
+            ("LATER", "constant `LATER` is used before it is defined"),
+            ("LATER", "constant `LATER` is defined more than once"),
+            ("a", "`a` is already a label"),
+            ("MISSING", "`MISSING` is not defined"),
+            ("a", "`a` is a label, not a constant"),
+            ("LATER", "constant `LATER` is used without a `#`"),
+        ]
+    );
+}
+
+#[test]
+fn expressions() {
+    let result = check(
+        ".equ BUF_SIZE, 4 * 8\n\
+        .equ MASK, ~(1 << 5) & 0xFF\n\
+        start: .space #BUF_SIZE\n\
+        end: ADD r0, r1, #(end - start) / 4\n\
+        LDR r0, [r1, #-BUF_SIZE + 1]\n\
+        B end + 8\n\
+        .word start + 4, -1, MASK | 1 ^ 2 % 3",
+    );
+    assert!(
+        result.diagnostics().is_empty(),
+        "{:?}",
+        result.diagnostics()
+    );
+}
+
+#[test]
+fn bad_expressions() {
+    let result = check(
+        "ADD r0, r1, #1 / (2 - 2)\n\
+        ADD r0, r1, #0xFFFF_FFFF + 1\n\
+        ADD r0, r1, #1 << 32\n\
+        ADD r0, r1, #(1 + 2\n\
+        ADD r0, r1, #1 +\n\
+        .space #end\n\
+        end: HLT\n\
+        .byte 0x80 * 2",
+    );
+    assert_eq!(
+        errors(&result),
+        [
+            ("", "expected `)`"),
+            ("", "expected a number"),
+            ("end", "`end` is a label, not a constant"),
+            ("(2 - 2)", "cannot divide by zero"),
+            ("0xFFFF_FFFF + 1", "4294967296 does not fit in 32 bits"),
+            ("32", "cannot shift by 32"),
+            ("0x80 * 2", "`.byte` can't be given 256, the largest is 255"),
+        ]
+    );
+}
+
+#[test]
+fn literal_pools() {
+    let result = check(
+        "LDR r0, =0x12345678\n\
+        LDR r1, =label + 4\n\
+        .ltorg\n\
+        label: LDR r2, =FAR\n\
+        .space 4100\n\
+        .ltorg 1\n\
+        .equ FAR, 1",
+    );
+    assert_eq!(
+        errors(&result),
+        [
+            (".ltorg 1", "`.ltorg` takes no values"),
+            ("FAR", "constant `FAR` is used before it is defined"),
+            ("=FAR", "literal pool is 4096 bytes away"),
+        ]
+    );
+}
@@ -1,4 +1,7 @@
+mod mismatch;
 pub mod pattern;
+#[cfg(test)]
+mod tests;
 mod token;
 
 #[cfg(feature = "derive")]
@@ -9,6 +12,7 @@ pub use pattern_derive::Pattern;
 
 use trie_rs::map::{Trie, TrieBuilder};
 
+pub use mismatch::Mismatch;
 pub use pattern::Pattern;
 pub use token::PatternToken;
 
@@ -26,16 +30,19 @@ impl<T: ConstPattern> HasPattern for T {
     }
 }
 
-/// Convenience function that wraps matched values together.
+/// Convenience function that wraps matched values together,
+/// explaining why nothing matched otherwise.
 pub fn match_pair<'a, 'b, V>(
     matcher: &'a Matcher<V>,
     pattern: &'b [Pattern],
-) -> Option<Match<'a, 'b, V>> {
-    let value = matcher.find_match(pattern)?;
-    Some(Match {
-        value,
-        matched: pattern,
-    })
+) -> Result<Match<'a, 'b, V>, Mismatch> {
+    match matcher.find_match(pattern) {
+        Some(value) => Ok(Match {
+            value,
+            matched: pattern,
+        }),
+        None => Err(matcher.explain(pattern)),
+    }
 }
 
 pub struct Matcher<V> {
@@ -46,6 +53,25 @@ impl<V> Matcher<V> {
     pub fn find_match(&self, pattern: &[Pattern]) -> Option<&V> {
         self.inner.exact_match(pattern)
     }
+
+    /// Finds the patterns that share a name with `pattern`, to explain why it didn't match.
+    pub fn explain(&self, pattern: &[Pattern]) -> Mismatch {
+        let name = pattern
+            .iter()
+            .take_while(|p| matches!(p, Pattern::Char(_)))
+            .copied()
+            .collect::<Vec<_>>();
+
+        // a longer name continues with more chars
+        let candidates = self
+            .inner
+            .postfix_search::<Vec<Pattern>, _>(&name)
+            .map(|(postfix, _)| postfix)
+            .filter(|postfix| !matches!(postfix.first(), Some(Pattern::Char(_))))
+            .collect();
+
+        Mismatch::new(pattern, candidates)
+    }
 }
 
 pub struct Patterns<V> {
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use crate::Pattern;
+
+/// Why a pattern didn't match, found from the patterns that share its name.
+///
+/// Names are the leading [`Pattern::Char`]s, everything after them but the
+/// [`Pattern::Condition`] and [`Pattern::SetFlags`] is an operand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The name the pattern starts with.
+    pub name: String,
+    /// The operands of every pattern with the same name,
+    /// empty if no pattern has that name.
+    pub candidates: Vec<Vec<Pattern>>,
+    /// The operands that were given.
+    pub found: Vec<Pattern>,
+    /// The index into `found` of the first operand that no candidate accepts.
+    pub diverged: usize,
+}
+
+impl Mismatch {
+    pub(crate) fn new(pattern: &[Pattern], candidates: Vec<Vec<Pattern>>) -> Self {
+        let (name, found) = split_name(pattern);
+        // forms that only differ by their suffixes have the same operands
+        let mut unique = Vec::<Vec<Pattern>>::new();
+        for candidate in candidates.iter().map(|candidate| operands(candidate)) {
+            if !unique.contains(&candidate) {
+                unique.push(candidate);
+            }
+        }
+        let candidates = unique;
+
+        let diverged = candidates
+            .iter()
+            .map(|candidate| common_prefix(candidate, &found))
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            name,
+            candidates,
+            found,
+            diverged,
+        }
+    }
+
+    /// Whether there aren't any patterns with this name at all.
+    pub fn is_unknown_name(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// What the closest candidates accept where the operands diverged,
+    /// `None` for candidates that expect no more operands.
+    pub fn expected(&self) -> Vec<Option<Pattern>> {
+        let mut expected = self
+            .candidates
+            .iter()
+            .filter(|candidate| common_prefix(candidate, &self.found) == self.diverged)
+            .map(|candidate| candidate.get(self.diverged).copied())
+            .collect::<Vec<_>>();
+        expected.sort();
+        expected.dedup();
+        expected
+    }
+
+    /// The operand that was found where the operands diverged, if any.
+    pub fn unexpected(&self) -> Option<Pattern> {
+        self.found.get(self.diverged).copied()
+    }
+
+    /// Describes where the operands diverged,
+    /// e.g. "expected Number or Register after Register, found Label".
+    pub fn divergence(&self) -> String {
+        let expected = self
+            .expected()
+            .into_iter()
+            .map(|pattern| match pattern {
+                Some(pattern) => format!("{pattern:?}"),
+                None => "no more operands".to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join(" or ");
+        let found = match self.unexpected() {
+            Some(pattern) => format!("{pattern:?}"),
+            None => "nothing".to_owned(),
+        };
+
+        match self.diverged {
+            0 => format!("expected {expected} first, found {found}"),
+            n => format!(
+                "expected {expected} after {}, found {found}",
+                list(&self.found[..n])
+            ),
+        }
+    }
+}
+
+/// "ADD expects Register, Register, Number or Register, Register, Register; found Register, Label"
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_unknown_name() {
+            return write!(f, "there is no instruction named {}", self.name);
+        }
+
+        let candidates = self
+            .candidates
+            .iter()
+            .map(|candidate| list(candidate))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        write!(
+            f,
+            "{} expects {candidates}; found {}",
+            self.name,
+            list(&self.found)
+        )
+    }
+}
+
+/// Splits a pattern into its name and operands.
+fn split_name(pattern: &[Pattern]) -> (String, Vec<Pattern>) {
+    let length = pattern
+        .iter()
+        .take_while(|p| matches!(p, Pattern::Char(_)))
+        .count();
+    let name = pattern[..length]
+        .iter()
+        .map(|p| match p {
+            Pattern::Char(c) => *c,
+            _ => unreachable!("names are only made of chars"),
+        })
+        .collect();
+    (name, operands(&pattern[length..]))
+}
+
+fn operands(pattern: &[Pattern]) -> Vec<Pattern> {
+    pattern
+        .iter()
+        .copied()
+        .filter(|p| !matches!(p, Pattern::Condition | Pattern::SetFlags))
+        .collect()
+}
+
+fn common_prefix(a: &[Pattern], b: &[Pattern]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn list(patterns: &[Pattern]) -> String {
+    if patterns.is_empty() {
+        return "no operands".to_owned();
+    }
+
+    patterns
+        .iter()
+        .map(|p| format!("{p:?}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
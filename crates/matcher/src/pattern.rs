@@ -4,7 +4,10 @@ pub enum Pattern {
     Char(char),
     Register,
     RegisterList,
+    StatusRegister,
     Condition,
+    SetFlags,
+    Sign,
     Shift,
     Number,
     Label,
@@ -12,10 +15,42 @@ pub enum Pattern {
     PreIndexAddress,
     PostIndexAddress,
     Bang,
+    Data,
 }
 
 use cir::CIR;
 
+/// The number of patterns in every part, the size of their [`concat`].
+pub const fn len(parts: &[&[Pattern]]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        len += parts[i].len();
+        i += 1;
+    }
+    len
+}
+
+/// Joins the patterns of each part, as a type can be made of more than one pattern.
+///
+/// `N` must be the [`len`] of the parts.
+pub const fn concat<const N: usize>(parts: &[&[Pattern]]) -> [Pattern; N] {
+    let mut pattern = [Pattern::Data; N];
+    let mut n = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        let mut j = 0;
+        while j < parts[i].len() {
+            pattern[n] = parts[i][j];
+            n += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    assert!(n == N, "`N` is the length of the parts");
+    pattern
+}
+
 pub fn from_cir(cir: &[CIR]) -> Vec<Pattern> {
     let mut res = Vec::new();
 
@@ -25,7 +60,10 @@ pub fn from_cir(cir: &[CIR]) -> Vec<Pattern> {
             CIR::Char(c) => Pattern::Char(*c),
             CIR::Register(_) => Pattern::Register,
             CIR::RegisterList(_) => Pattern::RegisterList,
+            CIR::StatusRegister(_) => Pattern::StatusRegister,
             CIR::Condition(_) => Pattern::Condition,
+            CIR::SetFlags => Pattern::SetFlags,
+            CIR::Sign(_) => Pattern::Sign,
             CIR::Shift(_) => Pattern::Shift,
             CIR::Number(_) => Pattern::Number,
             CIR::Label(_) => Pattern::Label,
@@ -33,6 +71,7 @@ pub fn from_cir(cir: &[CIR]) -> Vec<Pattern> {
             CIR::PreIndexAddress => Pattern::PreIndexAddress,
             CIR::PostIndexAddress => Pattern::PostIndexAddress,
             CIR::Bang => Pattern::Bang,
+            CIR::Byte(_) | CIR::Half(_) | CIR::Word(_) => Pattern::Data,
         };
 
         res.push(pattern);
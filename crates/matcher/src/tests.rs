@@ -0,0 +1,116 @@
+use crate::{Matcher, Mismatch, Pattern, Patterns};
+
+use Pattern::*;
+
+fn matcher(patterns: &[&[Pattern]]) -> Matcher<usize> {
+    let mut p = Patterns::new();
+    for (i, pattern) in patterns.iter().enumerate() {
+        p.push(i, pattern);
+    }
+    p.finish()
+}
+
+const ADD_IMM: &[Pattern] = &[
+    Char('A'),
+    Char('D'),
+    Char('D'),
+    Condition,
+    Register,
+    Register,
+    Number,
+];
+const ADD_REG: &[Pattern] = &[
+    Char('A'),
+    Char('D'),
+    Char('D'),
+    Condition,
+    Register,
+    Register,
+    Register,
+    Shift,
+    Number,
+];
+const ADR: &[Pattern] = &[Char('A'), Char('D'), Char('R'), Condition, Register, Label];
+const ADDS: &[Pattern] = &[
+    Char('A'),
+    Char('D'),
+    Char('D'),
+    Char('S'),
+    Condition,
+    Register,
+];
+
+#[test]
+fn matches() {
+    let m = matcher(&[ADD_IMM, ADD_REG, ADR]);
+    assert_eq!(crate::match_pair(&m, ADD_REG).map(|m| *m.value()), Ok(1));
+}
+
+#[test]
+fn unknown_name() {
+    let m = matcher(&[ADD_IMM, ADR]);
+    let mismatch = m.explain(&[Char('S'), Char('U'), Char('B'), Condition, Register]);
+    assert!(mismatch.is_unknown_name());
+    assert_eq!(mismatch.name, "SUB");
+    assert_eq!(mismatch.to_string(), "there is no instruction named SUB");
+}
+
+#[test]
+fn candidates_share_the_name() {
+    let m = matcher(&[ADD_IMM, ADD_REG, ADR, ADDS]);
+    let mismatch = m.explain(&[Char('A'), Char('D'), Char('D'), Condition, Register, Label]);
+    assert_eq!(
+        mismatch,
+        Mismatch {
+            name: "ADD".to_owned(),
+            candidates: vec![
+                vec![Register, Register, Register, Shift, Number],
+                vec![Register, Register, Number],
+            ],
+            found: vec![Register, Label],
+            diverged: 1,
+        }
+    );
+    assert_eq!(
+        mismatch.to_string(),
+        "ADD expects Register, Register, Register, Shift, Number or Register, Register, Number; \
+        found Register, Label"
+    );
+    assert_eq!(
+        mismatch.divergence(),
+        "expected Register after Register, found Label"
+    );
+}
+
+#[test]
+fn divergence() {
+    let m = matcher(&[ADD_IMM, ADD_REG]);
+    let add = |operands: &[Pattern]| {
+        let pattern = [&[Char('A'), Char('D'), Char('D'), Condition], operands].concat();
+        m.explain(&pattern)
+    };
+
+    // too few
+    let mismatch = add(&[Register, Register]);
+    assert_eq!(mismatch.expected(), [Some(Register), Some(Number)]);
+    assert_eq!(mismatch.unexpected(), None);
+    assert_eq!(
+        mismatch.divergence(),
+        "expected Register or Number after Register, Register, found nothing"
+    );
+
+    // too many
+    let mismatch = add(&[Register, Register, Number, Number]);
+    assert_eq!(mismatch.expected(), [None]);
+    assert_eq!(
+        mismatch.divergence(),
+        "expected no more operands after Register, Register, Number, found Number"
+    );
+
+    // wrong from the start
+    let mismatch = add(&[Number]);
+    assert_eq!(
+        mismatch.divergence(),
+        "expected Register first, found Number"
+    );
+}
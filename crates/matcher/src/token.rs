@@ -3,45 +3,87 @@ use cir::structured::*;
 use crate::Pattern;
 
 pub trait PatternToken {
-    const TOKEN: Pattern;
+    /// The patterns this type is parsed from, most types are only one.
+    const TOKENS: &'static [Pattern];
 }
 
 impl<T: RegName> PatternToken for Register<T> {
-    const TOKEN: Pattern = Pattern::Register;
+    const TOKENS: &'static [Pattern] = &[Pattern::Register];
+}
+
+impl PatternToken for RegisterPair {
+    const TOKENS: &'static [Pattern] = &[Pattern::Register, Pattern::Register];
 }
 
 impl PatternToken for RegisterList {
-    const TOKEN: Pattern = Pattern::RegisterList;
+    const TOKENS: &'static [Pattern] = &[Pattern::RegisterList];
 }
 
 impl PatternToken for Condition {
-    const TOKEN: Pattern = Pattern::Condition;
+    const TOKENS: &'static [Pattern] = &[Pattern::Condition];
+}
+
+impl PatternToken for SetFlags {
+    const TOKENS: &'static [Pattern] = &[Pattern::SetFlags];
+}
+
+impl PatternToken for Writeback {
+    const TOKENS: &'static [Pattern] = &[Pattern::Bang];
+}
+
+impl PatternToken for Psr {
+    const TOKENS: &'static [Pattern] = &[Pattern::StatusRegister];
+}
+
+impl PatternToken for PsrFields {
+    const TOKENS: &'static [Pattern] = &[Pattern::StatusRegister];
+}
+
+impl PatternToken for Sign {
+    const TOKENS: &'static [Pattern] = &[Pattern::Sign];
 }
 
 impl PatternToken for Shift {
-    const TOKEN: Pattern = Pattern::Shift;
+    const TOKENS: &'static [Pattern] = &[Pattern::Shift];
 }
 
 impl<const BITS: u8> PatternToken for Number<BITS> {
-    const TOKEN: Pattern = Pattern::Number;
+    const TOKENS: &'static [Pattern] = &[Pattern::Number];
+}
+
+impl<T> PatternToken for BitField<T> {
+    const TOKENS: &'static [Pattern] = &[Pattern::Number, Pattern::Number];
+}
+
+impl PatternToken for ModImm {
+    const TOKENS: &'static [Pattern] = &[Pattern::Number];
 }
 
 impl PatternToken for Label {
-    const TOKEN: Pattern = Pattern::Label;
+    const TOKENS: &'static [Pattern] = &[Pattern::Label];
 }
 
 impl PatternToken for Address<Offset> {
-    const TOKEN: Pattern = Pattern::OffsetAddress;
+    const TOKENS: &'static [Pattern] = &[Pattern::OffsetAddress];
 }
 
 impl PatternToken for Address<PreIndex> {
-    const TOKEN: Pattern = Pattern::PreIndexAddress;
+    const TOKENS: &'static [Pattern] = &[Pattern::PreIndexAddress];
 }
 
 impl PatternToken for Address<PostIndex> {
-    const TOKEN: Pattern = Pattern::PostIndexAddress;
+    const TOKENS: &'static [Pattern] = &[Pattern::PostIndexAddress];
+}
+
+impl PatternToken for BaseAddress {
+    const TOKENS: &'static [Pattern] = &[
+        Pattern::OffsetAddress,
+        Pattern::Register,
+        Pattern::Sign,
+        Pattern::Number,
+    ];
 }
 
 impl PatternToken for Bang {
-    const TOKEN: Pattern = Pattern::Bang;
+    const TOKENS: &'static [Pattern] = &[Pattern::Bang];
 }
@@ -1,10 +1,17 @@
 use std::{iter::Peekable, sync::Arc};
 
 use lexer::{Lexable, Token as _, TokenInfo, TokenStream};
-use rowan::{Checkpoint, GreenNodeBuilder, SyntaxNode};
+use rowan::{Checkpoint, GreenNodeBuilder, SyntaxNode, TextRange, TextSize};
 
 pub use rowan;
 
+/// An error found while parsing, at the token that caused it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub range: TextRange,
+    pub message: String,
+}
+
 pub struct Parser<L>
 where
     L: rowan::Language + Lexable,
@@ -12,6 +19,7 @@ where
     text: Arc<str>,
     builder: GreenNodeBuilder<'static>,
     tokens: Peekable<TokenStream<L>>,
+    errors: Vec<SyntaxError>,
 }
 
 impl<L> Parser<L>
@@ -23,6 +31,7 @@ where
             text,
             builder: GreenNodeBuilder::new(),
             tokens: tokens.peekable(),
+            errors: Vec::new(),
         }
     }
 }
@@ -79,8 +88,32 @@ where
         Marker::new(self.checkpoint()).finish(self, kind);
     }
 
-    pub fn finish(self) -> SyntaxNode<L> {
-        SyntaxNode::new_root(self.builder.finish())
+    /// Records an error at the next token.
+    pub fn error(&mut self, message: impl Into<String>) {
+        let range = match self.peek_token() {
+            Some(info) => TextRange::at(info.start.into(), info.len.into()),
+            None => TextRange::empty(TextSize::of(&*self.text)),
+        };
+        self.errors.push(SyntaxError {
+            range,
+            message: message.into(),
+        });
+    }
+
+    /// Records an error just before the next token, for something that is missing.
+    pub fn error_before(&mut self, message: impl Into<String>) {
+        let offset = match self.peek_token() {
+            Some(info) => info.start.into(),
+            None => TextSize::of(&*self.text),
+        };
+        self.errors.push(SyntaxError {
+            range: TextRange::empty(offset),
+            message: message.into(),
+        });
+    }
+
+    pub fn finish(self) -> (SyntaxNode<L>, Vec<SyntaxError>) {
+        (SyntaxNode::new_root(self.builder.finish()), self.errors)
     }
 
     fn checkpoint(&self) -> Checkpoint {
@@ -129,6 +162,12 @@ impl Marker {
         p.builder.finish_node()
     }
 
+    /// Starts another marker before this one,
+    /// so the node this finishes can be wrapped by another.
+    pub fn precede(&self) -> Marker {
+        Marker::new(self.checkpoint)
+    }
+
     pub fn abandon(mut self) {
         self.finished = true;
     }
@@ -53,25 +53,25 @@ fn impl_pattern(
 ) -> Result<TokenStream, (Span, String)> {
     let module = crate::crate_name();
 
-    let mut tokens = fields
-        .map(|f| pattern_token(&module, f))
-        .collect::<Vec<_>>();
-
-    if let Some(name_attr) = name_attr {
-        for c in name_attr.chars().rev() {
-            tokens.insert(0, quote! { #module::Pattern::Char(#c) });
-        }
-    }
+    let chars = name_attr
+        .iter()
+        .flat_map(|name| name.chars())
+        .map(|c| quote! { #module::Pattern::Char(#c) });
+    let parts = fields.map(|f| pattern_tokens(&module, f));
 
+    // each field can be made of a few patterns, so they are joined when compiled
     Ok(quote! {
         #[automatically_derived]
         impl #module::ConstPattern for #name {
-            const PATTERN: &[#module::Pattern] = &[ #(#tokens),* ];
+            const PATTERN: &[#module::Pattern] = {
+                const PARTS: &[&[#module::Pattern]] = &[&[#(#chars),*], #(#parts),*];
+                &#module::pattern::concat::<{ #module::pattern::len(PARTS) }>(PARTS)
+            };
         }
     })
 }
 
-fn pattern_token(module: &TokenStream, field: &Field) -> TokenStream {
+fn pattern_tokens(module: &TokenStream, field: &Field) -> TokenStream {
     let ty = &field.ty;
-    quote! { <#ty as #module::PatternToken>::TOKEN }
+    quote! { <#ty as #module::PatternToken>::TOKENS }
 }
@@ -64,7 +64,10 @@ test! {
     } expands to {
         #[automatically_derived]
         impl matcher::ConstPattern for Unit {
-            const PATTERN: &[matcher::Pattern] = &[];
+            const PATTERN: &[matcher::Pattern] = {
+                const PARTS: &[&[matcher::Pattern]] = &[&[]];
+                &matcher::pattern::concat::<{ matcher::pattern::len(PARTS) }>(PARTS)
+            };
         }
     }
 }
@@ -76,7 +79,10 @@ test! {
     } expands to {
         #[automatically_derived]
         impl matcher::ConstPattern for Vis {
-            const PATTERN: &[matcher::Pattern] = &[];
+            const PATTERN: &[matcher::Pattern] = {
+                const PARTS: &[&[matcher::Pattern]] = &[&[]];
+                &matcher::pattern::concat::<{ matcher::pattern::len(PARTS) }>(PARTS)
+            };
         }
     }
 }
@@ -87,10 +93,14 @@ test! {
     } expands to {
         #[automatically_derived]
         impl matcher::ConstPattern for Tuple {
-            const PATTERN: &[matcher::Pattern] = &[
-                <Condition as matcher::PatternToken>::TOKEN,
-                <Label as matcher::PatternToken>::TOKEN,
-            ];
+            const PATTERN: &[matcher::Pattern] = {
+                const PARTS: &[&[matcher::Pattern]] = &[
+                    &[],
+                    <Condition as matcher::PatternToken>::TOKENS,
+                    <Label as matcher::PatternToken>::TOKENS,
+                ];
+                &matcher::pattern::concat::<{ matcher::pattern::len(PARTS) }>(PARTS)
+            };
         }
     }
 }
@@ -106,12 +116,16 @@ test! {
     } expands to {
         #[automatically_derived]
         impl matcher::ConstPattern for Named {
-            const PATTERN: &[matcher::Pattern] = &[
-                <Condition as matcher::PatternToken>::TOKEN,
-                <Register<D> as matcher::PatternToken>::TOKEN,
-                <Register<N> as matcher::PatternToken>::TOKEN,
-                <Number<12> as matcher::PatternToken>::TOKEN,
-            ];
+            const PATTERN: &[matcher::Pattern] = {
+                const PARTS: &[&[matcher::Pattern]] = &[
+                    &[],
+                    <Condition as matcher::PatternToken>::TOKENS,
+                    <Register<D> as matcher::PatternToken>::TOKENS,
+                    <Register<N> as matcher::PatternToken>::TOKENS,
+                    <Number<12> as matcher::PatternToken>::TOKENS,
+                ];
+                &matcher::pattern::concat::<{ matcher::pattern::len(PARTS) }>(PARTS)
+            };
         }
     }
 }
@@ -21,7 +21,7 @@ pub(crate) fn crate_name() -> proc_macro2::TokenStream {
 }
 
 #[proc_macro_error]
-#[proc_macro_derive(Structured, attributes(name))]
+#[proc_macro_derive(Structured, attributes(name, check))]
 pub fn derive_structure(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -36,15 +36,34 @@ fn derive(input: DeriveInput) -> proc_macro2::TokenStream {
         panic!("Only supports structs")
     };
 
+    // `#[check(path)]` runs `path(&self)` on what was parsed
+    let check = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("check"))
+        .map(|attr| {
+            attr.parse_args::<syn::Path>()
+                .expect("check takes the path to a function")
+        })
+        .map(|path| quote!(#path(&parsed)?;));
+
     let fields = data_struct.fields;
-    let members = fields.members();
+    let members = fields.members().collect::<Vec<_>>();
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
     quote! {
         #[automatically_derived]
         impl #impl_generics #module::structured::Structured for #name #ty_generics #where_clause {
-            fn parse(buffer: &mut #module::structured::Buffer) -> Option<Self> {
-                Some(Self { #(#members: buffer.parse()?),* })
+            fn parse(
+                buffer: &mut #module::structured::Buffer,
+            ) -> Result<Self, #module::structured::Error> {
+                let parsed = Self { #(#members: buffer.parse()?),* };
+                #check
+                Ok(parsed)
+            }
+
+            fn unparse(&self, cir: &mut Vec<#module::CIR>) {
+                #(#module::structured::Structured::unparse(&self.#members, cir);)*
             }
         }
     }
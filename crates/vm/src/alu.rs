@@ -0,0 +1,54 @@
+//! Arithmetic helpers, following the pseudocode in the ARM Architecture Reference Manual.
+
+use cir::Shift;
+
+/// Adds `x`, `y` and `carry`, returning the result and the carry and overflow flags.
+pub(crate) fn add_with_carry(x: u32, y: u32, carry: bool) -> (u32, bool, bool) {
+    let unsigned_sum = x as u64 + y as u64 + carry as u64;
+    let signed_sum = x as i32 as i64 + y as i32 as i64 + carry as i64;
+    let result = unsigned_sum as u32;
+    let carry = result as u64 != unsigned_sum;
+    let overflow = result as i32 as i64 != signed_sum;
+    (result, carry, overflow)
+}
+
+/// Immediate shifts encode some amounts differently,
+/// `LSR #32` and `ASR #32` as `#0`, and `RRX` as `ROR #0`.
+pub(crate) fn decode_imm_shift(shift: Shift, imm5: u32) -> (Shift, u32) {
+    match (shift, imm5) {
+        (Shift::LSR | Shift::ASR, 0) => (shift, 32),
+        (Shift::ROR | Shift::RRX, 0) => (Shift::RRX, 1),
+        (Shift::RRX, _) => (Shift::RRX, 1),
+        _ => (shift, imm5),
+    }
+}
+
+/// Shifts `value` by `amount`, returning the result and the carry out.
+pub(crate) fn shift_c(value: u32, shift: Shift, amount: u32, carry: bool) -> (u32, bool) {
+    if amount == 0 {
+        return (value, carry);
+    }
+
+    match shift {
+        Shift::LSL => match amount {
+            1..=31 => (value << amount, value & (1 << (32 - amount)) != 0),
+            32 => (0, value & 1 != 0),
+            _ => (0, false),
+        },
+        Shift::LSR => match amount {
+            1..=31 => (value >> amount, value & (1 << (amount - 1)) != 0),
+            32 => (0, value & (1 << 31) != 0),
+            _ => (0, false),
+        },
+        Shift::ASR => {
+            let amount = amount.min(32);
+            let result = ((value as i32 as i64) >> amount) as u32;
+            (result, (value as i32 as i64 >> (amount - 1)) & 1 != 0)
+        }
+        Shift::ROR => {
+            let result = value.rotate_right(amount % 32);
+            (result, result & (1 << 31) != 0)
+        }
+        Shift::RRX => ((carry as u32) << 31 | value >> 1, value & 1 != 0),
+    }
+}
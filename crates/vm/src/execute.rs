@@ -0,0 +1,520 @@
+use cir::structured::{Label, ModImm, PsrFields, Sign};
+use instructions::{
+    BlockTransfer, DataProcessing, Displacement, Indexing, Instruction, LoadStore, LongMultiply,
+    Opcode, Operand, Size,
+};
+
+use crate::{alu, Error, Flags, Machine, Step, LR, PC};
+
+/// The mode bits of the CPSR, programs always run in user mode.
+const USER_MODE: u32 = 0b10000;
+/// The `f` field of a status register, which holds the flags.
+const FLAGS_FIELD: u32 = 0b1000;
+
+impl Machine {
+    pub(crate) fn execute(&mut self, inst: Instruction) -> Result<Step, Error> {
+        use instructions as i;
+
+        match inst {
+            Instruction::Adr(i::Adr(_, rd, label)) => {
+                let base = self.read(PC) & !0b11;
+                self.write(rd.0, offset(base, label));
+            }
+            Instruction::AndImm(inst) => self.data_processing(inst.into()),
+            Instruction::AndReg(inst) => self.data_processing(inst.into()),
+            Instruction::AndRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::EorImm(inst) => self.data_processing(inst.into()),
+            Instruction::EorReg(inst) => self.data_processing(inst.into()),
+            Instruction::EorRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::SubImm(inst) => self.data_processing(inst.into()),
+            Instruction::SubReg(inst) => self.data_processing(inst.into()),
+            Instruction::SubRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::RsbImm(inst) => self.data_processing(inst.into()),
+            Instruction::RsbReg(inst) => self.data_processing(inst.into()),
+            Instruction::RsbRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::AddImm(inst) => self.data_processing(inst.into()),
+            Instruction::AddReg(inst) => self.data_processing(inst.into()),
+            Instruction::AddRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::AdcImm(inst) => self.data_processing(inst.into()),
+            Instruction::AdcReg(inst) => self.data_processing(inst.into()),
+            Instruction::AdcRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::SbcImm(inst) => self.data_processing(inst.into()),
+            Instruction::SbcReg(inst) => self.data_processing(inst.into()),
+            Instruction::SbcRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::RscImm(inst) => self.data_processing(inst.into()),
+            Instruction::RscReg(inst) => self.data_processing(inst.into()),
+            Instruction::RscRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::TstImm(inst) => self.data_processing(inst.into()),
+            Instruction::TstReg(inst) => self.data_processing(inst.into()),
+            Instruction::TstRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::TeqImm(inst) => self.data_processing(inst.into()),
+            Instruction::TeqReg(inst) => self.data_processing(inst.into()),
+            Instruction::TeqRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::CmpImm(inst) => self.data_processing(inst.into()),
+            Instruction::CmpReg(inst) => self.data_processing(inst.into()),
+            Instruction::CmpRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::CmnImm(inst) => self.data_processing(inst.into()),
+            Instruction::CmnReg(inst) => self.data_processing(inst.into()),
+            Instruction::CmnRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::OrrImm(inst) => self.data_processing(inst.into()),
+            Instruction::OrrReg(inst) => self.data_processing(inst.into()),
+            Instruction::OrrRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::MovImm(inst) => self.data_processing(inst.into()),
+            Instruction::MovReg(inst) => self.data_processing(inst.into()),
+            Instruction::MovRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::BicImm(inst) => self.data_processing(inst.into()),
+            Instruction::BicReg(inst) => self.data_processing(inst.into()),
+            Instruction::BicRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::MvnImm(inst) => self.data_processing(inst.into()),
+            Instruction::MvnReg(inst) => self.data_processing(inst.into()),
+            Instruction::MvnRegShiftReg(inst) => self.data_processing(inst.into()),
+            Instruction::Mul(i::Mul(_, s, rd, rn, rm)) => {
+                let result = self.read(rn.0).wrapping_mul(self.read(rm.0));
+                self.write(rd.0, result);
+                if s.0 {
+                    self.set_nz(result & (1 << 31) != 0, result == 0);
+                }
+            }
+            Instruction::Mla(i::Mla(_, s, rd, rn, rm, ra)) => {
+                let product = self.read(rn.0).wrapping_mul(self.read(rm.0));
+                let result = product.wrapping_add(self.read(ra.0));
+                self.write(rd.0, result);
+                if s.0 {
+                    self.set_nz(result & (1 << 31) != 0, result == 0);
+                }
+            }
+            Instruction::Mls(i::Mls(_, rd, rn, rm, ra)) => {
+                let product = self.read(rn.0).wrapping_mul(self.read(rm.0));
+                self.write(rd.0, self.read(ra.0).wrapping_sub(product));
+            }
+            Instruction::Umull(inst) => self.long_multiply(inst.into(), false, false),
+            Instruction::Umlal(inst) => self.long_multiply(inst.into(), false, true),
+            Instruction::Smull(inst) => self.long_multiply(inst.into(), true, false),
+            Instruction::Smlal(inst) => self.long_multiply(inst.into(), true, true),
+            Instruction::Qadd(i::Qadd(_, rd, rm, rn)) => {
+                let (a, b) = (self.read(rm.0) as i32, self.read(rn.0) as i32);
+                let result = self.saturate(a.checked_add(b), a.saturating_add(b));
+                self.write(rd.0, result);
+            }
+            Instruction::Qsub(i::Qsub(_, rd, rm, rn)) => {
+                let (a, b) = (self.read(rm.0) as i32, self.read(rn.0) as i32);
+                let result = self.saturate(a.checked_sub(b), a.saturating_sub(b));
+                self.write(rd.0, result);
+            }
+            Instruction::Clz(i::Clz(_, rd, rm)) => {
+                self.write(rd.0, self.read(rm.0).leading_zeros())
+            }
+            Instruction::Rev(i::Rev(_, rd, rm)) => self.write(rd.0, self.read(rm.0).swap_bytes()),
+            Instruction::Rev16(i::Rev16(_, rd, rm)) => {
+                let value = self.read(rm.0);
+                self.write(rd.0, (value & 0xFF00FF00) >> 8 | (value & 0x00FF00FF) << 8);
+            }
+            Instruction::Sbfx(i::Sbfx(_, rd, rn, field)) => {
+                let (lsb, width) = (field.0, field.1);
+                // move the field to the top, then back down keeping its sign
+                let top = self.read(rn.0) >> lsb << (32 - width);
+                self.write(rd.0, ((top as i32) >> (32 - width)) as u32);
+            }
+            Instruction::Ubfx(i::Ubfx(_, rd, rn, field)) => {
+                let (lsb, width) = (field.0, field.1);
+                self.write(rd.0, self.read(rn.0) >> lsb & low_bits(width))
+            }
+            Instruction::Bfc(i::Bfc(_, rd, field)) => {
+                let (lsb, width) = (field.0, field.1);
+                self.write(rd.0, self.read(rd.0) & !(low_bits(width) << lsb))
+            }
+            Instruction::Bfi(i::Bfi(_, rd, rn, field)) => {
+                let (lsb, width) = (field.0, field.1);
+                let mask = low_bits(width) << lsb;
+                let inserted = self.read(rn.0) << lsb & mask;
+                self.write(rd.0, self.read(rd.0) & !mask | inserted);
+            }
+            Instruction::B(i::B(_, label)) => {
+                let target = offset(self.read(PC), label);
+                self.write(PC, target);
+            }
+            Instruction::Bl(i::Bl(_, label)) => {
+                let target = offset(self.read(PC), label);
+                self.link();
+                self.write(PC, target);
+            }
+            Instruction::Bx(i::Bx(_, rm)) => self.exchange(self.read(rm.0))?,
+            Instruction::BlxReg(i::BlxReg(_, rm)) => {
+                let target = self.read(rm.0);
+                self.link();
+                self.exchange(target)?;
+            }
+            Instruction::BlxImm(i::BlxImm(_, label)) => {
+                return Err(Error::Thumb {
+                    address: offset(self.read(PC), label),
+                })
+            }
+            Instruction::Mrs(i::Mrs(_, rd, psr)) => {
+                if psr.0 {
+                    return Err(Error::NoSpsr);
+                }
+                self.write(rd.0, self.flags.bits() | USER_MODE);
+            }
+            Instruction::MsrReg(i::MsrReg(_, fields, rn)) => {
+                self.write_psr(fields, self.read(rn.0))?
+            }
+            Instruction::MsrImm(i::MsrImm(_, fields, imm)) => self.write_psr(fields, imm.0)?,
+            Instruction::Nop(_) => (),
+            Instruction::Svc(i::Svc(_, number)) => return Ok(Step::Syscall(number.0)),
+            Instruction::Bkpt(i::Bkpt(_, number)) => return Ok(Step::Breakpoint(number.0 as u16)),
+            Instruction::Hlt(_) => return Ok(Step::Halt),
+            Instruction::LdrImmLit(i::LdrImmLit(_, rt, label)) => {
+                let base = self.read(PC) & !0b11;
+                let value = self.memory.read_word(offset(base, label))?;
+                self.write(rt.0, value);
+            }
+            Instruction::LdrImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::StrImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::StrRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrbImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrbImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrbImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrbRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrbRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrbRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrbImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::StrbImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrbImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrbRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::StrbRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrbRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrhImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrhImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrhImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrhRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrhRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrhRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrhImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::StrhImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrhImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrhRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::StrhRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrhRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrsbImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrsbImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrsbImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrsbRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrsbRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrsbRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrshImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrshImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrshImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrshRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrshRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrshRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrdImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrdImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrdImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrdRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::LdrdRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::LdrdRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrdImmOffset(inst) => self.load_store(inst.into())?,
+            Instruction::StrdImmPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrdImmPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrdRegOffset(inst) => self.load_store(inst.into())?,
+            Instruction::StrdRegPreIndex(inst) => self.load_store(inst.into())?,
+            Instruction::StrdRegPostIndex(inst) => self.load_store(inst.into())?,
+            Instruction::Push(inst) => self.block_transfer(inst.into())?,
+            Instruction::Pop(inst) => self.block_transfer(inst.into())?,
+            Instruction::Ldm(inst) => self.block_transfer(inst.into())?,
+            Instruction::Ldmib(inst) => self.block_transfer(inst.into())?,
+            Instruction::Ldmda(inst) => self.block_transfer(inst.into())?,
+            Instruction::Ldmdb(inst) => self.block_transfer(inst.into())?,
+            Instruction::Stm(inst) => self.block_transfer(inst.into())?,
+            Instruction::Stmib(inst) => self.block_transfer(inst.into())?,
+            Instruction::Stmda(inst) => self.block_transfer(inst.into())?,
+            Instruction::Stmdb(inst) => self.block_transfer(inst.into())?,
+            Instruction::Swp(i::Swp(_, rt, rt2, rn)) => {
+                let address = self.read(rn.0);
+                let loaded = self.memory.read_word(address)?;
+                self.memory.write_word(address, self.read(rt2.0))?;
+                self.write(rt.0, loaded);
+            }
+            Instruction::Swpb(i::Swpb(_, rt, rt2, rn)) => {
+                let address = self.read(rn.0);
+                let loaded = self.memory.read_byte(address)?;
+                self.memory.write_byte(address, self.read(rt2.0) as u8)?;
+                self.write(rt.0, loaded.into());
+            }
+        }
+
+        Ok(Step::Continue)
+    }
+
+    fn data_processing(&mut self, inst: DataProcessing) {
+        let DataProcessing {
+            opcode,
+            set_flags,
+            rd,
+            rn,
+            operand,
+        } = inst;
+        let c = self.flags.c;
+
+        let (operand, shift_carry) = match operand {
+            Operand::Imm(ModImm(imm)) => {
+                // a rotated immediate carries out its top bit
+                let rotated = ModImm::split(imm).is_some_and(|(rotation, _)| rotation != 0);
+                (imm, if rotated { imm & (1 << 31) != 0 } else { c })
+            }
+            Operand::Reg(rm, stype, imm5) => {
+                let (shift, amount) = alu::decode_imm_shift(stype.0, imm5.0);
+                alu::shift_c(self.read(rm.0), shift, amount, c)
+            }
+            Operand::RegShiftReg(rm, stype, rs) => {
+                let amount = self.read(rs.0) & 0xFF;
+                alu::shift_c(self.read(rm.0), stype.0, amount, c)
+            }
+        };
+        let n = rn.map_or(0, |rn| self.read(rn.0));
+
+        // logical operations keep the overflow flag
+        let logical = |result| (result, shift_carry, self.flags.v);
+        let (result, carry, overflow) = match opcode {
+            Opcode::And | Opcode::Tst => logical(n & operand),
+            Opcode::Eor | Opcode::Teq => logical(n ^ operand),
+            Opcode::Orr => logical(n | operand),
+            Opcode::Bic => logical(n & !operand),
+            Opcode::Mov => logical(operand),
+            Opcode::Mvn => logical(!operand),
+            Opcode::Sub | Opcode::Cmp => alu::add_with_carry(n, !operand, true),
+            Opcode::Rsb => alu::add_with_carry(!n, operand, true),
+            Opcode::Add | Opcode::Cmn => alu::add_with_carry(n, operand, false),
+            Opcode::Adc => alu::add_with_carry(n, operand, c),
+            Opcode::Sbc => alu::add_with_carry(n, !operand, c),
+            Opcode::Rsc => alu::add_with_carry(!n, operand, c),
+        };
+
+        // tests only update the flags
+        if let Some(rd) = rd {
+            self.write(rd.0, result);
+        }
+        if set_flags {
+            self.set_flags(result, carry, overflow);
+        }
+    }
+
+    fn load_store(&mut self, inst: LoadStore) -> Result<(), Error> {
+        let LoadStore {
+            load,
+            size,
+            indexing,
+            rt,
+            rn,
+            sign,
+            offset,
+        } = inst;
+        let offset = match offset {
+            Displacement::Imm(imm) => imm,
+            Displacement::Reg(rm, stype, imm5) => {
+                let (shift, amount) = alu::decode_imm_shift(stype.0, imm5.0);
+                alu::shift_c(self.read(rm.0), shift, amount, self.flags.c).0
+            }
+        };
+        let base = self.read(rn.0);
+        let offset_address = index(base, sign, offset);
+        let address = match indexing {
+            Indexing::Offset | Indexing::PreIndex => offset_address,
+            Indexing::PostIndex => base,
+        };
+
+        if load {
+            let memory = &self.memory;
+            let value = match size {
+                Size::Word | Size::Double => memory.read_word(address)?,
+                Size::Byte => memory.read_byte(address)? as u32,
+                Size::Half => memory.read_half(address)? as u32,
+                Size::SignedByte => memory.read_byte(address)? as i8 as u32,
+                Size::SignedHalf => memory.read_half(address)? as i16 as u32,
+            };
+            let second = match size {
+                Size::Double => Some(memory.read_word(address.wrapping_add(4))?),
+                _ => None,
+            };
+            if indexing != Indexing::Offset {
+                self.write(rn.0, offset_address);
+            }
+            self.write(rt.0, value);
+            if let Some(second) = second {
+                self.write(rt.0 + 1, second);
+            }
+        } else {
+            let value = self.read(rt.0);
+            let second = match size {
+                Size::Double => self.read(rt.0 + 1),
+                _ => 0,
+            };
+            let memory = &mut self.memory;
+            match size {
+                Size::Word => memory.write_word(address, value)?,
+                Size::Byte | Size::SignedByte => memory.write_byte(address, value as u8)?,
+                Size::Half | Size::SignedHalf => memory.write_half(address, value as u16)?,
+                Size::Double => {
+                    memory.write_word(address, value)?;
+                    memory.write_word(address.wrapping_add(4), second)?;
+                }
+            }
+            if indexing != Indexing::Offset {
+                self.write(rn.0, offset_address);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the address of the next instruction to the LR.
+    fn link(&mut self) {
+        let next = self.read(PC).wrapping_sub(4);
+        self.write(LR, next);
+    }
+
+    /// Branches to `target`, which is Thumb code when bit 0 is set.
+    fn exchange(&mut self, target: u32) -> Result<(), Error> {
+        if target & 1 != 0 {
+            return Err(Error::Thumb {
+                address: target & !1,
+            });
+        }
+        self.write(PC, target);
+        Ok(())
+    }
+
+    /// Writes the fields of `value` to a status register,
+    /// only the flags can be changed in user mode so the other fields are ignored.
+    fn write_psr(&mut self, PsrFields(spsr, mask): PsrFields, value: u32) -> Result<(), Error> {
+        if spsr {
+            return Err(Error::NoSpsr);
+        }
+        if mask & FLAGS_FIELD != 0 {
+            self.flags = Flags::from_bits(value);
+        }
+        Ok(())
+    }
+
+    fn block_transfer(&mut self, inst: BlockTransfer) -> Result<(), Error> {
+        let BlockTransfer {
+            load,
+            before,
+            increment,
+            rn,
+            writeback,
+            registers,
+        } = inst;
+        let base = self.read(rn.0);
+        let size = registers.count_ones() * 4;
+        // the lowest register is always at the lowest address
+        let (lowest, end) = if increment {
+            (base, base.wrapping_add(size))
+        } else {
+            (base.wrapping_sub(size), base.wrapping_sub(size))
+        };
+        let mut address = if before == increment {
+            lowest.wrapping_add(4)
+        } else {
+            lowest
+        };
+
+        // a loaded base replaces the written back one
+        if load && writeback {
+            self.write(rn.0, end);
+        }
+        for r in (0..16).filter(|r| registers & (1 << r) != 0) {
+            if load {
+                let value = self.memory.read_word(address)?;
+                self.write(r, value);
+            } else {
+                self.memory.write_word(address, self.read(r))?;
+            }
+            address = address.wrapping_add(4);
+        }
+        if !load && writeback {
+            self.write(rn.0, end);
+        }
+        Ok(())
+    }
+
+    /// Writes the 64-bit product of `rn` and `rm` to `RdHi:RdLo`,
+    /// adding the value already there when accumulating.
+    fn long_multiply(&mut self, inst: LongMultiply, signed: bool, accumulate: bool) {
+        let LongMultiply {
+            set_flags,
+            rdlo,
+            rdhi,
+            rn,
+            rm,
+        } = inst;
+        let (n, m) = (self.read(rn.0), self.read(rm.0));
+        let product = if signed {
+            (n as i32 as i64).wrapping_mul(m as i32 as i64) as u64
+        } else {
+            (n as u64) * (m as u64)
+        };
+        let result = if accumulate {
+            let value = (self.read(rdhi.0) as u64) << 32 | self.read(rdlo.0) as u64;
+            product.wrapping_add(value)
+        } else {
+            product
+        };
+
+        self.write(rdlo.0, result as u32);
+        self.write(rdhi.0, (result >> 32) as u32);
+        if set_flags {
+            self.set_nz(result & (1 << 63) != 0, result == 0);
+        }
+    }
+
+    fn set_flags(&mut self, result: u32, carry: bool, overflow: bool) {
+        self.set_nz(result & (1 << 31) != 0, result == 0);
+        self.flags.c = carry;
+        self.flags.v = overflow;
+    }
+
+    /// The result of a saturating instruction, which sets the `Q` flag when it saturates.
+    fn saturate(&mut self, exact: Option<i32>, saturated: i32) -> u32 {
+        if exact.is_none() {
+            self.flags.q = true;
+        }
+        saturated as u32
+    }
+
+    /// Multiplies only update the negative and zero flags.
+    fn set_nz(&mut self, negative: bool, zero: bool) {
+        self.flags.n = negative;
+        self.flags.z = zero;
+    }
+}
+
+/// The lowest `width` bits set, none when it is `0`.
+fn low_bits(width: u32) -> u32 {
+    u32::MAX.checked_shr(32 - width).unwrap_or(0)
+}
+
+/// Adds `offset` to, or subtracts it from, `base`.
+fn index(base: u32, Sign(sign): Sign, offset: u32) -> u32 {
+    match sign {
+        cir::Sign::Plus => base.wrapping_add(offset),
+        cir::Sign::Minus => base.wrapping_sub(offset),
+    }
+}
+
+/// Applies the offset of a [`Label`] to `base`.
+fn offset(base: u32, Label(offset, negative): Label) -> u32 {
+    if negative {
+        base.wrapping_sub(offset)
+    } else {
+        base.wrapping_add(offset)
+    }
+}
@@ -0,0 +1,275 @@
+//! A virtual machine that executes assembled HAND programs.
+
+#[cfg(test)]
+mod tests;
+
+mod alu;
+mod execute;
+mod memory;
+pub mod syscall;
+
+use dec::{Decodable, Word};
+use instructions::Instruction;
+
+pub use memory::Memory;
+pub use syscall::Syscalls;
+
+/// Stack pointer
+pub const SP: u32 = 13;
+/// Link register
+pub const LR: u32 = 14;
+/// Program counter
+pub const PC: u32 = 15;
+
+/// The condition flags of the CPSR.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Flags {
+    /// Negative
+    pub n: bool,
+    /// Zero
+    pub z: bool,
+    /// Carry
+    pub c: bool,
+    /// Overflow
+    pub v: bool,
+    /// Saturation, which stays set until it is cleared with `MSR`
+    pub q: bool,
+}
+
+impl Flags {
+    /// The flags as the top five bits of the CPSR.
+    pub fn bits(&self) -> u32 {
+        let Self { n, z, c, v, q } = *self;
+        u32::from(n) << 31
+            | u32::from(z) << 30
+            | u32::from(c) << 29
+            | u32::from(v) << 28
+            | u32::from(q) << 27
+    }
+
+    /// The flags from the top five bits of `psr`.
+    pub fn from_bits(psr: u32) -> Self {
+        Self {
+            n: psr & (1 << 31) != 0,
+            z: psr & (1 << 30) != 0,
+            c: psr & (1 << 29) != 0,
+            v: psr & (1 << 28) != 0,
+            q: psr & (1 << 27) != 0,
+        }
+    }
+
+    /// Should an instruction with the condition `cond` be executed.
+    pub fn passes(&self, cond: cir::Condition) -> bool {
+        use cir::Condition::*;
+
+        let Self { n, z, c, v, .. } = *self;
+        match cond {
+            EQ => z,
+            NE => !z,
+            CS => c,
+            CC => !c,
+            MI => n,
+            PL => !n,
+            VS => v,
+            VC => !v,
+            HI => c && !z,
+            LS => !c || z,
+            GE => n == v,
+            LT => n != v,
+            GT => !z && n == v,
+            LE => z || n != v,
+            AL => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The word at `address` isn't an instruction.
+    UnknownInstruction { address: u32, word: u32 },
+    /// `address` is outside of memory.
+    OutOfBounds { address: u32 },
+    /// A branch to Thumb code at `address`, which can't be executed.
+    Thumb { address: u32 },
+    /// `SVC #number` isn't a known call.
+    UnknownSyscall { number: u32 },
+    /// The SPSR is accessed, but programs run in user mode which doesn't have one.
+    NoSpsr,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownInstruction { address, word } => {
+                write!(f, "unknown instruction {word:#010X} at {address:#010X}")
+            }
+            Error::OutOfBounds { address } => {
+                write!(f, "memory access out of bounds at {address:#010X}")
+            }
+            Error::Thumb { address } => {
+                write!(f, "can't switch to Thumb code at {address:#010X}")
+            }
+            Error::UnknownSyscall { number } => write!(f, "unknown syscall `SVC #{number}`"),
+            Error::NoSpsr => write!(f, "there is no SPSR in user mode"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// What happened after executing an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Continue,
+    Halt,
+    /// An `SVC` was executed, asking for the call with this number.
+    Syscall(u32),
+    /// A `BKPT` was executed, holding this number.
+    Breakpoint(u16),
+}
+
+/// Why the machine stopped running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// A `HLT` instruction was executed.
+    Halted,
+    /// The program exited with a code.
+    Exited(u32),
+    /// A `BKPT` instruction was executed, running again continues after it.
+    Breakpoint(u16),
+    /// The step limit was reached before halting.
+    StepLimit,
+}
+
+pub struct Machine {
+    registers: [u32; 16],
+    flags: Flags,
+    memory: Memory,
+    steps: usize,
+    /// Has the current instruction written to the PC.
+    branched: bool,
+}
+
+impl Machine {
+    /// Creates a machine with `memory_size` bytes of zeroed memory.
+    pub fn new(memory_size: usize) -> Self {
+        let mut machine = Self {
+            registers: [0; 16],
+            flags: Flags::default(),
+            memory: Memory::new(memory_size),
+            steps: 0,
+            branched: false,
+        };
+        machine.reset();
+        machine
+    }
+
+    /// Loads a program, as produced by `asm::assemble`, at address `0`
+    /// and resets the machine to start executing it.
+    pub fn load(&mut self, program: &[u8]) -> Result<(), Error> {
+        self.memory.write(0, program)?;
+        self.reset();
+        Ok(())
+    }
+
+    /// Clears the registers and flags, the stack starts at the top of memory.
+    pub fn reset(&mut self) {
+        self.registers = [0; 16];
+        self.registers[SP as usize] = self.memory.len() as u32;
+        self.flags = Flags::default();
+        self.steps = 0;
+    }
+
+    /// Runs until a `HLT` is executed, or `limit` instructions have been executed.
+    ///
+    /// Output is discarded and there is no input, see [`Machine::run_with`].
+    pub fn run(&mut self, limit: usize) -> Result<Exit, Error> {
+        self.run_with(limit, &mut ())
+    }
+
+    /// Runs like [`Machine::run`], with `syscalls` handling input and output.
+    pub fn run_with(&mut self, limit: usize, syscalls: &mut impl Syscalls) -> Result<Exit, Error> {
+        for _ in 0..limit {
+            match self.step()? {
+                Step::Continue => (),
+                Step::Halt => return Ok(Exit::Halted),
+                Step::Breakpoint(number) => return Ok(Exit::Breakpoint(number)),
+                Step::Syscall(number) => {
+                    if let Some(exit) = self.syscall(number, syscalls)? {
+                        return Ok(exit);
+                    }
+                }
+            }
+        }
+        Ok(Exit::StepLimit)
+    }
+
+    /// Fetches, decodes and executes a single instruction.
+    pub fn step(&mut self) -> Result<Step, Error> {
+        let address = self.registers[PC as usize];
+        let word = self.memory.read_word(address)?;
+        let inst = Instruction::decode(Word::base(word))
+            .ok_or(Error::UnknownInstruction { address, word })?;
+
+        self.steps += 1;
+        self.branched = false;
+
+        let step = if self.flags.passes(inst.condition()) {
+            self.execute(inst)?
+        } else {
+            Step::Continue
+        };
+
+        if !self.branched {
+            self.registers[PC as usize] = address.wrapping_add(4);
+        }
+
+        Ok(step)
+    }
+
+    /// The number of instructions executed since the last reset.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn registers(&self) -> &[u32; 16] {
+        &self.registers
+    }
+
+    pub fn register(&self, r: u32) -> u32 {
+        self.registers[r as usize]
+    }
+
+    pub fn set_register(&mut self, r: u32, value: u32) {
+        self.registers[r as usize] = value;
+    }
+
+    pub fn flags(&self) -> Flags {
+        self.flags
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.memory
+    }
+
+    /// Reads a register as an instruction sees it, the PC is 8 bytes ahead.
+    fn read(&self, r: u32) -> u32 {
+        if r == PC {
+            self.registers[PC as usize].wrapping_add(8)
+        } else {
+            self.registers[r as usize]
+        }
+    }
+
+    /// Writes a register, writing the PC causes a branch.
+    fn write(&mut self, r: u32, value: u32) {
+        if r == PC {
+            self.branched = true;
+        }
+        self.registers[r as usize] = value;
+    }
+}
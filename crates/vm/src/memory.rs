@@ -0,0 +1,81 @@
+use crate::Error;
+
+/// Byte-addressable, little-endian memory.
+pub struct Memory {
+    bytes: Vec<u8>,
+}
+
+impl Memory {
+    pub fn new(size: usize) -> Self {
+        Self {
+            bytes: vec![0; size],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn read_byte(&self, address: u32) -> Result<u8, Error> {
+        self.slice(address, 1).map(|bytes| bytes[0])
+    }
+
+    pub fn write_byte(&mut self, address: u32, value: u8) -> Result<(), Error> {
+        self.slice_mut(address, 1)?[0] = value;
+        Ok(())
+    }
+
+    pub fn read_half(&self, address: u32) -> Result<u16, Error> {
+        let bytes = self.slice(address, 2)?;
+        Ok(u16::from_le_bytes(
+            bytes.try_into().expect("slice is 2 bytes"),
+        ))
+    }
+
+    pub fn write_half(&mut self, address: u32, value: u16) -> Result<(), Error> {
+        self.slice_mut(address, 2)?
+            .copy_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    pub fn read_word(&self, address: u32) -> Result<u32, Error> {
+        let bytes = self.slice(address, 4)?;
+        Ok(u32::from_le_bytes(
+            bytes.try_into().expect("slice is 4 bytes"),
+        ))
+    }
+
+    pub fn write_word(&mut self, address: u32, value: u32) -> Result<(), Error> {
+        self.slice_mut(address, 4)?
+            .copy_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    /// Copies `bytes` into memory starting at `address`.
+    pub fn write(&mut self, address: u32, bytes: &[u8]) -> Result<(), Error> {
+        self.slice_mut(address, bytes.len())?.copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn slice(&self, address: u32, len: usize) -> Result<&[u8], Error> {
+        let start = address as usize;
+        self.bytes
+            .get(start..start + len)
+            .ok_or(Error::OutOfBounds { address })
+    }
+
+    fn slice_mut(&mut self, address: u32, len: usize) -> Result<&mut [u8], Error> {
+        let start = address as usize;
+        self.bytes
+            .get_mut(start..start + len)
+            .ok_or(Error::OutOfBounds { address })
+    }
+}
@@ -0,0 +1,112 @@
+//! The services a program asks for with `SVC`.
+//!
+//! HAND programs pass arguments and get results in `r0`,
+//! the number in the instruction picks the call:
+//!
+//! | Call      | Name         | Does                                                       |
+//! |-----------|--------------|------------------------------------------------------------|
+//! | `SVC #0`  | print char   | prints the character in the low byte of `r0`               |
+//! | `SVC #1`  | print int    | prints `r0` as a signed decimal                            |
+//! | `SVC #2`  | print string | prints the string `r0` points to, up to its `0` byte       |
+//! | `SVC #3`  | read char    | reads a character into `r0`, `-1` at the end of the input  |
+//! | `SVC #4`  | exit         | stops the program, with `r0` as its exit code              |
+//!
+//! Exiting is handled by the [`Machine`], the rest are given to a [`Syscalls`]
+//! so whatever embeds it decides where output goes and input comes from.
+
+use std::io::{Read, Write};
+
+use crate::{Error, Exit, Machine};
+
+pub const PRINT_CHAR: u32 = 0;
+pub const PRINT_INT: u32 = 1;
+pub const PRINT_STRING: u32 = 2;
+pub const READ_CHAR: u32 = 3;
+pub const EXIT: u32 = 4;
+
+/// Handles the input and output of a program.
+///
+/// Only characters have to be handled, the rest are printed with [`Syscalls::print_char`].
+pub trait Syscalls {
+    /// `SVC #0`
+    fn print_char(&mut self, c: u8);
+
+    /// `SVC #3`, `None` at the end of the input.
+    fn read_char(&mut self) -> Option<u8>;
+
+    /// `SVC #1`
+    fn print_int(&mut self, value: i32) {
+        value.to_string().bytes().for_each(|c| self.print_char(c));
+    }
+
+    /// `SVC #2`, without the `0` byte at its end.
+    fn print_string(&mut self, string: &[u8]) {
+        string.iter().for_each(|c| self.print_char(*c));
+    }
+}
+
+/// Discards the output, and has no input.
+impl Syscalls for () {
+    fn print_char(&mut self, _: u8) {}
+
+    fn read_char(&mut self) -> Option<u8> {
+        None
+    }
+}
+
+/// Prints to the standard output, and reads from the standard input.
+pub struct Stdio;
+
+impl Syscalls for Stdio {
+    fn print_char(&mut self, c: u8) {
+        // a program can't do anything about failed output
+        let _ = std::io::stdout().write_all(&[c]);
+    }
+
+    fn read_char(&mut self) -> Option<u8> {
+        let mut c = [0];
+        std::io::stdout().flush().ok()?;
+        match std::io::stdin().read(&mut c) {
+            Ok(1) => Some(c[0]),
+            _ => None,
+        }
+    }
+
+    fn print_string(&mut self, string: &[u8]) {
+        let _ = std::io::stdout().write_all(string);
+    }
+}
+
+impl Machine {
+    /// Makes the call `number`, returning how the program exited if it did.
+    pub fn syscall(
+        &mut self,
+        number: u32,
+        syscalls: &mut impl Syscalls,
+    ) -> Result<Option<Exit>, Error> {
+        let r0 = self.register(0);
+        match number {
+            PRINT_CHAR => syscalls.print_char(r0 as u8),
+            PRINT_INT => syscalls.print_int(r0 as i32),
+            PRINT_STRING => {
+                let mut string = Vec::new();
+                let mut address = r0;
+                loop {
+                    match self.memory.read_byte(address)? {
+                        0 => break,
+                        c => string.push(c),
+                    }
+                    address = address.wrapping_add(1);
+                }
+                syscalls.print_string(&string);
+            }
+            READ_CHAR => {
+                let c = syscalls.read_char().map_or(u32::MAX, u32::from);
+                self.set_register(0, c);
+            }
+            EXIT => return Ok(Some(Exit::Exited(r0))),
+            number => return Err(Error::UnknownSyscall { number }),
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,597 @@
+use std::sync::Arc;
+
+use super::*;
+
+const MEMORY: usize = 0x1000;
+const LIMIT: usize = 1000;
+
+fn assemble(text: &str) -> Vec<u8> {
+    let assembly = asm::assemble(Arc::from(text));
+    assert!(!assembly.has_errors(), "{:?}", assembly.diagnostics());
+    assembly.into_binary()
+}
+
+fn machine(text: &str) -> Machine {
+    let program = assemble(text);
+    let mut machine = Machine::new(MEMORY);
+    machine.load(&program).unwrap();
+    machine
+}
+
+fn run(text: &str) -> Machine {
+    let mut machine = machine(text);
+    assert_eq!(machine.run(LIMIT), Ok(Exit::Halted));
+    machine
+}
+
+#[test]
+fn add() {
+    let machine = run("ADD r0, r0, #5\nADD r1, r0, #3\nHLT");
+    assert_eq!(machine.register(0), 5);
+    assert_eq!(machine.register(1), 8);
+    assert_eq!(machine.steps(), 3);
+}
+
+#[test]
+fn data_processing() {
+    let machine = run("MOV r0, #12\n\
+        MOV r1, #3\n\
+        SUB r2, r0, r1\n\
+        RSB r3, r1, #1\n\
+        AND r4, r0, #4\n\
+        ORR r5, r0, r1\n\
+        EOR r6, r0, r0\n\
+        BIC r7, r0, #8\n\
+        MVN r8, #0\n\
+        MOV r9, r0, LSR r1\n\
+        ADD r10, r1, r1, LSL #2\n\
+        HLT");
+    assert_eq!(
+        machine.registers()[..11],
+        [12, 3, 9, (-2_i32) as u32, 4, 15, 0, 4, u32::MAX, 1, 15]
+    );
+}
+
+#[test]
+fn carry() {
+    // 0xFFFF_FFFF + 1 carries into the top word
+    let machine = run("MVN r0, #0\n\
+        CMN r0, #1\n\
+        ADC r1, r1, #0\n\
+        CMP r1, #2\n\
+        SBC r2, r1, #0\n\
+        HLT");
+    assert_eq!(machine.register(1), 1);
+    assert_eq!(machine.register(2), 0);
+}
+
+#[test]
+fn tests() {
+    let machine = run("MOV r0, #6\nTST r0, #1\nHLT");
+    assert!(machine.flags().z);
+
+    let machine = run("MOV r0, #6\nTEQ r0, #6\nHLT");
+    assert!(machine.flags().z);
+
+    // the carry out of a rotated immediate is its top bit
+    let machine = run("TST r0, #0x80000000\nHLT");
+    assert_eq!(
+        machine.flags(),
+        Flags {
+            n: false,
+            z: true,
+            c: true,
+            v: false,
+            q: false,
+        }
+    );
+}
+
+#[test]
+fn set_flags() {
+    let machine = run("MOV r0, #1\nSUBS r0, r0, #1\nHLT");
+    assert_eq!(
+        machine.flags(),
+        Flags {
+            n: false,
+            z: true,
+            c: true,
+            v: false,
+            q: false,
+        }
+    );
+
+    // without the suffix the flags are kept
+    let machine = run("CMP r0, #1\nADD r0, r0, #1\nMOV r1, #0\nHLT");
+    assert!(machine.flags().n && !machine.flags().z);
+
+    // the carry out of a move is the carry out of its shift
+    let machine = run("MOV r0, #3\nMOVS r1, r0, LSR #1\nHLT");
+    assert_eq!(machine.register(1), 1);
+    assert!(machine.flags().c);
+}
+
+#[test]
+fn multiply() {
+    let machine = run("MOV r0, #6\n\
+        MOV r1, #7\n\
+        MUL r2, r0, r1\n\
+        MLA r3, r0, r1, r0\n\
+        MLS r4, r0, r0, r1\n\
+        MULS r5, r0, r6\n\
+        HLT");
+    assert_eq!(machine.registers()[2..6], [42, 48, (7 - 36_i32) as u32, 0]);
+    assert!(machine.flags().z && !machine.flags().n);
+}
+
+#[test]
+fn long_multiply() {
+    let machine = run("MVN r0, #0\n\
+        MOV r1, #2\n\
+        UMULL r2, r3, r0, r1\n\
+        SMULL r4, r5, r0, r1\n\
+        MOV r6, #1\n\
+        MOV r7, #1\n\
+        UMLAL r6, r7, r0, r1\n\
+        SMLALS r8, r9, r0, r1\n\
+        HLT");
+    // 0xFFFF_FFFF * 2 and -1 * 2
+    assert_eq!(machine.registers()[2..4], [0xFFFF_FFFE, 1]);
+    assert_eq!(machine.registers()[4..6], [0xFFFF_FFFE, u32::MAX]);
+    assert_eq!(machine.registers()[6..8], [0xFFFF_FFFF, 2]);
+    assert_eq!(machine.registers()[8..10], [0xFFFF_FFFE, u32::MAX]);
+    assert!(machine.flags().n && !machine.flags().z);
+}
+
+#[test]
+fn cmp() {
+    let machine = run("ADD r0, r0, #3\nCMP r0, #3\nHLT");
+    assert_eq!(
+        machine.flags(),
+        Flags {
+            n: false,
+            z: true,
+            c: true,
+            v: false,
+            q: false,
+        }
+    );
+
+    let machine = run("CMP r0, #1\nHLT");
+    assert_eq!(
+        machine.flags(),
+        Flags {
+            n: true,
+            z: false,
+            c: false,
+            v: false,
+            q: false,
+        }
+    );
+}
+
+#[test]
+fn conditions() {
+    use cir::Condition::*;
+
+    let zero = Flags {
+        z: true,
+        c: true,
+        ..Flags::default()
+    };
+    assert!(zero.passes(EQ) && zero.passes(CS) && zero.passes(LS) && zero.passes(GE));
+    assert!(!zero.passes(NE) && !zero.passes(HI) && !zero.passes(GT) && !zero.passes(LT));
+
+    let less = Flags {
+        n: true,
+        ..Flags::default()
+    };
+    assert!(less.passes(LT) && less.passes(LE) && less.passes(MI) && less.passes(CC));
+    assert!(!less.passes(GE) && !less.passes(GT) && !less.passes(PL) && !less.passes(VS));
+
+    assert!(Flags::default().passes(AL));
+}
+
+#[test]
+fn branch_loop() {
+    let machine = run("loop: ADD r0, r0, #1\n\
+        CMP r0, #10\n\
+        BNE loop\n\
+        HLT");
+    assert_eq!(machine.register(0), 10);
+    assert_eq!(machine.steps(), 10 * 3 + 1);
+}
+
+#[test]
+fn count_down() {
+    let machine = run("MOV r0, #5\n\
+        loop: ADD r1, r1, r0\n\
+        SUBS r0, r0, #1\n\
+        BNE loop\n\
+        HLT");
+    assert_eq!(machine.register(0), 0);
+    assert_eq!(machine.register(1), 5 + 4 + 3 + 2 + 1);
+}
+
+#[test]
+fn conditional_execution() {
+    // the larger of r0 and r1
+    let machine = run("MOV r0, #3\n\
+        MOV r1, #7\n\
+        CMP r0, r1\n\
+        MOVGE r2, r0\n\
+        MOVLT r2, r1\n\
+        HLT");
+    assert_eq!(machine.register(2), 7);
+}
+
+#[test]
+fn subroutines() {
+    let machine = run("MOV r0, #3\n\
+        BL double\n\
+        ADR r1, double\n\
+        BLX r1\n\
+        HLT\n\
+        double: ADD r0, r0, r0\n\
+        BX lr");
+    assert_eq!(machine.register(0), 12);
+    // the return address of the last call
+    assert_eq!(machine.register(LR), 0x10);
+}
+
+#[test]
+fn thumb() {
+    let mut exchange = machine("ADR r0, f\nADD r0, r0, #1\nBX r0\nf: HLT");
+    assert_eq!(exchange.run(LIMIT), Err(Error::Thumb { address: 0xC }));
+    let mut call = machine("BLX f\nf: HLT");
+    assert_eq!(call.run(LIMIT), Err(Error::Thumb { address: 0x4 }));
+}
+
+#[test]
+fn status_registers() {
+    let machine = run("CMP r0, #0\n\
+        MRS r1, CPSR\n\
+        MOVS r2, #1\n\
+        MSR CPSR_f, r1\n\
+        MOVEQ r3, #1\n\
+        MSR CPSR_c, #0\n\
+        MOVEQ r4, #1\n\
+        MSR CPSR_f, #0x80000000\n\
+        HLT");
+    // zero and carry, in user mode
+    assert_eq!(machine.register(1), 0x6000_0010);
+    assert_eq!(machine.register(3), 1);
+    // only the flags can be written
+    assert_eq!(machine.register(4), 1);
+    assert_eq!(
+        machine.flags(),
+        Flags {
+            n: true,
+            ..Flags::default()
+        }
+    );
+}
+
+#[test]
+fn no_spsr() {
+    let mut read = machine("MRS r0, SPSR");
+    assert_eq!(read.run(LIMIT), Err(Error::NoSpsr));
+    let mut write = machine("MSR SPSR_f, r0");
+    assert_eq!(write.run(LIMIT), Err(Error::NoSpsr));
+}
+
+#[test]
+fn bit_operations() {
+    let machine = run("LDR r0, =0x12345678\n\
+        CLZ r1, r0\n\
+        REV r2, r0\n\
+        REV16 r3, r0\n\
+        UBFX r4, r0, #4, #8\n\
+        SBFX r5, r2, #0, #8\n\
+        MOV r6, r0\n\
+        BFC r6, #8, #16\n\
+        MOV r7, #0xA\n\
+        MOV r8, r0\n\
+        BFI r8, r7, #28, #4\n\
+        NOP\n\
+        HLT");
+    assert_eq!(machine.register(1), 3);
+    assert_eq!(machine.register(2), 0x7856_3412);
+    assert_eq!(machine.register(3), 0x3412_7856);
+    assert_eq!(machine.register(4), 0x67);
+    assert_eq!(machine.register(5), 0x12);
+    assert_eq!(machine.register(6), 0x1200_0078);
+    assert_eq!(machine.register(8), 0xA234_5678);
+}
+
+#[test]
+fn saturating() {
+    let machine = run("MVN r0, #0x80000000\n\
+        MOV r1, #1\n\
+        QADD r2, r1, r1\n\
+        MRS r3, CPSR\n\
+        QADD r4, r0, r1\n\
+        QSUB r5, r1, r0\n\
+        MVN r6, #0\n\
+        QSUB r7, r0, r6\n\
+        HLT");
+    assert_eq!(machine.register(2), 2);
+    // no saturation yet
+    assert_eq!(machine.register(3) & (1 << 27), 0);
+    assert_eq!(machine.register(4), 0x7FFF_FFFF);
+    assert_eq!(machine.register(5), 0x8000_0002);
+    assert_eq!(machine.register(7), 0x7FFF_FFFF);
+    assert!(machine.flags().q);
+}
+
+#[test]
+fn swaps() {
+    let machine = run("ADR r0, value\n\
+        MOV r1, #5\n\
+        SWP r2, r1, [r0]\n\
+        LDR r3, =0x1FF\n\
+        SWPB r4, r3, [r0]\n\
+        LDR r5, [r0]\n\
+        HLT\n\
+        value: .word 0x11223344");
+    assert_eq!(machine.register(2), 0x1122_3344);
+    assert_eq!(machine.register(4), 5);
+    assert_eq!(machine.register(5), 0xFF);
+}
+
+#[test]
+fn breakpoints() {
+    let mut machine = machine("MOV r0, #1\nBKPT #7\nMOV r0, #2\nHLT");
+    assert_eq!(machine.run(LIMIT), Ok(Exit::Breakpoint(7)));
+    assert_eq!(machine.register(0), 1);
+    // running again continues after it
+    assert_eq!(machine.run(LIMIT), Ok(Exit::Halted));
+    assert_eq!(machine.register(0), 2);
+}
+
+/// Records the output, and reads from a fixed input.
+#[derive(Default)]
+struct Console {
+    input: std::collections::VecDeque<u8>,
+    output: Vec<u8>,
+}
+
+impl Syscalls for Console {
+    fn print_char(&mut self, c: u8) {
+        self.output.push(c);
+    }
+
+    fn read_char(&mut self) -> Option<u8> {
+        self.input.pop_front()
+    }
+}
+
+#[test]
+fn syscalls() {
+    let mut machine = machine(
+        "loop: SVC #3\n\
+        CMN r0, #1\n\
+        BEQ done\n\
+        SVC #0\n\
+        B loop\n\
+        done: MOV r0, #0\n\
+        SUB r0, r0, #42\n\
+        SVC #1\n\
+        ADR r0, text\n\
+        SVC #2\n\
+        MOV r0, #3\n\
+        SWI #4\n\
+        HLT\n\
+        text: .asciz \"!\\n\"",
+    );
+    let mut console = Console {
+        input: b"ok ".iter().copied().collect(),
+        ..Console::default()
+    };
+    assert_eq!(machine.run_with(LIMIT, &mut console), Ok(Exit::Exited(3)));
+    assert_eq!(console.output, b"ok -42!\n");
+}
+
+#[test]
+fn unknown_syscall() {
+    let mut unknown = machine("SVC #5");
+    assert_eq!(unknown.run(LIMIT), Err(Error::UnknownSyscall { number: 5 }));
+    // exiting doesn't need a handler
+    let mut exit = machine("MOV r0, #1\nSVC #4");
+    assert_eq!(exit.run(LIMIT), Ok(Exit::Exited(1)));
+}
+
+#[test]
+fn step_limit() {
+    let mut machine = machine("loop: B loop");
+    assert_eq!(machine.run(LIMIT), Ok(Exit::StepLimit));
+    assert_eq!(machine.register(PC), 0);
+}
+
+#[test]
+fn load() {
+    let mut machine = machine(
+        "ADD r1, r1, #0x100\n\
+        LDR r0, [r1, #4]!\n\
+        ADD r2, r2, #4\n\
+        LDR r3, [r1, r2, LSL #1]!\n\
+        LDM r1, {r4, r5}\n\
+        HLT",
+    );
+    for i in 0..8 {
+        machine
+            .memory_mut()
+            .write_word(0x100 + i * 4, i + 1)
+            .unwrap();
+    }
+    assert_eq!(machine.run(LIMIT), Ok(Exit::Halted));
+    assert_eq!(machine.register(0), 2);
+    assert_eq!(machine.register(1), 0x10C);
+    assert_eq!(machine.register(3), 4);
+    assert_eq!(machine.register(4), 4);
+    assert_eq!(machine.register(5), 5);
+}
+
+#[test]
+fn load_sizes() {
+    let machine = run("ADR r1, table\n\
+        LDRB r0, [r1], #1\n\
+        LDRSB r2, [r1]\n\
+        LDRH r3, [r1, #1]!\n\
+        LDRSH r4, [r1, #-1]\n\
+        LDRD r6, r7, [r1, #2]\n\
+        HLT\n\
+        table: .byte 0x12, 0x80, 0x34, 0xFF\n\
+        .word 0x11223344, 0x55667788");
+    assert_eq!(machine.register(0), 0x12);
+    assert_eq!(machine.register(2), 0xFFFF_FF80);
+    assert_eq!(machine.register(3), 0xFF34);
+    assert_eq!(machine.register(4), 0x3480);
+    // the post-index and pre-index both write back
+    assert_eq!(machine.register(1), 0x1E);
+    assert_eq!(machine.register(6), 0x1122_3344);
+    assert_eq!(machine.register(7), 0x5566_7788);
+}
+
+#[test]
+fn store_sizes() {
+    let mut machine = machine(
+        "ADD r1, r1, #0x100\n\
+        SUB r0, r0, #1\n\
+        STRB r0, [r1], #1\n\
+        STRH r0, [r1, #1]\n\
+        MOV r2, #5\n\
+        MOV r3, #6\n\
+        STRD r2, r3, [r1, #7]\n\
+        HLT",
+    );
+    assert_eq!(machine.run(LIMIT), Ok(Exit::Halted));
+    let memory = machine.memory_mut();
+    assert_eq!(memory.read_word(0x100).unwrap(), 0xFFFF_00FF);
+    assert_eq!(memory.read_word(0x104).unwrap(), 0);
+    assert_eq!(memory.read_word(0x108).unwrap(), 5);
+    assert_eq!(memory.read_word(0x10C).unwrap(), 6);
+    assert_eq!(machine.register(1), 0x101);
+}
+
+#[test]
+fn block_transfers() {
+    let mut machine = machine(
+        "ADD r0, r0, #0x100\n\
+        LDMIB r0, {r1, r2}\n\
+        LDMDA r0!, {r3}\n\
+        ADD r4, r4, #0x200\n\
+        STMDB r4!, {r1-r3}\n\
+        STMIA r4, {r0}\n\
+        HLT",
+    );
+    for i in 0..4 {
+        machine
+            .memory_mut()
+            .write_word(0xFC + i * 4, i + 1)
+            .unwrap();
+    }
+    assert_eq!(machine.run(LIMIT), Ok(Exit::Halted));
+    assert_eq!(machine.register(1), 3);
+    assert_eq!(machine.register(2), 4);
+    assert_eq!(machine.register(3), 2);
+    // the writeback moves past every register
+    assert_eq!(machine.register(0), 0xFC);
+    assert_eq!(machine.register(4), 0x1F4);
+    let memory = machine.memory_mut();
+    assert_eq!(memory.read_word(0x1F4).unwrap(), 0xFC);
+    assert_eq!(memory.read_word(0x1F8).unwrap(), 4);
+    assert_eq!(memory.read_word(0x1FC).unwrap(), 2);
+}
+
+#[test]
+fn push_pop() {
+    let machine = run("MOV sp, #0x400\n\
+        MOV r0, #1\n\
+        MOV r1, #2\n\
+        PUSH {r0, r1}\n\
+        POP {r2}\n\
+        POP {r3}\n\
+        HLT");
+    assert_eq!(machine.register(2), 1);
+    assert_eq!(machine.register(3), 2);
+    assert_eq!(machine.register(13), 0x400);
+}
+
+#[test]
+fn pc_relative() {
+    let machine = run("ADR r0, value\n\
+        LDR r1, value\n\
+        HLT\n\
+        value: HLT");
+    assert_eq!(machine.register(0), 12);
+    assert_eq!(machine.register(1), 0xE100_0070);
+}
+
+#[test]
+fn pc_relative_rotated() {
+    let program = assemble("ADR r0, far\nHLT\n.space 0x2000\nfar: HLT");
+    let mut machine = Machine::new(0x3000);
+    machine.load(&program).unwrap();
+    assert_eq!(machine.run(LIMIT), Ok(Exit::Halted));
+    assert_eq!(machine.register(0), 0x2008);
+}
+
+#[test]
+fn unknown_instruction() {
+    let mut machine = Machine::new(MEMORY);
+    machine.load(&0xF000_0000_u32.to_le_bytes()).unwrap();
+    assert_eq!(
+        machine.run(LIMIT),
+        Err(Error::UnknownInstruction {
+            address: 0,
+            word: 0xF000_0000
+        })
+    );
+}
+
+#[test]
+fn out_of_bounds() {
+    let mut machine = machine("ADD r0, r0, #0x1000\nLDR r1, [r0, #4]!\nHLT");
+    assert_eq!(
+        machine.run(LIMIT),
+        Err(Error::OutOfBounds { address: 0x1004 })
+    );
+}
+
+#[test]
+fn data() {
+    let machine = run("ADR r1, table\n\
+        LDR r0, [r1, #4]!\n\
+        HLT\n\
+        table: .word 10, 20");
+    assert_eq!(machine.register(0), 20);
+}
+
+#[test]
+fn subtracted_offsets() {
+    let machine = run("ADR r1, end\n\
+        LDR r0, [r1, #-4]!\n\
+        ADD r2, r2, #4\n\
+        LDR r3, [r1, -r2]!\n\
+        HLT\n\
+        .word 10, 20\n\
+        end:");
+    assert_eq!(machine.register(0), 20);
+    assert_eq!(machine.register(3), 10);
+}
+
+#[test]
+fn literal_pool() {
+    let machine = run("LDR r0, =0x12345678\n\
+        LDR r1, =value\n\
+        B skip\n\
+        .ltorg\n\
+        skip: LDR r2, =-1\n\
+        HLT\n\
+        value: .byte 1");
+    assert_eq!(machine.register(0), 0x1234_5678);
+    // the address of `value`, after the first pool
+    assert_eq!(machine.register(1), 0x1C);
+    assert_eq!(machine.register(2), 0xFFFF_FFFF);
+}
@@ -2,14 +2,26 @@ use crate::*;
 
 /// Form PC-relative address adds an immediate value to the PC value to form a PC-relative address,
 /// and writes the result to the destination register.
-#[derive(Pattern, Structured)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
 #[name = "ADR"]
-pub struct Adr(Condition, Register<D>, Label);
+#[check(Adr::check)]
+pub struct Adr(pub Condition, pub Register<D>, pub Label);
 
+#[decodable]
 impl Encodable for Adr {
     fn encode(&self) -> Word {
-        let Self(cond, rd, Label(address, _)) = self;
-        let imm12 = Number::<12>(*address);
-        encode![cond | 0 0 1 0 | 1 0 0 | 0 | 1 1 1 1 | rd | imm12]
+        let Self(cond, rd, Label(address, negative)) = self;
+        // labels before the instruction are encoded as a `SUB`
+        let add = !negative;
+        let imm12 = ModImm(*address);
+        encode![cond | 0 0 1 0 | add | negative | 0 | 0 | 1 1 1 1 | rd | imm12]
+    }
+}
+
+impl Adr {
+    fn check(&self) -> Result<(), Error> {
+        // it's an `ADD` or `SUB` from the PC, so the offset is a rotated immediate
+        ModImm::new(self.2 .0)?;
+        Ok(())
     }
 }
@@ -0,0 +1,107 @@
+use crate::*;
+
+/// `Signed Bit Field Extract` extracts a field of bits from a register value,
+/// sign-extends it, and writes it to the destination register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "SBFX"]
+#[check(Sbfx::check)]
+pub struct Sbfx(
+    pub Condition,
+    pub Register<D>,
+    pub Register<N>,
+    pub BitField<Extract>,
+);
+
+#[decodable]
+impl Encodable for Sbfx {
+    fn encode(&self) -> Word {
+        let Self(cond, rd, rn, field) = self;
+        let (widthm1, lsb) = field.split();
+        encode![cond | 0 1 1 1 1 0 1 | widthm1 | rd | lsb | 1 0 1 | rn]
+    }
+}
+
+impl Sbfx {
+    fn check(&self) -> Result<(), Error> {
+        let Self(_, rd, rn, _) = self;
+        no_pc(&[rd.0, rn.0])
+    }
+}
+
+/// `Unsigned Bit Field Extract` extracts a field of bits from a register value,
+/// zero-extends it, and writes it to the destination register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "UBFX"]
+#[check(Ubfx::check)]
+pub struct Ubfx(
+    pub Condition,
+    pub Register<D>,
+    pub Register<N>,
+    pub BitField<Extract>,
+);
+
+#[decodable]
+impl Encodable for Ubfx {
+    fn encode(&self) -> Word {
+        let Self(cond, rd, rn, field) = self;
+        let (widthm1, lsb) = field.split();
+        encode![cond | 0 1 1 1 1 1 1 | widthm1 | rd | lsb | 1 0 1 | rn]
+    }
+}
+
+impl Ubfx {
+    fn check(&self) -> Result<(), Error> {
+        let Self(_, rd, rn, _) = self;
+        no_pc(&[rd.0, rn.0])
+    }
+}
+
+/// `Bit Field Clear` clears a field of bits in the destination register,
+/// leaving the other bits as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "BFC"]
+#[check(Bfc::check)]
+pub struct Bfc(pub Condition, pub Register<D>, pub BitField<Insert>);
+
+#[decodable]
+impl Encodable for Bfc {
+    fn encode(&self) -> Word {
+        let Self(cond, rd, field) = self;
+        let (msb, lsb) = field.split();
+        encode![cond | 0 1 1 1 1 1 0 | msb | rd | lsb | 0 0 1 | 1 1 1 1]
+    }
+}
+
+impl Bfc {
+    fn check(&self) -> Result<(), Error> {
+        no_pc(&[self.1 .0])
+    }
+}
+
+/// `Bit Field Insert` copies the low bits of a register value into a field of bits
+/// in the destination register, leaving the other bits as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "BFI"]
+#[check(Bfi::check)]
+pub struct Bfi(
+    pub Condition,
+    pub Register<D>,
+    pub Register<N>,
+    pub BitField<Insert>,
+);
+
+#[decodable]
+impl Encodable for Bfi {
+    fn encode(&self) -> Word {
+        let Self(cond, rd, rn, field) = self;
+        let (msb, lsb) = field.split();
+        encode![cond | 0 1 1 1 1 1 0 | msb | rd | lsb | 0 0 1 | rn]
+    }
+}
+
+impl Bfi {
+    fn check(&self) -> Result<(), Error> {
+        let Self(_, rd, rn, _) = self;
+        no_pc(&[rd.0, rn.0])
+    }
+}
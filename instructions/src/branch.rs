@@ -1,20 +1,165 @@
 use crate::*;
 
 /// Branch causes a branch to a target address.
-#[derive(Pattern, Structured)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
 #[name = "B"]
-pub struct B(Condition, Label);
+#[check(B::check)]
+pub struct B(pub Condition, pub Label);
 
+#[decodable]
 impl Encodable for B {
     fn encode(&self) -> Word {
-        let Self(cond, Label(address, negative)) = self;
+        let Self(cond, label) = self;
+        let imm24 = BranchOffset::<24>(*label);
+        encode![cond | 1 0 1 | 0 | imm24]
+    }
+}
+
+impl B {
+    fn check(&self) -> Result<(), Error> {
+        BranchOffset::<24>::new(self.1)?;
+        Ok(())
+    }
+}
+
+/// Branch with Link calls a subroutine at a target address,
+/// and writes the address of the next instruction to the LR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "BL"]
+#[check(Bl::check)]
+pub struct Bl(pub Condition, pub Label);
+
+#[decodable]
+impl Encodable for Bl {
+    fn encode(&self) -> Word {
+        let Self(cond, label) = self;
+        let imm24 = BranchOffset::<24>(*label);
+        encode![cond | 1 0 1 | 1 | imm24]
+    }
+}
+
+impl Bl {
+    fn check(&self) -> Result<(), Error> {
+        BranchOffset::<24>::new(self.1)?;
+        Ok(())
+    }
+}
+
+/// Branch and Exchange causes a branch to an address held in a register.
+///
+/// `BX lr` returns from a subroutine called with `BL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "BX"]
+pub struct Bx(pub Condition, pub Register<M>);
+
+#[decodable]
+impl Encodable for Bx {
+    fn encode(&self) -> Word {
+        let Self(cond, rm) = self;
+        encode![cond | 0 0 0 1 0 0 1 0 | 1 1 1 1 | 1 1 1 1 | 1 1 1 1 | 0 0 0 1 | rm]
+    }
+}
+
+/// Branch with Link and Exchange (register) calls a subroutine at an address held in a register,
+/// and writes the address of the next instruction to the LR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "BLX"]
+#[check(BlxReg::check)]
+pub struct BlxReg(pub Condition, pub Register<M>);
+
+#[decodable]
+impl Encodable for BlxReg {
+    fn encode(&self) -> Word {
+        let Self(cond, rm) = self;
+        encode![cond | 0 0 0 1 0 0 1 0 | 1 1 1 1 | 1 1 1 1 | 1 1 1 1 | 0 0 1 1 | rm]
+    }
+}
+
+impl BlxReg {
+    fn check(&self) -> Result<(), Error> {
+        if self.1 .0 == 15 {
+            return Err(Error::ProgramCounter);
+        }
+        Ok(())
+    }
+}
+
+/// Branch with Link and Exchange (immediate) calls a Thumb subroutine at a target address,
+/// and writes the address of the next instruction to the LR.
+///
+/// It is always executed, so it can't have a condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "BLX"]
+#[check(BlxImm::check)]
+pub struct BlxImm(pub Condition, pub Label);
+
+#[decodable]
+impl Encodable for BlxImm {
+    fn encode(&self) -> Word {
+        let Self(_, label) = self;
+        let imm24 = BranchOffset::<24>(*label);
+        // labels are word aligned, so the halfword bit `H` is clear
+        encode![1 1 1 1 | 1 0 1 | 0 | imm24]
+    }
+}
+
+impl BlxImm {
+    fn check(&self) -> Result<(), Error> {
+        if self.0 .0 != cir::Condition::AL {
+            return Err(Error::Conditional);
+        }
+        BranchOffset::<24>::new(self.1)?;
+        Ok(())
+    }
+}
+
+/// The offset to a [`Label`] as a signed number of words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BranchOffset<const BITS: u8>(pub Label);
+
+impl<const BITS: u8> BranchOffset<BITS> {
+    /// The offset to `label`, if it fits in `BITS` signed bits.
+    pub fn new(label: Label) -> Result<Self, Error> {
+        let Label(address, negative) = label;
+        let words = (address / 4) as i32;
+        let offset = if negative { -words } else { words };
+        let reach = 1 << (BITS - 1);
+        if (-reach..reach).contains(&offset) {
+            Ok(Self(label))
+        } else {
+            Err(Error::TooFar { offset, bits: BITS })
+        }
+    }
+}
+
+impl<const BITS: u8> Encodable for BranchOffset<BITS> {
+    fn encode(&self) -> Word {
+        let Self(Label(address, negative)) = self;
         let address = address / 4;
-        let address = if *negative {
+        Word::base(if *negative {
             address.wrapping_neg()
         } else {
             address
+        })
+    }
+
+    fn size(&self) -> u8 {
+        BITS
+    }
+}
+
+impl<const BITS: u8> Decodable for BranchOffset<BITS> {
+    const SIZE: u8 = BITS;
+
+    fn decode(word: Word) -> Option<Self> {
+        let offset = word.get();
+        // sign extend
+        let negative = offset & (1 << (BITS - 1)) != 0;
+        let address = if negative {
+            (offset | (u32::MAX << BITS)).wrapping_neg()
+        } else {
+            offset
         };
-        let imm24 = Number::<24>(address);
-        encode![cond | 1 0 1 | 0 | imm24]
+        Some(Self(Label(address * 4, negative)))
     }
 }
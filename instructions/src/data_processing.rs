@@ -0,0 +1,320 @@
+use crate::*;
+
+/// The operation performed by a data-processing instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    And,
+    Eor,
+    Sub,
+    Rsb,
+    Add,
+    Adc,
+    Sbc,
+    Rsc,
+    Tst,
+    Teq,
+    Cmp,
+    Cmn,
+    Orr,
+    Mov,
+    Bic,
+    Mvn,
+}
+
+/// The last operand of a data-processing instruction, which every form shares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    /// `#imm`
+    Imm(ModImm),
+    /// `rm, shift #imm5`
+    Reg(Register<M>, Shift, Number<5>),
+    /// `rm, shift rs`
+    RegShiftReg(Register<M>, Shift, Register<S>),
+}
+
+/// A data-processing instruction split into its parts,
+/// `rd` is missing from tests like `CMP`, and `rn` from moves like `MOV`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataProcessing {
+    pub opcode: Opcode,
+    /// Whether the flags are updated, always for tests.
+    pub set_flags: bool,
+    pub rd: Option<Register<D>>,
+    pub rn: Option<Register<N>>,
+    pub operand: Operand,
+}
+
+/// Defines the immediate, register and register-shifted register forms of an instruction.
+///
+/// Each form is named after the instruction, `Imm`, `Reg` and `RegShiftReg`.
+/// The kind of instruction picks which registers come before the operand:
+/// - `binary` writes `rd` with the result of `rn` and the operand
+/// - `move` writes `rd` with the operand
+/// - `test` only updates the flags from `rn` and the operand
+///
+/// Binary and move forms only update the flags with an `S` suffix.
+macro_rules! data_processing {
+    ($(
+        $(#[$doc:meta])*
+        $opcode:ident $name:tt [$($bits:tt)*] $kind:ident => $imm:ident, $reg:ident, $rsr:ident;
+    )*) => {
+        $(
+            data_processing!(@$kind $opcode $name [$($bits)*] $imm, $reg, $rsr; $(#[$doc])*);
+        )*
+    };
+    (@binary $opcode:ident $name:tt [$($bits:tt)*] $imm:ident, $reg:ident, $rsr:ident; $(#[$doc:meta])*) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $imm(pub Condition, pub SetFlags, pub Register<D>, pub Register<N>, pub ModImm);
+
+        $(#[$doc])*
+        ///
+        /// The register is optionally shifted by an immediate.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $reg(
+            pub Condition,
+            pub SetFlags,
+            pub Register<D>,
+            pub Register<N>,
+            pub Register<M>,
+            pub Shift,
+            pub Number<5>,
+        );
+
+        $(#[$doc])*
+        ///
+        /// The register is shifted by the bottom byte of another register.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $rsr(
+            pub Condition,
+            pub SetFlags,
+            pub Register<D>,
+            pub Register<N>,
+            pub Register<M>,
+            pub Shift,
+            pub Register<S>,
+        );
+
+        #[decodable]
+        impl Encodable for $imm {
+            fn encode(&self) -> Word {
+                let Self(cond, s, rd, rn, imm12) = self;
+                encode![cond | 0 0 1 | $($bits)* | s | rn | rd | imm12]
+            }
+        }
+
+        #[decodable]
+        impl Encodable for $reg {
+            fn encode(&self) -> Word {
+                let Self(cond, s, rd, rn, rm, stype, imm5) = self;
+                encode![cond | 0 0 0 | $($bits)* | s | rn | rd | imm5 | stype | 0 | rm]
+            }
+        }
+
+        #[decodable]
+        impl Encodable for $rsr {
+            fn encode(&self) -> Word {
+                let Self(cond, s, rd, rn, rm, stype, rs) = self;
+                encode![cond | 0 0 0 | $($bits)* | s | rn | rd | rs | 0 | stype | 1 | rm]
+            }
+        }
+
+        data_processing!(@parts $opcode $imm, $reg, $rsr; |s, rd, rn| s.0, Some(rd), Some(rn));
+    };
+    (@move $opcode:ident $name:tt [$($bits:tt)*] $imm:ident, $reg:ident, $rsr:ident; $(#[$doc:meta])*) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $imm(pub Condition, pub SetFlags, pub Register<D>, pub ModImm);
+
+        $(#[$doc])*
+        ///
+        /// The register is optionally shifted by an immediate.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $reg(
+            pub Condition,
+            pub SetFlags,
+            pub Register<D>,
+            pub Register<M>,
+            pub Shift,
+            pub Number<5>,
+        );
+
+        $(#[$doc])*
+        ///
+        /// The register is shifted by the bottom byte of another register.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $rsr(
+            pub Condition,
+            pub SetFlags,
+            pub Register<D>,
+            pub Register<M>,
+            pub Shift,
+            pub Register<S>,
+        );
+
+        #[decodable]
+        impl Encodable for $imm {
+            fn encode(&self) -> Word {
+                let Self(cond, s, rd, imm12) = self;
+                encode![cond | 0 0 1 | $($bits)* | s | 0 0 0 0 | rd | imm12]
+            }
+        }
+
+        #[decodable]
+        impl Encodable for $reg {
+            fn encode(&self) -> Word {
+                let Self(cond, s, rd, rm, stype, imm5) = self;
+                encode![cond | 0 0 0 | $($bits)* | s | 0 0 0 0 | rd | imm5 | stype | 0 | rm]
+            }
+        }
+
+        #[decodable]
+        impl Encodable for $rsr {
+            fn encode(&self) -> Word {
+                let Self(cond, s, rd, rm, stype, rs) = self;
+                encode![cond | 0 0 0 | $($bits)* | s | 0 0 0 0 | rd | rs | 0 | stype | 1 | rm]
+            }
+        }
+
+        data_processing!(@parts $opcode $imm, $reg, $rsr; |s, rd| s.0, Some(rd), None);
+    };
+    (@test $opcode:ident $name:tt [$($bits:tt)*] $imm:ident, $reg:ident, $rsr:ident; $(#[$doc:meta])*) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $imm(pub Condition, pub Register<N>, pub ModImm);
+
+        $(#[$doc])*
+        ///
+        /// The register is optionally shifted by an immediate.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $reg(
+            pub Condition,
+            pub Register<N>,
+            pub Register<M>,
+            pub Shift,
+            pub Number<5>,
+        );
+
+        $(#[$doc])*
+        ///
+        /// The register is shifted by the bottom byte of another register.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $rsr(
+            pub Condition,
+            pub Register<N>,
+            pub Register<M>,
+            pub Shift,
+            pub Register<S>,
+        );
+
+        // tests always update the flags
+        #[decodable]
+        impl Encodable for $imm {
+            fn encode(&self) -> Word {
+                let Self(cond, rn, imm12) = self;
+                let s = 1;
+                encode![cond | 0 0 1 | $($bits)* | s | rn | 0 0 0 0 | imm12]
+            }
+        }
+
+        #[decodable]
+        impl Encodable for $reg {
+            fn encode(&self) -> Word {
+                let Self(cond, rn, rm, stype, imm5) = self;
+                let s = 1;
+                encode![cond | 0 0 0 | $($bits)* | s | rn | 0 0 0 0 | imm5 | stype | 0 | rm]
+            }
+        }
+
+        #[decodable]
+        impl Encodable for $rsr {
+            fn encode(&self) -> Word {
+                let Self(cond, rn, rm, stype, rs) = self;
+                let s = 1;
+                encode![cond | 0 0 0 | $($bits)* | s | rn | 0 0 0 0 | rs | 0 | stype | 1 | rm]
+            }
+        }
+
+        data_processing!(@parts $opcode $imm, $reg, $rsr; |rn| true, None, Some(rn));
+    };
+    (@parts $opcode:ident $imm:ident, $reg:ident, $rsr:ident; |$($r:pat_param),*| $set_flags:expr, $rd:expr, $rn:expr) => {
+        impl From<$imm> for DataProcessing {
+            fn from($imm(_, $($r,)* imm): $imm) -> Self {
+                let operand = Operand::Imm(imm);
+                Self { opcode: Opcode::$opcode, set_flags: $set_flags, rd: $rd, rn: $rn, operand }
+            }
+        }
+
+        impl From<$reg> for DataProcessing {
+            fn from($reg(_, $($r,)* rm, stype, imm5): $reg) -> Self {
+                let operand = Operand::Reg(rm, stype, imm5);
+                Self { opcode: Opcode::$opcode, set_flags: $set_flags, rd: $rd, rn: $rn, operand }
+            }
+        }
+
+        impl From<$rsr> for DataProcessing {
+            fn from($rsr(_, $($r,)* rm, stype, rs): $rsr) -> Self {
+                let operand = Operand::RegShiftReg(rm, stype, rs);
+                Self { opcode: Opcode::$opcode, set_flags: $set_flags, rd: $rd, rn: $rn, operand }
+            }
+        }
+    };
+}
+
+data_processing! {
+    /// `Bitwise AND` performs a bitwise AND of a register value and an operand,
+    /// and writes the result to the destination register.
+    And "AND" [0 0 0 0] binary => AndImm, AndReg, AndRegShiftReg;
+    /// `Bitwise Exclusive OR` performs a bitwise exclusive OR of a register value and an operand,
+    /// and writes the result to the destination register.
+    Eor "EOR" [0 0 0 1] binary => EorImm, EorReg, EorRegShiftReg;
+    /// `Subtract` subtracts an operand from a register value,
+    /// and writes the result to the destination register.
+    Sub "SUB" [0 0 1 0] binary => SubImm, SubReg, SubRegShiftReg;
+    /// `Reverse Subtract` subtracts a register value from an operand,
+    /// and writes the result to the destination register.
+    Rsb "RSB" [0 0 1 1] binary => RsbImm, RsbReg, RsbRegShiftReg;
+    /// `Add` adds an operand to a register value,
+    /// and writes the result to the destination register.
+    Add "ADD" [0 1 0 0] binary => AddImm, AddReg, AddRegShiftReg;
+    /// `Add with Carry` adds an operand and the carry flag to a register value,
+    /// and writes the result to the destination register.
+    Adc "ADC" [0 1 0 1] binary => AdcImm, AdcReg, AdcRegShiftReg;
+    /// `Subtract with Carry` subtracts an operand and NOT(carry) from a register value,
+    /// and writes the result to the destination register.
+    Sbc "SBC" [0 1 1 0] binary => SbcImm, SbcReg, SbcRegShiftReg;
+    /// `Reverse Subtract with Carry` subtracts a register value and NOT(carry) from an operand,
+    /// and writes the result to the destination register.
+    Rsc "RSC" [0 1 1 1] binary => RscImm, RscReg, RscRegShiftReg;
+    /// `Test` performs a bitwise AND of a register value and an operand.
+    /// It updates the condition flags based on the result, and discards the result.
+    Tst "TST" [1 0 0 0] test => TstImm, TstReg, TstRegShiftReg;
+    /// `Test Equivalence` performs a bitwise exclusive OR of a register value and an operand.
+    /// It updates the condition flags based on the result, and discards the result.
+    Teq "TEQ" [1 0 0 1] test => TeqImm, TeqReg, TeqRegShiftReg;
+    /// `Compare` subtracts an operand from a register value.
+    /// It updates the condition flags based on the result, and discards the result.
+    Cmp "CMP" [1 0 1 0] test => CmpImm, CmpReg, CmpRegShiftReg;
+    /// `Compare Negative` adds an operand to a register value.
+    /// It updates the condition flags based on the result, and discards the result.
+    Cmn "CMN" [1 0 1 1] test => CmnImm, CmnReg, CmnRegShiftReg;
+    /// `Bitwise OR` performs a bitwise inclusive OR of a register value and an operand,
+    /// and writes the result to the destination register.
+    Orr "ORR" [1 1 0 0] binary => OrrImm, OrrReg, OrrRegShiftReg;
+    /// `Move` writes an operand to the destination register.
+    Mov "MOV" [1 1 0 1] move => MovImm, MovReg, MovRegShiftReg;
+    /// `Bitwise Bit Clear` performs a bitwise AND of a register value and the complement of an operand,
+    /// and writes the result to the destination register.
+    Bic "BIC" [1 1 1 0] binary => BicImm, BicReg, BicRegShiftReg;
+    /// `Bitwise NOT` writes the bitwise inverse of an operand to the destination register.
+    Mvn "MVN" [1 1 1 1] move => MvnImm, MvnReg, MvnRegShiftReg;
+}
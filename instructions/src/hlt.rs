@@ -0,0 +1,14 @@
+use crate::*;
+
+/// Halting breakpoint causes the processor to stop executing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "HLT"]
+pub struct Hlt(pub Condition);
+
+#[decodable]
+impl Encodable for Hlt {
+    fn encode(&self) -> Word {
+        let Self(cond) = self;
+        encode![cond | 0 0 0 1 0 0 0 0 | 0 0 0 0 0 0 0 0 0 0 0 0 | 0 1 1 1 | 0 0 0 0]
+    }
+}
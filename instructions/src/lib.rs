@@ -1,14 +1,266 @@
-mod add;
+#[cfg(test)]
+mod tests;
+
 mod adr;
+mod bit_field;
 mod branch;
-mod cmp;
+mod data_processing;
+mod hlt;
+mod load_store;
+mod misc;
+mod multi_load_store;
+mod multiply;
+mod saturating;
+mod status;
+mod svc;
+mod swap;
 
+use dec::{decodable, Decodable};
 use enc::*;
 
 use cir::structured::*;
 use matcher::Pattern;
 
-pub use add::*;
 pub use adr::*;
+pub use bit_field::*;
 pub use branch::*;
-pub use cmp::*;
+pub use data_processing::*;
+pub use hlt::*;
+pub use load_store::*;
+pub use misc::*;
+pub use multi_load_store::*;
+pub use multiply::*;
+pub use saturating::*;
+pub use status::*;
+pub use svc::*;
+pub use swap::*;
+
+/// Defines [`Instruction`], the set of every instruction that can be decoded.
+///
+/// Decoding tries each instruction in order, so more specific encodings,
+/// like `ADR` which is an `ADD` with `PC` as its base, must come first.
+macro_rules! instructions {
+    ($($name:ident),* $(,)?) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Instruction {
+            $($name($name)),*
+        }
+
+        $(
+            impl From<$name> for Instruction {
+                fn from(inst: $name) -> Self {
+                    Self::$name(inst)
+                }
+            }
+        )*
+
+        impl Decodable for Instruction {
+            fn decode(word: Word) -> Option<Self> {
+                $(
+                    if let Some(it) = $name::decode(word) {
+                        return Some(Self::$name(it));
+                    }
+                )*
+                None
+            }
+        }
+
+        impl Instruction {
+            /// The condition the instruction is executed under.
+            ///
+            /// Every instruction holds its [`Condition`] first,
+            /// those that are always executed hold `AL`.
+            pub fn condition(&self) -> cir::Condition {
+                match self {
+                    $(Self::$name(it) => it.0 .0),*
+                }
+            }
+        }
+
+        impl cir::Convert for Instruction {
+            fn to_cir(&self) -> Vec<cir::CIR> {
+                match self {
+                    $(Self::$name(it) => to_cir(it)),*
+                }
+            }
+        }
+    };
+}
+
+instructions! {
+    Adr,
+    AndImm,
+    AndReg,
+    AndRegShiftReg,
+    EorImm,
+    EorReg,
+    EorRegShiftReg,
+    SubImm,
+    SubReg,
+    SubRegShiftReg,
+    RsbImm,
+    RsbReg,
+    RsbRegShiftReg,
+    AddImm,
+    AddReg,
+    AddRegShiftReg,
+    AdcImm,
+    AdcReg,
+    AdcRegShiftReg,
+    SbcImm,
+    SbcReg,
+    SbcRegShiftReg,
+    RscImm,
+    RscReg,
+    RscRegShiftReg,
+    TstImm,
+    TstReg,
+    TstRegShiftReg,
+    TeqImm,
+    TeqReg,
+    TeqRegShiftReg,
+    CmpImm,
+    CmpReg,
+    CmpRegShiftReg,
+    CmnImm,
+    CmnReg,
+    CmnRegShiftReg,
+    OrrImm,
+    OrrReg,
+    OrrRegShiftReg,
+    MovImm,
+    MovReg,
+    MovRegShiftReg,
+    BicImm,
+    BicReg,
+    BicRegShiftReg,
+    MvnImm,
+    MvnReg,
+    MvnRegShiftReg,
+    Mul,
+    Mla,
+    Mls,
+    Umull,
+    Umlal,
+    Smull,
+    Smlal,
+    Qadd,
+    Qsub,
+    Clz,
+    Rev,
+    Rev16,
+    Sbfx,
+    Ubfx,
+    // `BFC` is a `BFI` with the PC as its source
+    Bfc,
+    Bfi,
+    B,
+    Bl,
+    Bx,
+    BlxReg,
+    BlxImm,
+    Mrs,
+    MsrReg,
+    // `NOP` is an `MSR` that writes none of the fields
+    Nop,
+    MsrImm,
+    Svc,
+    Bkpt,
+    Hlt,
+    // `LDR rt, label` is an `LDR` with the PC as its base
+    LdrImmLit,
+    LdrImmOffset,
+    LdrImmPreIndex,
+    LdrImmPostIndex,
+    LdrRegOffset,
+    LdrRegPreIndex,
+    LdrRegPostIndex,
+    StrImmOffset,
+    StrImmPreIndex,
+    StrImmPostIndex,
+    StrRegOffset,
+    StrRegPreIndex,
+    StrRegPostIndex,
+    LdrbImmOffset,
+    LdrbImmPreIndex,
+    LdrbImmPostIndex,
+    LdrbRegOffset,
+    LdrbRegPreIndex,
+    LdrbRegPostIndex,
+    StrbImmOffset,
+    StrbImmPreIndex,
+    StrbImmPostIndex,
+    StrbRegOffset,
+    StrbRegPreIndex,
+    StrbRegPostIndex,
+    LdrhImmOffset,
+    LdrhImmPreIndex,
+    LdrhImmPostIndex,
+    LdrhRegOffset,
+    LdrhRegPreIndex,
+    LdrhRegPostIndex,
+    StrhImmOffset,
+    StrhImmPreIndex,
+    StrhImmPostIndex,
+    StrhRegOffset,
+    StrhRegPreIndex,
+    StrhRegPostIndex,
+    LdrsbImmOffset,
+    LdrsbImmPreIndex,
+    LdrsbImmPostIndex,
+    LdrsbRegOffset,
+    LdrsbRegPreIndex,
+    LdrsbRegPostIndex,
+    LdrshImmOffset,
+    LdrshImmPreIndex,
+    LdrshImmPostIndex,
+    LdrshRegOffset,
+    LdrshRegPreIndex,
+    LdrshRegPostIndex,
+    LdrdImmOffset,
+    LdrdImmPreIndex,
+    LdrdImmPostIndex,
+    LdrdRegOffset,
+    LdrdRegPreIndex,
+    LdrdRegPostIndex,
+    StrdImmOffset,
+    StrdImmPreIndex,
+    StrdImmPostIndex,
+    StrdRegOffset,
+    StrdRegPreIndex,
+    StrdRegPostIndex,
+    // `PUSH` and `POP` are block transfers with `sp!` as their base
+    Push,
+    Pop,
+    Ldm,
+    Ldmib,
+    Ldmda,
+    Ldmdb,
+    Stm,
+    Stmib,
+    Stmda,
+    Stmdb,
+    Swp,
+    Swpb,
+}
+
+/// Checks that none of `registers` are the PC, where its value is unpredictable.
+fn no_pc(registers: &[u32]) -> Result<(), Error> {
+    if registers.contains(&15) {
+        return Err(Error::ProgramCounter);
+    }
+    Ok(())
+}
+
+/// Writes the mnemonic of `T` followed by its arguments.
+fn to_cir<T: matcher::ConstPattern + Structured>(inst: &T) -> Vec<cir::CIR> {
+    let mut cir = T::PATTERN
+        .iter()
+        .map_while(|p| match p {
+            matcher::Pattern::Char(c) => Some(cir::CIR::Char(*c)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    inst.unparse(&mut cir);
+    cir
+}
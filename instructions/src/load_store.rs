@@ -0,0 +1,313 @@
+use crate::*;
+
+/// How a load or store uses its offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indexing {
+    /// `[rn, offset]` uses the offset address, leaving the base as is
+    Offset,
+    /// `[rn, offset]!` uses the offset address, and writes it back to the base
+    PreIndex,
+    /// `[rn], offset` uses the base, then writes the offset address back to it
+    PostIndex,
+}
+
+/// What a load or store transfers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Size {
+    Word,
+    Byte,
+    Half,
+    /// A byte that is sign-extended when loaded
+    SignedByte,
+    /// A halfword that is sign-extended when loaded
+    SignedHalf,
+    /// Two words, to or from a pair of registers
+    Double,
+}
+
+/// The offset of a load or store, added to or subtracted from the base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Displacement {
+    /// `#imm`
+    Imm(u32),
+    /// `rm, shift #imm5`
+    Reg(Register<M>, Shift, Number<5>),
+}
+
+/// A load or store split into its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadStore {
+    pub load: bool,
+    pub size: Size,
+    pub indexing: Indexing,
+    /// The register transferred, the first of a pair for [`Size::Double`].
+    pub rt: Register<T>,
+    pub rn: Register<N>,
+    pub sign: Sign,
+    pub offset: Displacement,
+}
+
+/// Defines every addressing mode of a load or store.
+///
+/// Each form is named after the instruction, `Imm` or `Reg`, then `Offset`, `PreIndex` or `PostIndex`.
+/// The kind of instruction picks how it is encoded:
+/// - `word` transfers words and bytes, with a 12-bit immediate or a shifted register
+/// - `half` transfers halfwords, signed bytes and doublewords, with an 8-bit immediate or a register
+macro_rules! load_store {
+    ($(
+        $(#[$doc:meta])*
+        $kind:ident $name:tt $size:ident $rt:ty [$($bits:tt)*] => $($form:ident),*;
+    )*) => {
+        $(
+            load_store!(@$kind $name $size $rt [$($bits)*] $($form),*; $(#[$doc])*);
+        )*
+    };
+    (@word $name:tt $size:ident $rt:ty [$b:tt $l:tt]
+        $imm_offset:ident, $imm_pre:ident, $imm_post:ident,
+        $reg_offset:ident, $reg_pre:ident, $reg_post:ident;
+        $(#[$doc:meta])*
+    ) => {
+        load_store!(@word_imm $name $size $rt [$b $l] $imm_offset Offset [1 0]; $(#[$doc])*
+            /// The base register is left as is.
+        );
+        load_store!(@word_imm $name $size $rt [$b $l] $imm_pre PreIndex [1 1]; $(#[$doc])*
+            /// The offset address is written back to the base register.
+        );
+        load_store!(@word_imm $name $size $rt [$b $l] $imm_post PostIndex [0 0]; $(#[$doc])*
+            /// The base register is the address, and the offset address is written back to it.
+        );
+        load_store!(@word_reg $name $size $rt [$b $l] $reg_offset Offset [1 0]; $(#[$doc])*
+            /// The base register is left as is.
+        );
+        load_store!(@word_reg $name $size $rt [$b $l] $reg_pre PreIndex [1 1]; $(#[$doc])*
+            /// The offset address is written back to the base register.
+        );
+        load_store!(@word_reg $name $size $rt [$b $l] $reg_post PostIndex [0 0]; $(#[$doc])*
+            /// The base register is the address, and the offset address is written back to it.
+        );
+    };
+    (@half $name:tt $size:ident $rt:ty [$l:tt $s:tt $h:tt]
+        $imm_offset:ident, $imm_pre:ident, $imm_post:ident,
+        $reg_offset:ident, $reg_pre:ident, $reg_post:ident;
+        $(#[$doc:meta])*
+    ) => {
+        load_store!(@half_imm $name $size $rt [$l $s $h] $imm_offset Offset [1 0]; $(#[$doc])*
+            /// The base register is left as is.
+        );
+        load_store!(@half_imm $name $size $rt [$l $s $h] $imm_pre PreIndex [1 1]; $(#[$doc])*
+            /// The offset address is written back to the base register.
+        );
+        load_store!(@half_imm $name $size $rt [$l $s $h] $imm_post PostIndex [0 0]; $(#[$doc])*
+            /// The base register is the address, and the offset address is written back to it.
+        );
+        load_store!(@half_reg $name $size $rt [$l $s $h] $reg_offset Offset [1 0]; $(#[$doc])*
+            /// The base register is left as is.
+        );
+        load_store!(@half_reg $name $size $rt [$l $s $h] $reg_pre PreIndex [1 1]; $(#[$doc])*
+            /// The offset address is written back to the base register.
+        );
+        load_store!(@half_reg $name $size $rt [$l $s $h] $reg_post PostIndex [0 0]; $(#[$doc])*
+            /// The base register is the address, and the offset address is written back to it.
+        );
+    };
+    (@word_imm $name:tt $size:ident $rt:ty [$b:tt $l:tt] $ty:ident $mode:ident [$p:tt $w:tt]; $(#[$doc:meta])*) => {
+        $(#[$doc])*
+        ///
+        /// The offset is an immediate, added to or subtracted from the base.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $ty(
+            pub Condition,
+            pub $rt,
+            pub Address<$mode>,
+            pub Register<N>,
+            pub Sign,
+            pub Number<12>,
+        );
+
+        #[decodable]
+        impl Encodable for $ty {
+            fn encode(&self) -> Word {
+                let Self(cond, rt, _, rn, u, imm12) = self;
+                let p = $p;
+                let w = $w;
+                encode![cond | 0 1 0 | p | u | $b | w | $l | rn | rt | imm12]
+            }
+        }
+
+        load_store!(@parts $ty $name $size $mode |imm12| Displacement::Imm(imm12.0));
+    };
+    (@word_reg $name:tt $size:ident $rt:ty [$b:tt $l:tt] $ty:ident $mode:ident [$p:tt $w:tt]; $(#[$doc:meta])*) => {
+        $(#[$doc])*
+        ///
+        /// The offset is a register optionally shifted by an immediate,
+        /// added to or subtracted from the base.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $ty(
+            pub Condition,
+            pub $rt,
+            pub Address<$mode>,
+            pub Register<N>,
+            pub Sign,
+            pub Register<M>,
+            pub Shift,
+            pub Number<5>,
+        );
+
+        #[decodable]
+        impl Encodable for $ty {
+            fn encode(&self) -> Word {
+                let Self(cond, rt, _, rn, u, rm, stype, imm5) = self;
+                let p = $p;
+                let w = $w;
+                encode![cond | 0 1 1 | p | u | $b | w | $l | rn | rt | imm5 | stype | 0 | rm]
+            }
+        }
+
+        load_store!(@parts $ty $name $size $mode |rm, stype, imm5| Displacement::Reg(rm, stype, imm5));
+    };
+    (@half_imm $name:tt $size:ident $rt:ty [$l:tt $s:tt $h:tt] $ty:ident $mode:ident [$p:tt $w:tt]; $(#[$doc:meta])*) => {
+        $(#[$doc])*
+        ///
+        /// The offset is an immediate, added to or subtracted from the base.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $ty(
+            pub Condition,
+            pub $rt,
+            pub Address<$mode>,
+            pub Register<N>,
+            pub Sign,
+            pub Number<8>,
+        );
+
+        #[decodable]
+        impl Encodable for $ty {
+            fn encode(&self) -> Word {
+                let Self(cond, rt, _, rn, u, imm8) = self;
+                let (imm4h, imm4l) = imm8.split();
+                let p = $p;
+                let w = $w;
+                encode![cond | 0 0 0 | p | u | 1 | w | $l | rn | rt | imm4h | 1 | $s | $h | 1 | imm4l]
+            }
+        }
+
+        load_store!(@parts $ty $name $size $mode |imm8| Displacement::Imm(imm8.0));
+    };
+    (@half_reg $name:tt $size:ident $rt:ty [$l:tt $s:tt $h:tt] $ty:ident $mode:ident [$p:tt $w:tt]; $(#[$doc:meta])*) => {
+        $(#[$doc])*
+        ///
+        /// The offset is a register, added to or subtracted from the base.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+        #[name = $name]
+        pub struct $ty(
+            pub Condition,
+            pub $rt,
+            pub Address<$mode>,
+            pub Register<N>,
+            pub Sign,
+            pub Register<M>,
+        );
+
+        #[decodable]
+        impl Encodable for $ty {
+            fn encode(&self) -> Word {
+                let Self(cond, rt, _, rn, u, rm) = self;
+                let p = $p;
+                let w = $w;
+                encode![cond | 0 0 0 | p | u | 0 | w | $l | rn | rt | 0 0 0 0 | 1 | $s | $h | 1 | rm]
+            }
+        }
+
+        load_store!(@parts $ty $name $size $mode |rm| {
+            Displacement::Reg(rm, Shift(cir::Shift::LSL), Number(0))
+        });
+    };
+    (@parts $ty:ident $name:tt $size:ident $mode:ident |$($offset:ident),*| $displacement:expr) => {
+        impl From<$ty> for LoadStore {
+            fn from($ty(_, rt, _, rn, sign, $($offset),*): $ty) -> Self {
+                Self {
+                    // `LDRD` and `STRD` don't use the L bit, so go by the name
+                    load: $name.starts_with("LDR"),
+                    size: Size::$size,
+                    indexing: Indexing::$mode,
+                    rt: Register::new(rt.0),
+                    rn,
+                    sign,
+                    offset: $displacement,
+                }
+            }
+        }
+    };
+}
+
+load_store! {
+    /// `Load Register` calculates an address from a base register value and an offset,
+    /// loads a word from memory, and writes it to a register.
+    word "LDR" Word Register<T> [0 1] =>
+        LdrImmOffset, LdrImmPreIndex, LdrImmPostIndex, LdrRegOffset, LdrRegPreIndex, LdrRegPostIndex;
+    /// `Store Register` calculates an address from a base register value and an offset,
+    /// and stores a word from a register to memory.
+    word "STR" Word Register<T> [0 0] =>
+        StrImmOffset, StrImmPreIndex, StrImmPostIndex, StrRegOffset, StrRegPreIndex, StrRegPostIndex;
+    /// `Load Register Byte` calculates an address from a base register value and an offset,
+    /// loads a byte from memory, zero-extends it to form a 32-bit word, and writes it to a register.
+    word "LDRB" Byte Register<T> [1 1] =>
+        LdrbImmOffset, LdrbImmPreIndex, LdrbImmPostIndex, LdrbRegOffset, LdrbRegPreIndex, LdrbRegPostIndex;
+    /// `Store Register Byte` calculates an address from a base register value and an offset,
+    /// and stores a byte from a register to memory.
+    word "STRB" Byte Register<T> [1 0] =>
+        StrbImmOffset, StrbImmPreIndex, StrbImmPostIndex, StrbRegOffset, StrbRegPreIndex, StrbRegPostIndex;
+    /// `Load Register Halfword` calculates an address from a base register value and an offset,
+    /// loads a halfword from memory, zero-extends it to form a 32-bit word, and writes it to a register.
+    half "LDRH" Half Register<T> [1 0 1] =>
+        LdrhImmOffset, LdrhImmPreIndex, LdrhImmPostIndex, LdrhRegOffset, LdrhRegPreIndex, LdrhRegPostIndex;
+    /// `Store Register Halfword` calculates an address from a base register value and an offset,
+    /// and stores a halfword from a register to memory.
+    half "STRH" Half Register<T> [0 0 1] =>
+        StrhImmOffset, StrhImmPreIndex, StrhImmPostIndex, StrhRegOffset, StrhRegPreIndex, StrhRegPostIndex;
+    /// `Load Register Signed Byte` calculates an address from a base register value and an offset,
+    /// loads a byte from memory, sign-extends it to form a 32-bit word, and writes it to a register.
+    half "LDRSB" SignedByte Register<T> [1 1 0] =>
+        LdrsbImmOffset, LdrsbImmPreIndex, LdrsbImmPostIndex, LdrsbRegOffset, LdrsbRegPreIndex, LdrsbRegPostIndex;
+    /// `Load Register Signed Halfword` calculates an address from a base register value and an offset,
+    /// loads a halfword from memory, sign-extends it to form a 32-bit word, and writes it to a register.
+    half "LDRSH" SignedHalf Register<T> [1 1 1] =>
+        LdrshImmOffset, LdrshImmPreIndex, LdrshImmPostIndex, LdrshRegOffset, LdrshRegPreIndex, LdrshRegPostIndex;
+    /// `Load Register Dual` calculates an address from a base register value and an offset,
+    /// loads two words from memory, and writes them to a pair of registers.
+    half "LDRD" Double RegisterPair [0 1 0] =>
+        LdrdImmOffset, LdrdImmPreIndex, LdrdImmPostIndex, LdrdRegOffset, LdrdRegPreIndex, LdrdRegPostIndex;
+    /// `Store Register Dual` calculates an address from a base register value and an offset,
+    /// and stores two words from a pair of registers to memory.
+    half "STRD" Double RegisterPair [0 1 1] =>
+        StrdImmOffset, StrdImmPreIndex, StrdImmPostIndex, StrdRegOffset, StrdRegPreIndex, StrdRegPostIndex;
+}
+
+/// Load Register (literal) calculates an address from the PC value and an immediate offset,
+/// loads a word from memory, and writes it to a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "LDR"]
+#[check(LdrImmLit::check)]
+pub struct LdrImmLit(pub Condition, pub Register<T>, pub Label);
+
+#[decodable]
+impl Encodable for LdrImmLit {
+    fn encode(&self) -> Word {
+        let Self(cond, rt, Label(address, negative)) = self;
+        let u = !negative;
+        let p = 1;
+        let w = 0;
+        let imm12 = Number::<12>(*address);
+        encode![cond | 0 1 0 | p | u | 0 | w | 1 | 1 1 1 1 | rt | imm12]
+    }
+}
+
+impl LdrImmLit {
+    fn check(&self) -> Result<(), Error> {
+        // the label is reached by adding or subtracting `imm12`
+        Number::<12>::new(self.2 .0)?;
+        Ok(())
+    }
+}
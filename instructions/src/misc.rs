@@ -0,0 +1,107 @@
+use crate::*;
+
+/// `Count Leading Zeros` counts the zero bits above the highest set bit of a register value,
+/// and writes the count to the destination register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "CLZ"]
+#[check(Clz::check)]
+pub struct Clz(pub Condition, pub Register<D>, pub Register<M>);
+
+#[decodable]
+impl Encodable for Clz {
+    fn encode(&self) -> Word {
+        let Self(cond, rd, rm) = self;
+        encode![cond | 0 0 0 1 0 1 1 0 | 1 1 1 1 | rd | 1 1 1 1 | 0 0 0 1 | rm]
+    }
+}
+
+impl Clz {
+    fn check(&self) -> Result<(), Error> {
+        let Self(_, rd, rm) = self;
+        no_pc(&[rd.0, rm.0])
+    }
+}
+
+/// `Byte-Reverse Word` reverses the order of the bytes in a register value,
+/// and writes the result to the destination register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "REV"]
+#[check(Rev::check)]
+pub struct Rev(pub Condition, pub Register<D>, pub Register<M>);
+
+#[decodable]
+impl Encodable for Rev {
+    fn encode(&self) -> Word {
+        let Self(cond, rd, rm) = self;
+        encode![cond | 0 1 1 0 1 0 1 1 | 1 1 1 1 | rd | 1 1 1 1 | 0 0 1 1 | rm]
+    }
+}
+
+impl Rev {
+    fn check(&self) -> Result<(), Error> {
+        let Self(_, rd, rm) = self;
+        no_pc(&[rd.0, rm.0])
+    }
+}
+
+/// `Byte-Reverse Packed Halfword` reverses the order of the bytes in each halfword of a register value,
+/// and writes the result to the destination register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "REV16"]
+#[check(Rev16::check)]
+pub struct Rev16(pub Condition, pub Register<D>, pub Register<M>);
+
+#[decodable]
+impl Encodable for Rev16 {
+    fn encode(&self) -> Word {
+        let Self(cond, rd, rm) = self;
+        encode![cond | 0 1 1 0 1 0 1 1 | 1 1 1 1 | rd | 1 1 1 1 | 1 0 1 1 | rm]
+    }
+}
+
+impl Rev16 {
+    fn check(&self) -> Result<(), Error> {
+        let Self(_, rd, rm) = self;
+        no_pc(&[rd.0, rm.0])
+    }
+}
+
+/// `No Operation` does nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "NOP"]
+pub struct Nop(pub Condition);
+
+#[decodable]
+impl Encodable for Nop {
+    fn encode(&self) -> Word {
+        let Self(cond) = self;
+        encode![cond | 0 0 1 1 0 0 1 0 | 0 0 0 0 | 1 1 1 1 | 0 0 0 0 0 0 0 0 0 0 0 0]
+    }
+}
+
+/// `Breakpoint` stops the program for a debugger,
+/// which can read the number it holds.
+///
+/// It is always executed, so it can't have a condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "BKPT"]
+#[check(Bkpt::check)]
+pub struct Bkpt(pub Condition, pub Number<16>);
+
+#[decodable]
+impl Encodable for Bkpt {
+    fn encode(&self) -> Word {
+        let Self(_, imm16) = self;
+        let (imm12, imm4) = imm16.split();
+        encode![1 1 1 0 | 0 0 0 1 0 0 1 0 | imm12 | 0 1 1 1 | imm4]
+    }
+}
+
+impl Bkpt {
+    fn check(&self) -> Result<(), Error> {
+        if self.0 .0 != cir::Condition::AL {
+            return Err(Error::Conditional);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use crate::*;
+
+/// Defines a block transfer, which loads or stores a list of registers
+/// at consecutive memory locations from the address in a base register.
+///
+/// `P` picks whether the base is moved before or after each transfer,
+/// and `U` whether it goes up or down.
+macro_rules! block_transfer {
+    ($(
+        $(#[$doc:meta])*
+        $ty:ident $name:tt [$p:tt $u:tt $l:tt];
+    )*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+            #[name = $name]
+            pub struct $ty(pub Condition, pub Register<N>, pub Writeback, pub RegisterList);
+
+            #[decodable]
+            impl Encodable for $ty {
+                fn encode(&self) -> Word {
+                    let Self(cond, rn, w, register_list) = self;
+                    encode![cond | 1 0 0 | $p | $u | 0 | w | $l | rn | register_list]
+                }
+            }
+
+            impl From<$ty> for BlockTransfer {
+                fn from($ty(_, rn, w, register_list): $ty) -> Self {
+                    Self {
+                        load: matches!($l, 1),
+                        before: matches!($p, 1),
+                        increment: matches!($u, 1),
+                        rn,
+                        writeback: w.0,
+                        registers: register_list.0,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+/// A block transfer split into its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTransfer {
+    pub load: bool,
+    /// Whether the address moves before each register is transferred.
+    pub before: bool,
+    /// Whether the address goes up from the base.
+    pub increment: bool,
+    pub rn: Register<N>,
+    pub writeback: bool,
+    /// A bit for each register transferred, lowest at the lowest address.
+    pub registers: u16,
+}
+
+block_transfer! {
+    /// `Load Multiple` loads multiple registers from consecutive memory locations
+    /// using an address from a base register, which goes up after each load.
+    Ldm "LDM" [0 1 1];
+    /// `Load Multiple Increment Before` loads multiple registers from consecutive memory locations
+    /// using an address from a base register, which goes up before each load.
+    Ldmib "LDMIB" [1 1 1];
+    /// `Load Multiple Decrement After` loads multiple registers from consecutive memory locations
+    /// ending at the address in a base register.
+    Ldmda "LDMDA" [0 0 1];
+    /// `Load Multiple Decrement Before` loads multiple registers from consecutive memory locations
+    /// ending just below the address in a base register.
+    Ldmdb "LDMDB" [1 0 1];
+    /// `Store Multiple` stores multiple registers to consecutive memory locations
+    /// using an address from a base register, which goes up after each store.
+    Stm "STM" [0 1 0];
+    /// `Store Multiple Increment Before` stores multiple registers to consecutive memory locations
+    /// using an address from a base register, which goes up before each store.
+    Stmib "STMIB" [1 1 0];
+    /// `Store Multiple Decrement After` stores multiple registers to consecutive memory locations
+    /// ending at the address in a base register.
+    Stmda "STMDA" [0 0 0];
+    /// `Store Multiple Decrement Before` stores multiple registers to consecutive memory locations
+    /// ending just below the address in a base register.
+    Stmdb "STMDB" [1 0 0];
+}
+
+/// `Push Multiple Registers` stores multiple registers to the stack,
+/// and moves the stack pointer down past them.
+///
+/// It is an `STMDB sp!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "PUSH"]
+pub struct Push(pub Condition, pub RegisterList);
+
+#[decodable]
+impl Encodable for Push {
+    fn encode(&self) -> Word {
+        let Self(cond, register_list) = self;
+        encode![cond | 1 0 0 1 0 0 1 0 | 1 1 0 1 | register_list]
+    }
+}
+
+impl From<Push> for BlockTransfer {
+    fn from(Push(cond, register_list): Push) -> Self {
+        Stmdb(cond, Register::new(13), Writeback(true), register_list).into()
+    }
+}
+
+/// `Pop Multiple Registers` loads multiple registers from the stack,
+/// and moves the stack pointer up past them.
+///
+/// It is an `LDM sp!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "POP"]
+pub struct Pop(pub Condition, pub RegisterList);
+
+#[decodable]
+impl Encodable for Pop {
+    fn encode(&self) -> Word {
+        let Self(cond, register_list) = self;
+        encode![cond | 1 0 0 0 1 0 1 1 | 1 1 0 1 | register_list]
+    }
+}
+
+impl From<Pop> for BlockTransfer {
+    fn from(Pop(cond, register_list): Pop) -> Self {
+        Ldm(cond, Register::new(13), Writeback(true), register_list).into()
+    }
+}
@@ -0,0 +1,160 @@
+use crate::*;
+
+/// `Multiply` multiplies two register values,
+/// and writes the least significant 32 bits of the result to the destination register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "MUL"]
+#[check(Mul::check)]
+pub struct Mul(
+    pub Condition,
+    pub SetFlags,
+    pub Register<D>,
+    pub Register<N>,
+    pub Register<M>,
+);
+
+#[decodable]
+impl Encodable for Mul {
+    fn encode(&self) -> Word {
+        let Self(cond, s, rd, rn, rm) = self;
+        encode![cond | 0 0 0 0 0 0 0 | s | rd | 0 0 0 0 | rm | 1 0 0 1 | rn]
+    }
+}
+
+impl Mul {
+    fn check(&self) -> Result<(), Error> {
+        let Self(_, _, rd, rn, rm) = self;
+        no_pc(&[rd.0, rn.0, rm.0])
+    }
+}
+
+/// `Multiply Accumulate` multiplies two register values, and adds a third register value.
+/// The least significant 32 bits of the result are written to the destination register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "MLA"]
+#[check(Mla::check)]
+pub struct Mla(
+    pub Condition,
+    pub SetFlags,
+    pub Register<D>,
+    pub Register<N>,
+    pub Register<M>,
+    pub Register<A>,
+);
+
+#[decodable]
+impl Encodable for Mla {
+    fn encode(&self) -> Word {
+        let Self(cond, s, rd, rn, rm, ra) = self;
+        encode![cond | 0 0 0 0 0 0 1 | s | rd | ra | rm | 1 0 0 1 | rn]
+    }
+}
+
+impl Mla {
+    fn check(&self) -> Result<(), Error> {
+        let Self(_, _, rd, rn, rm, ra) = self;
+        no_pc(&[rd.0, rn.0, rm.0, ra.0])
+    }
+}
+
+/// `Multiply and Subtract` multiplies two register values, and subtracts the product from a third register value.
+/// The least significant 32 bits of the result are written to the destination register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "MLS"]
+#[check(Mls::check)]
+pub struct Mls(
+    pub Condition,
+    pub Register<D>,
+    pub Register<N>,
+    pub Register<M>,
+    pub Register<A>,
+);
+
+#[decodable]
+impl Encodable for Mls {
+    fn encode(&self) -> Word {
+        let Self(cond, rd, rn, rm, ra) = self;
+        encode![cond | 0 0 0 0 0 1 1 0 | rd | ra | rm | 1 0 0 1 | rn]
+    }
+}
+
+impl Mls {
+    fn check(&self) -> Result<(), Error> {
+        let Self(_, rd, rn, rm, ra) = self;
+        no_pc(&[rd.0, rn.0, rm.0, ra.0])
+    }
+}
+
+/// Defines a long multiply, which writes a 64-bit result to `RdHi:RdLo`.
+macro_rules! long_multiply {
+    ($(
+        $(#[$doc:meta])*
+        $ty:ident $name:tt [$($bits:tt)*];
+    )*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+            #[name = $name]
+            #[check(check_long)]
+            pub struct $ty(
+                pub Condition,
+                pub SetFlags,
+                pub Register<DLo>,
+                pub Register<DHi>,
+                pub Register<N>,
+                pub Register<M>,
+            );
+
+            #[decodable]
+            impl Encodable for $ty {
+                fn encode(&self) -> Word {
+                    let Self(cond, s, rdlo, rdhi, rn, rm) = self;
+                    encode![cond | 0 0 0 0 1 | $($bits)* | s | rdhi | rdlo | rm | 1 0 0 1 | rn]
+                }
+            }
+
+            impl From<$ty> for LongMultiply {
+                fn from($ty(_, s, rdlo, rdhi, rn, rm): $ty) -> Self {
+                    Self { set_flags: s.0, rdlo, rdhi, rn, rm }
+                }
+            }
+        )*
+    };
+}
+
+/// A long multiply split into its registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongMultiply {
+    pub set_flags: bool,
+    pub rdlo: Register<DLo>,
+    pub rdhi: Register<DHi>,
+    pub rn: Register<N>,
+    pub rm: Register<M>,
+}
+
+long_multiply! {
+    /// `Unsigned Multiply Long` multiplies two unsigned register values,
+    /// and writes the 64-bit result to two registers.
+    Umull "UMULL" [0 0];
+    /// `Unsigned Multiply Accumulate Long` multiplies two unsigned register values,
+    /// and adds the 64-bit result to the value held in two registers.
+    Umlal "UMLAL" [0 1];
+    /// `Signed Multiply Long` multiplies two signed register values,
+    /// and writes the 64-bit result to two registers.
+    Smull "SMULL" [1 0];
+    /// `Signed Multiply Accumulate Long` multiplies two signed register values,
+    /// and adds the 64-bit result to the value held in two registers.
+    Smlal "SMLAL" [1 1];
+}
+
+/// Both halves of the result can't be written to the same register.
+fn check_long<T: Copy + Into<LongMultiply>>(inst: &T) -> Result<(), Error> {
+    let LongMultiply {
+        rdlo, rdhi, rn, rm, ..
+    } = (*inst).into();
+    no_pc(&[rdlo.0, rdhi.0, rn.0, rm.0])?;
+    if rdlo.0 == rdhi.0 {
+        return Err(Error::SameRegister(rdlo.0));
+    }
+    Ok(())
+}
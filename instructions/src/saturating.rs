@@ -0,0 +1,42 @@
+use crate::*;
+
+/// Defines a saturating instruction, which clamps its signed result to the range of a word
+/// and sets the `Q` flag when it does.
+macro_rules! saturating {
+    ($(
+        $(#[$doc:meta])*
+        $ty:ident $name:tt [$op:tt $op2:tt];
+    )*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+            #[name = $name]
+            #[check($ty::check)]
+            pub struct $ty(pub Condition, pub Register<D>, pub Register<M>, pub Register<N>);
+
+            #[decodable]
+            impl Encodable for $ty {
+                fn encode(&self) -> Word {
+                    let Self(cond, rd, rm, rn) = self;
+                    encode![cond | 0 0 0 1 0 | $op | $op2 | 0 | rn | rd | 0 0 0 0 | 0 1 0 1 | rm]
+                }
+            }
+
+            impl $ty {
+                fn check(&self) -> Result<(), Error> {
+                    let Self(_, rd, rm, rn) = self;
+                    no_pc(&[rd.0, rm.0, rn.0])
+                }
+            }
+        )*
+    };
+}
+
+saturating! {
+    /// `Saturating Add` adds two register values,
+    /// and writes the saturated result to the destination register.
+    Qadd "QADD" [0 0];
+    /// `Saturating Subtract` subtracts one register value from another,
+    /// and writes the saturated result to the destination register.
+    Qsub "QSUB" [0 1];
+}
@@ -0,0 +1,62 @@
+use crate::*;
+
+/// Move to Register from Status Register reads the `CPSR` or `SPSR` into a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "MRS"]
+#[check(Mrs::check)]
+pub struct Mrs(pub Condition, pub Register<D>, pub Psr);
+
+#[decodable]
+impl Encodable for Mrs {
+    fn encode(&self) -> Word {
+        let Self(cond, rd, psr) = self;
+        encode![cond | 0 0 0 1 0 | psr | 0 0 | 1 1 1 1 | rd | 0 0 0 0 0 0 0 0 0 0 0 0]
+    }
+}
+
+impl Mrs {
+    fn check(&self) -> Result<(), Error> {
+        if self.1 .0 == 15 {
+            return Err(Error::ProgramCounter);
+        }
+        Ok(())
+    }
+}
+
+/// Move to Status Register from Register writes a register to fields of the `CPSR` or `SPSR`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "MSR"]
+#[check(MsrReg::check)]
+pub struct MsrReg(pub Condition, pub PsrFields, pub Register<N>);
+
+#[decodable]
+impl Encodable for MsrReg {
+    fn encode(&self) -> Word {
+        let Self(cond, fields, rn) = self;
+        let (psr, mask) = fields.split();
+        encode![cond | 0 0 0 1 0 | psr | 1 0 | mask | 1 1 1 1 | 0 0 0 0 0 0 0 0 | rn]
+    }
+}
+
+impl MsrReg {
+    fn check(&self) -> Result<(), Error> {
+        if self.2 .0 == 15 {
+            return Err(Error::ProgramCounter);
+        }
+        Ok(())
+    }
+}
+
+/// Move to Status Register (immediate) writes an immediate value to fields of the `CPSR` or `SPSR`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "MSR"]
+pub struct MsrImm(pub Condition, pub PsrFields, pub ModImm);
+
+#[decodable]
+impl Encodable for MsrImm {
+    fn encode(&self) -> Word {
+        let Self(cond, fields, imm12) = self;
+        let (psr, mask) = fields.split();
+        encode![cond | 0 0 1 1 0 | psr | 1 0 | mask | 1 1 1 1 | imm12]
+    }
+}
@@ -0,0 +1,17 @@
+use crate::*;
+
+/// Supervisor Call asks whatever is running the program for a service,
+/// picked by the number it holds.
+///
+/// It is also written as `SWI`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+#[name = "SVC"]
+pub struct Svc(pub Condition, pub Number<24>);
+
+#[decodable]
+impl Encodable for Svc {
+    fn encode(&self) -> Word {
+        let Self(cond, imm24) = self;
+        encode![cond | 1 1 1 1 | imm24]
+    }
+}
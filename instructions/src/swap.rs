@@ -0,0 +1,46 @@
+use crate::*;
+
+/// Defines a swap, which loads from memory and stores to the same address in one go.
+macro_rules! swap {
+    ($(
+        $(#[$doc:meta])*
+        $ty:ident $name:tt [$b:tt];
+    )*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Pattern, Structured)]
+            #[name = $name]
+            #[check($ty::check)]
+            pub struct $ty(pub Condition, pub Register<T>, pub Register<T2>, pub BaseAddress);
+
+            #[decodable]
+            impl Encodable for $ty {
+                fn encode(&self) -> Word {
+                    let Self(cond, rt, rt2, rn) = self;
+                    encode![cond | 0 0 0 1 0 | $b | 0 0 | rn | rt | 0 0 0 0 | 1 0 0 1 | rt2]
+                }
+            }
+
+            impl $ty {
+                fn check(&self) -> Result<(), Error> {
+                    let Self(_, rt, rt2, rn) = self;
+                    no_pc(&[rt.0, rt2.0, rn.0])?;
+                    if rn.0 == rt.0 || rn.0 == rt2.0 {
+                        return Err(Error::SameRegister(rn.0));
+                    }
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+swap! {
+    /// `Swap Word` loads a word from the address in a base register,
+    /// stores a register value to the same address, and writes the loaded word to a register.
+    Swp "SWP" [0];
+    /// `Swap Byte` loads a byte from the address in a base register,
+    /// stores the low byte of a register value to the same address,
+    /// and writes the zero-extended loaded byte to a register.
+    Swpb "SWPB" [1];
+}
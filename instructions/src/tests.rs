@@ -0,0 +1,377 @@
+use cir::{Convert, CIR};
+
+use super::*;
+
+/// Parses the first instruction in `text` as `T`.
+fn parse<T: Structured>(text: &str) -> T {
+    let hand = hand::parse(text.into());
+    let cir = hand.to_cir();
+    let args = &cir[1..];
+    let end = args
+        .iter()
+        .position(|c| matches!(c, CIR::Instruction(_)))
+        .unwrap_or(args.len());
+    let args = &args[..end];
+    parse_from_args::<T>(args)
+        .or_else(|_| {
+            // like `asm`, a register without a shift is shifted by `LSL #0`
+            let shifted = [args, &[CIR::Shift(cir::Shift::LSL), CIR::Number(0)]].concat();
+            parse_from_args::<T>(&shifted)
+        })
+        .expect("Correct pattern")
+}
+
+mod macros {
+    macro_rules! test_decoding {
+        ($name:ident of $ty:ty; $hand:expr) => {
+            #[test]
+            fn $name() {
+                let inst = parse::<$ty>($hand);
+                let word = inst.encode();
+                assert_eq!(<$ty>::decode(word), Some(inst));
+                assert_eq!(Instruction::decode(word), Some(Instruction::from(inst)));
+            }
+        };
+    }
+
+    macro_rules! test_encoding {
+        ($name:ident of $ty:ty; $hand:expr => $expected:expr) => {
+            #[test]
+            fn $name() {
+                let inst = parse::<$ty>($hand);
+                let word = inst.encode();
+                assert_eq!(word, Word::base($expected), "{:#010X}", word.get());
+                assert_eq!(Instruction::decode(word), Some(Instruction::from(inst)));
+            }
+        };
+    }
+
+    pub(crate) use test_decoding;
+    pub(crate) use test_encoding;
+}
+
+macros::test_decoding!(add_imm of AddImm; "ADD r0, r1, #1");
+macros::test_decoding!(add_imm_rotated of AddImm; "ADD r0, r1, #0x3FC");
+macros::test_decoding!(add_imm_wrapped of AddImm; "ADD r0, r1, #0xF000000F");
+macros::test_decoding!(adr_forward of Adr; "ADR r0, next\nADD r0, r0, #0\nnext: ADD r0, r0, #0");
+macros::test_decoding!(adr_backward of Adr; "ADR r0, next\nnext: ADD r0, r0, #0");
+macros::test_decoding!(b_forward of B; "B end\nADD r0, r0, #0\nend: B end");
+macros::test_decoding!(b_backward of B; "loop: B loop");
+macros::test_decoding!(bl_forward of Bl; "BL end\nADD r0, r0, #0\nend: B end");
+macros::test_decoding!(bl_backward of Bl; "loop: BL loop");
+macros::test_decoding!(cmp_imm of CmpImm; "CMP r0, #100");
+macros::test_decoding!(cmp_imm_rotated of CmpImm; "CMP r0, #0xFF000000");
+macros::test_decoding!(hlt of Hlt; "HLT");
+macros::test_decoding!(ldr_imm_preidx of LdrImmPreIndex; "LDR r0, [r1, #1]!");
+macros::test_decoding!(ldr_imm_preidx_sub of LdrImmPreIndex; "LDR r0, [r1, #-4]!");
+macros::test_decoding!(ldr_reg_preidx of LdrRegPreIndex; "LDR r0, [r1, r2, LSL #1]!");
+macros::test_decoding!(ldr_reg_preidx_sub of LdrRegPreIndex; "LDR r0, [r1, -r2, LSL #1]!");
+macros::test_decoding!(ldr_imm_lit of LdrImmLit; "label: LDR r0, label");
+macros::test_decoding!(ldm of Ldm; "LDM r0, {r1, r4-r6}");
+
+macros::test_encoding!(and_imm of AndImm; "AND r0, r1, #0xFF" => 0xE201_00FF);
+macros::test_encoding!(eor_reg of EorReg; "EOR r0, r0, r0" => 0xE020_0000);
+macros::test_encoding!(sub_imm of SubImm; "SUB r0, r1, #1" => 0xE241_0001);
+macros::test_encoding!(rsb_imm of RsbImm; "RSB r0, r1, #0" => 0xE261_0000);
+macros::test_encoding!(adc_reg of AdcReg; "ADC r0, r1, r2" => 0xE0A1_0002);
+macros::test_encoding!(sbc_reg of SbcReg; "SBC r0, r1, r2, LSL #2" => 0xE0C1_0102);
+macros::test_encoding!(rsc_reg_shift_reg of RscRegShiftReg; "RSC r0, r1, r2, ASR r3" => 0xE0E1_0352);
+macros::test_encoding!(tst_imm of TstImm; "TST r0, #1" => 0xE310_0001);
+macros::test_encoding!(teq_reg of TeqReg; "TEQ r0, r1" => 0xE130_0001);
+macros::test_encoding!(cmp_reg of CmpReg; "CMP r0, r1, LSR #1" => 0xE150_00A1);
+macros::test_encoding!(cmp_reg_shift_reg of CmpRegShiftReg; "CMP r0, r1, LSL r2" => 0xE150_0211);
+macros::test_encoding!(cmn_imm of CmnImm; "CMN r0, #1" => 0xE370_0001);
+macros::test_encoding!(orr_reg of OrrReg; "ORR r0, r1, r2" => 0xE181_0002);
+macros::test_encoding!(mov_imm of MovImm; "MOV r0, #1" => 0xE3A0_0001);
+macros::test_encoding!(mov_reg of MovReg; "MOV r0, r1, LSL #3" => 0xE1A0_0181);
+macros::test_encoding!(mov_reg_rrx of MovReg; "MOV r0, r1, RRX" => 0xE1A0_0061);
+macros::test_encoding!(bic_imm of BicImm; "BIC r0, r0, #0xFF000000" => 0xE3C0_04FF);
+macros::test_encoding!(mvn_imm of MvnImm; "MVN r0, #0" => 0xE3E0_0000);
+macros::test_encoding!(mvn_reg_shift_reg of MvnRegShiftReg; "MVN r0, r1, ROR r2" => 0xE1E0_0271);
+macros::test_encoding!(adds_imm of AddImm; "ADDS r0, r0, #1" => 0xE290_0001);
+macros::test_encoding!(subs_reg of SubReg; "SUBS r0, r1, r2" => 0xE051_0002);
+macros::test_encoding!(movs_reg of MovReg; "MOVS r0, r1" => 0xE1B0_0001);
+macros::test_encoding!(subseq_imm of SubImm; "SUBSEQ r0, r0, #1" => 0x0250_0001);
+macros::test_encoding!(subeqs_imm of SubImm; "SUBEQS r0, r0, #1" => 0x0250_0001);
+macros::test_encoding!(mul of Mul; "MUL r0, r1, r2" => 0xE000_0291);
+macros::test_encoding!(muls of Mul; "MULS r0, r1, r2" => 0xE010_0291);
+macros::test_encoding!(mla of Mla; "MLA r0, r1, r2, r3" => 0xE020_3291);
+macros::test_encoding!(mls of Mls; "MLS r0, r1, r2, r3" => 0xE060_3291);
+macros::test_encoding!(umull of Umull; "UMULL r0, r1, r2, r3" => 0xE081_0392);
+macros::test_encoding!(umlal of Umlal; "UMLAL r0, r1, r2, r3" => 0xE0A1_0392);
+macros::test_encoding!(smull of Smull; "SMULL r0, r1, r2, r3" => 0xE0C1_0392);
+macros::test_encoding!(smlals of Smlal; "SMLALS r0, r1, r2, r3" => 0xE0F1_0392);
+macros::test_encoding!(str_imm_offset of StrImmOffset; "STR r0, [r1, #4]" => 0xE581_0004);
+macros::test_encoding!(ldr_reg_offset of LdrRegOffset; "LDR r0, [r1, r2, LSL #2]" => 0xE791_0102);
+macros::test_encoding!(ldrb_imm_postidx of LdrbImmPostIndex; "LDRB r0, [r1], #1" => 0xE4D1_0001);
+macros::test_encoding!(strb_reg_postidx of StrbRegPostIndex; "STRB r0, [r1], -r2" => 0xE641_0002);
+macros::test_encoding!(ldrh_imm_offset of LdrhImmOffset; "LDRH r0, [r1, #0x12]" => 0xE1D1_01B2);
+macros::test_encoding!(strh_reg_preidx of StrhRegPreIndex; "STRH r0, [r1, r2]!" => 0xE1A1_00B2);
+macros::test_encoding!(ldrsb_imm_offset of LdrsbImmOffset; "LDRSB r0, [r1, #-1]" => 0xE151_00D1);
+macros::test_encoding!(ldrsh_no_offset of LdrshImmOffset; "LDRSH r0, [r1]" => 0xE1D1_00F0);
+macros::test_encoding!(ldrd_imm_offset of LdrdImmOffset; "LDRD r0, r1, [r2, #8]" => 0xE1C2_00D8);
+macros::test_encoding!(strd_imm_postidx of StrdImmPostIndex; "STRD r2, r3, [r4], #-8" => 0xE044_20F8);
+macros::test_encoding!(ldmib of Ldmib; "LDMIB r0, {r1}" => 0xE990_0002);
+macros::test_encoding!(ldmda_writeback of Ldmda; "LDMDA r0!, {r1-r3}" => 0xE830_000E);
+macros::test_encoding!(ldmdb of Ldmdb; "LDMDB r1, {r0}" => 0xE911_0001);
+macros::test_encoding!(stm of Stm; "STM r1, {r0}" => 0xE881_0001);
+macros::test_encoding!(stmib_writeback of Stmib; "STMIB r1!, {r0, pc}" => 0xE9A1_8001);
+macros::test_encoding!(stmda of Stmda; "STMDA r1, {r0}" => 0xE801_0001);
+macros::test_encoding!(stmdb_writeback of Stmdb; "STMDB r0!, {r1, r2}" => 0xE920_0006);
+macros::test_encoding!(push of Push; "PUSH {r0-r4, lr}" => 0xE92D_401F);
+macros::test_encoding!(pop of Pop; "POP {r0-r4, pc}" => 0xE8BD_801F);
+macros::test_encoding!(bl of Bl; "BL next\nnext: HLT" => 0xEBFF_FFFF);
+macros::test_encoding!(blne of Bl; "BLNE next\nHLT\nnext: HLT" => 0x1B00_0000);
+macros::test_encoding!(bx_lr of Bx; "BX lr" => 0xE12F_FF1E);
+macros::test_encoding!(blx_reg of BlxReg; "BLX r3" => 0xE12F_FF33);
+macros::test_encoding!(blx_imm of BlxImm; "BLX next\nnext: HLT" => 0xFAFF_FFFF);
+macros::test_encoding!(svc of Svc; "SVC #0x123456" => 0xEF12_3456);
+macros::test_encoding!(svcne of Svc; "SVCNE #4" => 0x1F00_0004);
+macros::test_encoding!(mrs_cpsr of Mrs; "MRS r0, CPSR" => 0xE10F_0000);
+macros::test_encoding!(mrs_spsr of Mrs; "MRSNE r1, SPSR" => 0x114F_1000);
+macros::test_encoding!(msr_flags of MsrReg; "MSR CPSR_f, r0" => 0xE128_F000);
+macros::test_encoding!(msr_fields of MsrReg; "MSR SPSR_fc, r2" => 0xE169_F002);
+macros::test_encoding!(msr_whole of MsrReg; "MSR CPSR, r3" => 0xE129_F003);
+macros::test_encoding!(msr_imm of MsrImm; "MSR CPSR_f, #0xF0000000" => 0xE328_F20F);
+macros::test_encoding!(clz of Clz; "CLZ r0, r1" => 0xE16F_0F11);
+macros::test_encoding!(rev of Rev; "REV r2, r3" => 0xE6BF_2F33);
+macros::test_encoding!(rev16 of Rev16; "REV16 r2, r3" => 0xE6BF_2FB3);
+macros::test_encoding!(ubfx of Ubfx; "UBFX r0, r1, #4, #8" => 0xE7E7_0251);
+macros::test_encoding!(sbfx of Sbfx; "SBFX r0, r1, #0, #16" => 0xE7AF_0051);
+macros::test_encoding!(bfi of Bfi; "BFI r0, r1, #8, #4" => 0xE7CB_0411);
+macros::test_encoding!(bfc of Bfc; "BFC r0, #0, #32" => 0xE7DF_001F);
+macros::test_encoding!(qadd of Qadd; "QADD r0, r1, r2" => 0xE102_0051);
+macros::test_encoding!(qsub of Qsub; "QSUBNE r0, r1, r2" => 0x1122_0051);
+macros::test_encoding!(nop of Nop; "NOP" => 0xE320_F000);
+macros::test_encoding!(nopeq of Nop; "NOPEQ" => 0x0320_F000);
+macros::test_encoding!(bkpt of Bkpt; "BKPT #0x1234" => 0xE121_2374);
+macros::test_encoding!(swp of Swp; "SWP r0, r1, [r2]" => 0xE102_0091);
+macros::test_encoding!(swpb of Swpb; "SWPB r0, r1, [r2]" => 0xE142_0091);
+
+#[test]
+fn add_reg() {
+    use cir::{Condition::AL, Shift::LSL};
+
+    let cir = [
+        CIR::Condition(AL),
+        CIR::Register(0),
+        CIR::Register(1),
+        CIR::Register(2),
+        CIR::Shift(LSL),
+        CIR::Number(3),
+    ];
+    let inst = parse_from_args::<AddReg>(&cir).unwrap();
+    assert_eq!(AddReg::decode(inst.encode()), Some(inst));
+    assert_eq!(Instruction::from(inst).to_cir()[3..], cir);
+}
+
+#[test]
+fn add_reg_shift_reg() {
+    use cir::{Condition::AL, Shift::ASR};
+
+    let cir = [
+        CIR::Condition(AL),
+        CIR::Register(0),
+        CIR::Register(1),
+        CIR::Register(2),
+        CIR::Shift(ASR),
+        CIR::Register(3),
+    ];
+    let inst = parse_from_args::<AddRegShiftReg>(&cir).unwrap();
+    assert_eq!(AddRegShiftReg::decode(inst.encode()), Some(inst));
+    assert_eq!(Instruction::from(inst).to_cir()[3..], cir);
+}
+
+#[test]
+fn adr_before_add() {
+    // `ADR` is an `ADD` to the PC, which is more specific
+    let adr = parse::<Adr>("ADR r0, next\nADD r0, r0, #0\nnext: ADD r0, r0, #0");
+    assert!(matches!(
+        Instruction::decode(adr.encode()),
+        Some(Instruction::Adr(_))
+    ));
+}
+
+#[test]
+fn adr_rotated() {
+    use cir::Condition::AL;
+
+    let adr = |offset| {
+        parse_from_args::<Adr>(&[CIR::Condition(AL), CIR::Register(0), CIR::Label(offset)])
+    };
+    // the offset is an immediate of an `ADD` or `SUB`
+    let far = adr(0x2000).unwrap();
+    assert_eq!(far.encode(), Word::base(0xE28F_0A02));
+    assert_eq!(Adr::decode(far.encode()), Some(far));
+    assert_eq!(adr(-0x2000).unwrap().encode(), Word::base(0xE24F_0A02));
+    assert_eq!(adr(0x1004), Err(Error::NotRotated(0x1004)));
+}
+
+#[test]
+fn branch_reach() {
+    use cir::Condition::AL;
+
+    let b = |offset| parse_from_args::<B>(&[CIR::Condition(AL), CIR::Label(offset)]);
+    assert!(b(0x1FF_FFFC).is_ok());
+    assert!(b(-0x200_0000).is_ok());
+    assert_eq!(
+        b(0x200_0000),
+        Err(Error::TooFar {
+            offset: 0x80_0000,
+            bits: 24
+        })
+    );
+
+    let blx = |offset| parse_from_args::<BlxImm>(&[CIR::Condition(AL), CIR::Label(offset)]);
+    assert!(blx(-0x200_0000).is_ok());
+    assert_eq!(
+        blx(-0x200_0004),
+        Err(Error::TooFar {
+            offset: -0x80_0001,
+            bits: 24
+        })
+    );
+}
+
+#[test]
+fn push_before_stmdb() {
+    // `PUSH` is an `STMDB` to `sp!`, which is more specific
+    let stmdb = parse::<Stmdb>("STMFD sp!, {r0, lr}");
+    assert_eq!(stmdb.encode(), parse::<Push>("PUSH {r0, lr}").encode());
+    assert!(matches!(
+        Instruction::decode(stmdb.encode()),
+        Some(Instruction::Push(_))
+    ));
+    // without writeback it stays an `STMDB`
+    let stmdb = parse::<Stmdb>("STMDB sp, {r0, lr}");
+    assert!(matches!(
+        Instruction::decode(stmdb.encode()),
+        Some(Instruction::Stmdb(_))
+    ));
+}
+
+#[test]
+fn exchanges() {
+    use cir::Condition::{AL, EQ};
+
+    assert_eq!(
+        parse_from_args::<BlxImm>(&[CIR::Condition(EQ), CIR::Label(0)]),
+        Err(Error::Conditional)
+    );
+    assert_eq!(
+        parse_from_args::<BlxReg>(&[CIR::Condition(AL), CIR::Register(15)]),
+        Err(Error::ProgramCounter)
+    );
+}
+
+#[test]
+fn bit_fields() {
+    use cir::Condition::AL;
+
+    let ubfx = |lsb, width| {
+        parse_from_args::<Ubfx>(&[
+            CIR::Condition(AL),
+            CIR::Register(0),
+            CIR::Register(1),
+            CIR::Number(lsb),
+            CIR::Number(width),
+        ])
+    };
+    assert!(ubfx(31, 1).is_ok());
+    assert_eq!(ubfx(28, 8), Err(Error::BitField { lsb: 28, width: 8 }));
+    assert_eq!(ubfx(4, 0), Err(Error::BitField { lsb: 4, width: 0 }));
+    assert_eq!(ubfx(32, 1), Err(Error::BitField { lsb: 32, width: 1 }));
+}
+
+#[test]
+fn swap_registers() {
+    use cir::{Condition::AL, Sign::Plus};
+
+    let swp = |rt, rt2, rn, offset| {
+        parse_from_args::<Swp>(&[
+            CIR::Condition(AL),
+            CIR::Register(rt),
+            CIR::Register(rt2),
+            CIR::OffsetAddress,
+            CIR::Register(rn),
+            CIR::Sign(Plus),
+            CIR::Number(offset),
+        ])
+    };
+    assert!(swp(0, 0, 1, 0).is_ok());
+    assert_eq!(swp(0, 1, 1, 0), Err(Error::SameRegister(1)));
+    assert_eq!(swp(0, 1, 15, 0), Err(Error::ProgramCounter));
+    assert_eq!(swp(0, 1, 2, 4), Err(Error::Offset));
+}
+
+#[test]
+fn multiply_registers() {
+    use cir::Condition::AL;
+
+    let args = |registers: [u32; 4]| {
+        [CIR::Condition(AL)]
+            .into_iter()
+            .chain(registers.map(CIR::Register))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        parse_from_args::<Umull>(&args([0, 0, 1, 2])),
+        Err(Error::SameRegister(0))
+    );
+    assert_eq!(
+        parse_from_args::<Mla>(&args([0, 1, 15, 2])),
+        Err(Error::ProgramCounter)
+    );
+    assert!(parse_from_args::<Smlal>(&args([0, 1, 0, 1])).is_ok());
+}
+
+#[test]
+fn register_pairs() {
+    use cir::{Condition::AL, Sign::Plus};
+
+    let args = |first, second| {
+        [
+            CIR::Condition(AL),
+            CIR::Register(first),
+            CIR::Register(second),
+            CIR::OffsetAddress,
+            CIR::Register(4),
+            CIR::Sign(Plus),
+            CIR::Number(0),
+        ]
+    };
+    assert_eq!(
+        parse_from_args::<LdrdImmOffset>(&args(1, 2)),
+        Err(Error::NotPair(1, 2))
+    );
+    assert_eq!(
+        parse_from_args::<LdrdImmOffset>(&args(0, 2)),
+        Err(Error::NotPair(0, 2))
+    );
+    // `r14, r15` would load the PC
+    assert_eq!(
+        parse_from_args::<StrdImmOffset>(&args(14, 15)),
+        Err(Error::NotPair(14, 15))
+    );
+    assert!(parse_from_args::<LdrdImmOffset>(&args(2, 3)).is_ok());
+}
+
+#[test]
+fn conditions() {
+    use cir::Condition::{AL, NE};
+
+    let condition = |word| Instruction::decode(word).map(|inst| inst.condition());
+    let cmp = [CIR::Condition(NE), CIR::Register(0), CIR::Number(100)];
+    let cmp = parse_from_args::<CmpImm>(&cmp).unwrap();
+    assert_eq!(condition(cmp.encode()), Some(NE));
+    assert_eq!(condition(parse::<Hlt>("HLT").encode()), Some(AL));
+    // always executed, without a condition field
+    assert_eq!(condition(parse::<Bkpt>("BKPT #1").encode()), Some(AL));
+}
+
+#[test]
+fn unknown() {
+    assert_eq!(Instruction::decode(Word::base(0xF000_0000)), None);
+    // `RSB r0, pc, #0` has both of the `ADD` and `SUB` bits set
+    assert_eq!(Adr::decode(Word::base(0xE26F_0000)), None);
+}